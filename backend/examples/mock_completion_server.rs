@@ -0,0 +1,22 @@
+//! Local stand-in for an OpenAI-compatible completion server
+//!
+//! Answers every chat completion with a valid insights object, so the AI
+//! insights path can be exercised without an API key or network access.
+//!
+//! Run with: cargo run --features mock-llm --example mock_completion_server [addr]
+//! Then start the backend with OPENAI_BASE_URL=http://<addr>/v1
+
+use speedtest_pro_backend::services::mock_completion::MockCompletionServer;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    
+    let addr = std::env::args().nth(1).unwrap_or_else(|| "127.0.0.1:8089".to_string());
+    let server = MockCompletionServer::bind(&addr, Vec::new()).await?;
+    
+    println!("\n🧪 Mock completion server listening on {}", server.local_addr()?);
+    println!("💡 Point the backend at it with OPENAI_BASE_URL={}\n", server.base_url()?);
+    
+    server.run().await
+}
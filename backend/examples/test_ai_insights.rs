@@ -1,9 +1,9 @@
-/// Test program for AI-Powered Network Insights
-/// 
-/// This demonstrates how GPT-4 analyzes network test results
-/// and provides intelligent recommendations
-///
-/// Run with: cargo run --example test_ai_insights
+//! Test program for AI-Powered Network Insights
+//! 
+//! This demonstrates how GPT-4 analyzes network test results
+//! and provides intelligent recommendations
+//!
+//! Run with: cargo run --example test_ai_insights
 
 use speedtest_pro_backend::models::TestResult;
 use speedtest_pro_backend::services::loaded_latency::{LoadedLatencyResult, BufferbloatGrade};
@@ -48,6 +48,8 @@ async fn main() {
         protocol: "TCP".to_string(),
         client_ip: "1.2.3.4".to_string(),
         test_duration_ms: 10000,
+        device_id: None,
+        measured: true,
     };
     
     let loaded_latency = LoadedLatencyResult {
@@ -55,33 +57,49 @@ async fn main() {
         idle_max_ms: 18.0,
         idle_avg_ms: 15.0,
         idle_median_ms: 15.0,
-        idle_samples: vec![15.0],
+        idle_samples: Vec::new(),
+        idle_jitter: Default::default(),
+        idle_quality: Default::default(),
+        idle_packet_loss: None,
+        idle_one_way: None,
         
         download_min_ms: 80.0,
         download_max_ms: 105.0,
         download_avg_ms: 95.0,
         download_median_ms: 93.0,
-        download_samples: vec![95.0],
+        download_samples: Vec::new(),
+        download_jitter: Default::default(),
+        download_quality: Default::default(),
+        download_packet_loss: None,
+        download_one_way: None,
         
         upload_min_ms: 160.0,
         upload_max_ms: 200.0,
         upload_avg_ms: 180.0,
         upload_median_ms: 175.0,
-        upload_samples: vec![180.0],
+        upload_samples: Vec::new(),
+        upload_jitter: Default::default(),
+        upload_quality: Default::default(),
+        upload_packet_loss: None,
+        upload_one_way: None,
+        clock_model: None,
         
         bufferbloat_download_ms: 80.0,
         bufferbloat_upload_ms: 165.0,
         bufferbloat_download_ratio: 5.33,
         bufferbloat_upload_ratio: 11.0,
         bufferbloat_grade: BufferbloatGrade::C,
+        bufferbloat_rationale: None,
         
         idle_rpm: 4000.0,
         download_rpm: 632.0,
         upload_rpm: 333.0,
+        download_responsiveness: None,
+        upload_responsiveness: None,
     };
     
     // Calculate AIM scores
-    let aim_scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency);
+    let aim_scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
     
     println!("Raw Metrics:");
     println!("  Download: {:.1} Mbps", test_result.download_mbps);
@@ -92,10 +110,10 @@ async fn main() {
     println!("  Bufferbloat Grade: {}\n", loaded_latency.bufferbloat_grade.as_str());
     
     println!("AIM Scores:");
-    println!("  Gaming: {:.0}/100", aim_scores.gaming.score);
-    println!("  Streaming: {:.0}/100", aim_scores.streaming.score);
-    println!("  Video Conferencing: {:.0}/100", aim_scores.video_conferencing.score);
-    println!("  General Browsing: {:.0}/100\n", aim_scores.general_browsing.score);
+    for score in &aim_scores.use_cases {
+        println!("  {}: {:.0}/100", score.label, score.score);
+    }
+    println!();
     
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     println!("🤖 Analyzing with AI...\n");
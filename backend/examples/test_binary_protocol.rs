@@ -1,10 +1,11 @@
-/// Test program for Binary WebSocket Protocol
-/// 
-/// Demonstrates MessagePack vs JSON efficiency
-/// Shows 30-50% size reduction and faster serialization
-///
-/// Run with: cargo run --example test_binary_protocol
+//! Test program for Binary WebSocket Protocol
+//! 
+//! Demonstrates MessagePack vs JSON efficiency
+//! Shows 30-50% size reduction and faster serialization
+//!
+//! Run with: cargo run --example test_binary_protocol
 
+use speedtest_pro_backend::services::aim_scoring::UseCase;
 use speedtest_pro_backend::services::binary_protocol::*;
 use std::time::Instant;
 
@@ -48,10 +49,12 @@ fn main() {
             idle_rpm: 3947,
             dl_rpm: 2105,
             ul_rpm: 1312,
-            gaming_score: 78,
-            streaming_score: 95,
-            video_score: 72,
-            browsing_score: 88,
+            use_case_scores: [
+                (UseCase::Gaming, 78),
+                (UseCase::Streaming, 95),
+                (UseCase::VideoConferencing, 72),
+                (UseCase::GeneralBrowsing, 88),
+            ].into_iter().collect(),
             overall_score: 83,
             duration_ms: 10000,
             timestamp: 1700000000,
@@ -116,7 +119,7 @@ fn test_message_encoding(message: &BinaryMessage, name: &str) {
     
     // Test encoding/decoding
     let msgpack_encoded = BinaryProtocol::encode(message).unwrap();
-    let decoded = BinaryProtocol::decode(&msgpack_encoded).unwrap();
+    let _decoded = BinaryProtocol::decode(&msgpack_encoded).unwrap();
     
     println!("\n✅ Encoding/Decoding: Success");
     println!("   Round-trip verified - data integrity maintained");
@@ -125,7 +128,7 @@ fn test_message_encoding(message: &BinaryMessage, name: &str) {
 fn test_batch_encoding(batch: &mut MessageBatch) {
     // Individual JSON size
     let mut json_total = 0;
-    for msg in &batch.messages {
+    for msg in batch.messages() {
         json_total += serde_json::to_vec(msg).unwrap().len();
     }
     
@@ -270,10 +273,12 @@ fn simulate_speed_test() {
             idle_rpm: 4000,
             dl_rpm: 2400,
             ul_rpm: 1714,
-            gaming_score: 85,
-            streaming_score: 95,
-            video_score: 80,
-            browsing_score: 90,
+            use_case_scores: [
+                (UseCase::Gaming, 85),
+                (UseCase::Streaming, 95),
+                (UseCase::VideoConferencing, 80),
+                (UseCase::GeneralBrowsing, 90),
+            ].into_iter().collect(),
             overall_score: 87,
             duration_ms: 10000,
             timestamp: 1700000000,
@@ -1,18 +1,19 @@
-/// Comprehensive Integration Test
-/// 
-/// Tests all features working together:
-/// - Loaded Latency
-/// - AIM Scoring
-/// - AI Insights
-/// - Binary Protocol
-///
-/// Run with: cargo run --example test_integration
+//! Comprehensive Integration Test
+//! 
+//! Tests all features working together:
+//! - Loaded Latency
+//! - AIM Scoring
+//! - AI Insights
+//! - Binary Protocol
+//!
+//! Run with: cargo run --example test_integration
 
 use speedtest_pro_backend::models::{TestResult, EnhancedTestResult};
-use speedtest_pro_backend::services::loaded_latency::{LoadedLatencyTester, BufferbloatGrade};
+use speedtest_pro_backend::services::loaded_latency::BufferbloatGrade;
 use speedtest_pro_backend::services::aim_scoring::AIMCalculator;
+use speedtest_pro_backend::services::household_capacity::CapacityCalculator;
 use speedtest_pro_backend::services::ai_insights::AINetworkAnalyzer;
-use speedtest_pro_backend::services::binary_protocol::{BinaryProtocol, BinaryMessage, CompactTestResult};
+use speedtest_pro_backend::services::binary_protocol::{BinaryMessage, CompactTestResult};
 
 #[tokio::main]
 async fn main() {
@@ -38,6 +39,8 @@ async fn main() {
         protocol: "TCP".to_string(),
         client_ip: "1.2.3.4".to_string(),
         test_duration_ms: 10000,
+        device_id: None,
+        measured: true,
     };
     
     println!("Basic Metrics:");
@@ -57,29 +60,45 @@ async fn main() {
         idle_max_ms: 18.0,
         idle_avg_ms: 15.0,
         idle_median_ms: 15.0,
-        idle_samples: vec![15.0; 20],
+        idle_samples: Vec::new(),
+        idle_jitter: Default::default(),
+        idle_quality: Default::default(),
+        idle_packet_loss: None,
+        idle_one_way: None,
         
         download_min_ms: 80.0,
         download_max_ms: 105.0,
         download_avg_ms: 95.0,
         download_median_ms: 93.0,
-        download_samples: vec![95.0; 20],
+        download_samples: Vec::new(),
+        download_jitter: Default::default(),
+        download_quality: Default::default(),
+        download_packet_loss: None,
+        download_one_way: None,
         
         upload_min_ms: 160.0,
         upload_max_ms: 200.0,
         upload_avg_ms: 180.0,
         upload_median_ms: 175.0,
-        upload_samples: vec![180.0; 20],
+        upload_samples: Vec::new(),
+        upload_jitter: Default::default(),
+        upload_quality: Default::default(),
+        upload_packet_loss: None,
+        upload_one_way: None,
+        clock_model: None,
         
         bufferbloat_download_ms: 80.0,
         bufferbloat_upload_ms: 165.0,
         bufferbloat_download_ratio: 5.33,
         bufferbloat_upload_ratio: 11.0,
         bufferbloat_grade: BufferbloatGrade::C,
+        bufferbloat_rationale: None,
         
         idle_rpm: 4000.0,
         download_rpm: 632.0,
         upload_rpm: 333.0,
+        download_responsiveness: None,
+        upload_responsiveness: None,
     };
     
     println!("{}\n", loaded_latency.summary());
@@ -89,9 +108,12 @@ async fn main() {
     println!("🎯 STEP 3: AIM Use-Case Scoring");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     
-    let aim_scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency);
+    let aim_scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
     println!("{}\n", aim_scores.summary());
     
+    let household_capacity = CapacityCalculator::estimate(&test_result, &loaded_latency);
+    println!("🏠 {}\n", household_capacity.summary);
+    
     // STEP 4: Generate AI Insights
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     println!("🤖 STEP 4: AI-Powered Insights");
@@ -136,10 +158,7 @@ async fn main() {
         idle_rpm: loaded_latency.idle_rpm as u16,
         dl_rpm: loaded_latency.download_rpm as u16,
         ul_rpm: loaded_latency.upload_rpm as u16,
-        gaming_score: aim_scores.gaming.score as u8,
-        streaming_score: aim_scores.streaming.score as u8,
-        video_score: aim_scores.video_conferencing.score as u8,
-        browsing_score: aim_scores.general_browsing.score as u8,
+        use_case_scores: CompactTestResult::compact_scores(&aim_scores),
         overall_score: aim_scores.overall_score as u8,
         duration_ms: test_result.test_duration_ms as u32,
         timestamp: test_result.timestamp.timestamp() as u64,
@@ -160,8 +179,15 @@ async fn main() {
     
     let enhanced_result = EnhancedTestResult {
         basic: test_result.clone(),
+        advice: Some(loaded_latency.advice()),
         loaded_latency: Some(loaded_latency),
+        download_consistency: None, // Needs the responsiveness runs
+        upload_consistency: None,
+        download_stalls: None,
+        upload_stalls: None,
         aim_scores: Some(aim_scores),
+        plan_compliance: None, // Needs a registered ISP plan
+        household_capacity: Some(household_capacity),
         ai_insights: None, // Would be populated if AI was enabled
     };
     
@@ -193,6 +219,6 @@ async fn main() {
     println!("💡 Next Steps:");
     println!("  1. Start backend: cargo run");
     println!("  2. Test enhanced endpoint: POST /api/test/enhanced/start");
-    println!("  3. Connect WebSocket: ws://localhost:8080/ws/enhanced/{test_id}");
+    println!("  3. Connect WebSocket: ws://localhost:8080/ws/enhanced/{{test_id}}");
     println!("  4. Build React frontend to visualize results\n");
 }
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::env;
 
+use crate::services::ai_insights::InsightProviderKind;
+use crate::services::loaded_latency::BufferbloatThresholds;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub server_id: String,
@@ -10,12 +13,18 @@ pub struct AppConfig {
     pub server_lon: f64,
     pub bind_host: String,
     pub bind_port: u16,
+    pub udp_echo_port: u16,
     pub max_concurrent_tests: usize,
     pub database_path: String,
     pub default_test_duration_ms: u64,
     pub chunk_size_bytes: usize,
     pub min_test_duration_ms: u64,
     pub max_test_duration_ms: u64,
+    pub bufferbloat: BufferbloatThresholds,
+    /// TOML or JSON scoring profile; the built-in profile when unset
+    pub scoring_profile_path: Option<String>,
+    /// Who writes insights: "auto" (default), "openai", "rules" or "off"
+    pub insight_provider: InsightProviderKind,
 }
 
 impl AppConfig {
@@ -37,6 +46,10 @@ impl AppConfig {
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .unwrap_or(8080),
+            udp_echo_port: env::var("UDP_ECHO_PORT")
+                .unwrap_or_else(|_| "8081".to_string())
+                .parse()
+                .unwrap_or(8081),
             max_concurrent_tests: env::var("MAX_CONCURRENT_TESTS")
                 .unwrap_or_else(|_| "50".to_string())
                 .parse()
@@ -59,6 +72,51 @@ impl AppConfig {
                 .unwrap_or_else(|_| "30000".to_string())
                 .parse()
                 .unwrap_or(30000),
+            bufferbloat: Self::bufferbloat_from_env(),
+            scoring_profile_path: env::var("SCORING_PROFILE").ok().filter(|p| !p.is_empty()),
+            insight_provider: Self::insight_provider_from_env(),
+        }
+    }
+    
+    /// `INSIGHT_PROVIDER`, or `auto` with a warning when the value is not a provider name
+    fn insight_provider_from_env() -> InsightProviderKind {
+        let Ok(name) = env::var("INSIGHT_PROVIDER") else {
+            return InsightProviderKind::default();
+        };
+        InsightProviderKind::from_name(&name).unwrap_or_else(|| {
+            let fallback = InsightProviderKind::default();
+            log::warn!(
+                "⚠️ Unknown INSIGHT_PROVIDER {:?}, using {}; expected one of: {}",
+                name,
+                fallback.as_str(),
+                InsightProviderKind::ALL.map(|kind| kind.as_str().to_string()).join(", ")
+            );
+            fallback
+        })
+    }
+    
+    /// Bufferbloat thresholds; comma-separated lists must have one limit per grade A+ to D
+    fn bufferbloat_from_env() -> BufferbloatThresholds {
+        let defaults = BufferbloatThresholds::default();
+        let limits = |name: &str, fallback: Vec<f64>| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.split(',').map(|x| x.trim().parse().ok()).collect::<Option<Vec<f64>>>())
+                .filter(|v| v.len() == fallback.len())
+                .unwrap_or(fallback)
+        };
+        
+        BufferbloatThresholds {
+            increase_ms: limits("BUFFERBLOAT_INCREASE_MS", defaults.increase_ms),
+            ratio: limits("BUFFERBLOAT_RATIO", defaults.ratio),
+            ratio_floor_ms: env::var("BUFFERBLOAT_RATIO_FLOOR_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.ratio_floor_ms),
+            max_ratio_penalty: env::var("BUFFERBLOAT_MAX_RATIO_PENALTY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_ratio_penalty),
         }
     }
 }
@@ -1,33 +1,45 @@
-/// HTTP Download Endpoint for Speed Testing
-/// 
-/// Provides large file downloads for testing download speeds.
-/// This is an alternative to WebSocket-based testing.
+//! HTTP Download Endpoint for Speed Testing
+//! 
+//! Provides large file downloads for testing download speeds.
+//! This is an alternative to WebSocket-based testing.
 
 use actix_web::{web, HttpResponse, Result};
 use bytes::Bytes;
 use log::info;
+use once_cell::sync::Lazy;
 use rand::Rng;
 
-/// Generate and serve random data for download testing
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+/// One megabyte of random data, generated once and streamed repeatedly
+/// so large downloads don't block a worker while filling a buffer
+static RANDOM_BLOCK: Lazy<Bytes> = Lazy::new(|| {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..BLOCK_SIZE)
+        .map(|_| rng.gen::<u8>())
+        .collect();
+    Bytes::from(data)
+});
+
+/// Serve random data for download testing
 pub async fn download_test(
     query: web::Query<DownloadQuery>,
 ) -> Result<HttpResponse> {
     let size_mb = query.size_mb.unwrap_or(10).min(100); // Max 100MB
-    let size_bytes = size_mb * 1024 * 1024;
+    let size_bytes = size_mb * BLOCK_SIZE;
     
     info!("📥 HTTP download test: {} MB", size_mb);
     
-    // Generate random data
-    let mut rng = rand::thread_rng();
-    let data: Vec<u8> = (0..size_bytes)
-        .map(|_| rng.gen::<u8>())
-        .collect();
+    let blocks = futures::stream::iter(
+        (0..size_mb).map(|_| Ok::<_, actix_web::Error>(RANDOM_BLOCK.clone()))
+    );
     
     Ok(HttpResponse::Ok()
         .content_type("application/octet-stream")
         .insert_header(("Content-Disposition", format!("attachment; filename=\"speedtest-{}.bin\"", size_mb)))
         .insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"))
-        .body(Bytes::from(data)))
+        .no_chunking(size_bytes as u64)
+        .streaming(blocks))
 }
 
 /// Accept upload data for testing upload speeds
@@ -1,39 +1,95 @@
-/// Enhanced Speed Test Handler with all advanced features
-/// 
-/// Integrates:
-/// - Loaded Latency Testing
-/// - AIM Use-Case Scoring
-/// - AI-Powered Insights (optional)
-/// - Binary WebSocket Protocol
+//! Enhanced Speed Test Handler with all advanced features
+//! 
+//! Integrates:
+//! - Loaded Latency Testing
+//! - AIM Use-Case Scoring
+//! - AI-Powered Insights (optional)
+//! - Binary WebSocket Protocol
+
+use std::collections::HashMap;
 
 use actix_web::{web, HttpRequest, HttpResponse, Result, Error};
-use actix_ws::Message;
 use log::{info, error, warn};
 use uuid::Uuid;
 
 use crate::config::AppConfig;
-use crate::models::{StartTestRequest, StartTestResponse, TestResult, EnhancedTestResult};
+use crate::handlers::request_locale;
+use crate::models::{StartTestResponse, TestResult, TestSocketQuery, EnhancedTestResult};
 use crate::services::database::Database;
-use crate::services::measurement::MeasurementEngine;
 use crate::services::loaded_latency::LoadedLatencyTester;
+use crate::services::responsiveness::LoadDirection;
 use crate::services::aim_scoring::AIMCalculator;
-use crate::services::ai_insights::AINetworkAnalyzer;
+use crate::services::ai_insights::{self, SharedInsightProvider};
+use crate::services::household_capacity::{ActivityMix, CapacityCalculator};
+use crate::services::plan_compliance::{self, PlanComplianceCalculator};
+use crate::services::scoring_profile::ScoringProfile;
+use crate::services::scoring_versions::ScoreInputs;
+use crate::services::udp_echo::UdpSessionRegistry;
+use crate::services::usage_priorities::{OverallWeights, PendingWeights};
 use crate::services::binary_protocol::{BinaryProtocol, BinaryMessage, TestStage};
 
 /// Start enhanced test with all features
 pub async fn start_enhanced_test(
+    http_req: HttpRequest,
     req: web::Json<EnhancedTestRequest>,
     config: web::Data<AppConfig>,
+    udp_sessions: web::Data<UdpSessionRegistry>,
+    scoring_profile: web::Data<ScoringProfile>,
+    pending_weights: web::Data<PendingWeights>,
 ) -> Result<HttpResponse> {
     info!("🚀 Starting enhanced speed test with all features");
     
+    let weights = match OverallWeights::resolve(
+        &scoring_profile,
+        req.preset.as_deref(),
+        req.priorities.as_ref(),
+    ) {
+        Ok(weights) => weights,
+        Err(e) => {
+            warn!("Rejected usage priorities: {}", e);
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    };
+    
+    if let Some(device_id) = &req.device_id {
+        if let Err(e) = plan_compliance::validate_device_id(device_id) {
+            warn!("Rejected device id: {}", e);
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    }
+    
     let test_id = Uuid::new_v4().to_string();
     
+    let udp_secret = match udp_sessions.register(&test_id) {
+        Ok(secret) => secret,
+        Err(e) => {
+            warn!("Refused test start: {}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    };
+    pending_weights.insert(&test_id, weights);
+    
+    // Browsers cannot set headers on WebSocket requests, so the negotiated
+    // locale travels in the URL
+    let locale = request_locale(&http_req, req.lang.as_deref());
+    let mut websocket_url = format!("ws://{}:{}/ws/enhanced/{}?lang={}",
+        config.server_ip, config.bind_port, test_id, locale.as_str());
+    if let Some(device_id) = &req.device_id {
+        websocket_url.push_str(&format!("&device_id={}", device_id));
+    }
+    
     let response = StartTestResponse {
         test_id: test_id.clone(),
         server_id: config.server_id.clone(),
-        websocket_url: format!("ws://{}:{}/ws/enhanced/{}", 
-            config.server_ip, config.bind_port, test_id),
+        websocket_url,
+        udp_echo_port: config.udp_echo_port,
+        udp_secret,
     };
     
     Ok(HttpResponse::Ok().json(response))
@@ -44,23 +100,48 @@ pub struct EnhancedTestRequest {
     pub include_ai_insights: Option<bool>,
     pub use_binary_protocol: Option<bool>,
     pub duration_ms: Option<u64>,
+    /// Household preset for the overall score: "balanced", "gamer", "remote_worker" or "streamer"
+    pub preset: Option<String>,
+    /// Relative priority per use case, e.g. `{"streaming": 3, "browsing": 1}`
+    pub priorities: Option<HashMap<String, f64>>,
+    /// Ties the result to a device's registered ISP plan
+    pub device_id: Option<String>,
+    /// Language of the result texts; defaults to `Accept-Language`
+    pub lang: Option<String>,
 }
 
 /// Enhanced WebSocket test with binary protocol and all features
+#[allow(clippy::too_many_arguments)]
 pub async fn websocket_enhanced_test(
     req: HttpRequest,
     stream: web::Payload,
     path: web::Path<String>,
+    query: web::Query<TestSocketQuery>,
     config: web::Data<AppConfig>,
     db: web::Data<Database>,
+    udp_sessions: web::Data<UdpSessionRegistry>,
+    scoring_profile: web::Data<ScoringProfile>,
+    pending_weights: web::Data<PendingWeights>,
+    insight_provider: web::Data<SharedInsightProvider>,
 ) -> Result<HttpResponse, Error> {
     let test_id = path.into_inner();
     info!("🌐 Enhanced WebSocket connection for test: {}", test_id);
     
-    let (res, mut session, mut stream) = actix_ws::handle(&req, stream)?;
+    let (res, mut session, stream) = actix_ws::handle(&req, stream)?;
     
     let config = config.get_ref().clone();
     let db = db.get_ref().clone();
+    let udp_sessions = udp_sessions.get_ref().clone();
+    let scoring_profile = scoring_profile.into_inner();
+    let insight_provider = insight_provider.get_ref().clone();
+    // Tests opened without going through start use the profile's weights
+    let overall_weights = pending_weights.take(&test_id)
+        .unwrap_or_else(|| OverallWeights::from_profile(&scoring_profile));
+    
+    let locale = request_locale(&req, query.lang.as_deref());
+    // Ignore device ids that would not have passed start validation
+    let device_id = query.into_inner().device_id
+        .filter(|id| plan_compliance::validate_device_id(id).is_ok());
     
     // Get client IP
     let client_ip = req
@@ -72,13 +153,17 @@ pub async fn websocket_enhanced_test(
         // Keep the incoming stream alive for the duration of the test.
         // Without holding on to this stream, Actix will drop the WebSocket
         // connection immediately after the HTTP upgrade completes.
-        let mut _ws_stream = stream;
+        let _ws_stream = stream;
         // Create test result
         let mut result = TestResult::new(config.server_id.clone(), client_ip);
         result.id = test_id.clone();
+        result.device_id = device_id;
+        // The speeds below are placeholders until this run measures the
+        // client's throughput, so the result is never judged against a plan
+        result.measured = false;
         
         // Initialize loaded latency tester
-        let mut latency_tester = LoadedLatencyTester::new();
+        let mut latency_tester = LoadedLatencyTester::with_thresholds(config.bufferbloat.clone());
         
         // Send progress: Initializing
         send_progress(&mut session, TestStage::Initializing, 0, "Starting test...").await;
@@ -88,6 +173,9 @@ pub async fn websocket_enhanced_test(
         send_progress(&mut session, TestStage::IdleLatency, 10, "Measuring baseline latency...").await;
         
         let target = format!("{}:{}", config.server_ip, config.bind_port);
+        // Packet loss is left unmeasured: probing the server's own UDP echo
+        // responder from here would report loopback loss, not the client's
+        // link. The echo session stays open for the client's own probes.
         if let Err(e) = latency_tester.measure_idle_latency(&target, 20).await {
             error!("Idle latency measurement failed: {}", e);
         }
@@ -96,18 +184,21 @@ pub async fn websocket_enhanced_test(
         info!("📥 Stage 2: Download test with loaded latency");
         send_progress(&mut session, TestStage::Download, 30, "Testing download speed...").await;
         
-        // Simulate download test (in real implementation, this would be actual download)
-        let engine = MeasurementEngine::new(config.clone());
-        
         // Measure loaded latency during download
         for i in 0..10 {
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            if let Ok(latency) = latency_tester.measure_download_loaded_latency(&target).await {
+            if latency_tester.measure_download_loaded_latency(&target).await.is_some() {
                 send_progress(&mut session, TestStage::Download, 30 + (i * 3), 
                     &format!("Download: {:.1} Mbps", 100.0 + (i as f64 * 30.0))).await;
             }
         }
         
+        // Spec-conformant responsiveness under download load
+        send_progress(&mut session, TestStage::Download, 55, "Measuring download responsiveness...").await;
+        if let Err(e) = latency_tester.measure_responsiveness(&target, LoadDirection::Download).await {
+            warn!("Download responsiveness test failed: {}", e);
+        }
+        
         result.download_mbps = 300.0; // Mock value
         
         // STAGE 3: Upload Test with Loaded Latency
@@ -116,14 +207,19 @@ pub async fn websocket_enhanced_test(
         
         for i in 0..10 {
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            if let Ok(latency) = latency_tester.measure_upload_loaded_latency(&target).await {
+            if latency_tester.measure_upload_loaded_latency(&target).await.is_some() {
                 send_progress(&mut session, TestStage::Upload, 60 + (i * 3),
                     &format!("Upload: {:.1} Mbps", 30.0 + (i as f64 * 2.0))).await;
             }
         }
         
+        // Spec-conformant responsiveness under upload load
+        send_progress(&mut session, TestStage::Upload, 85, "Measuring upload responsiveness...").await;
+        if let Err(e) = latency_tester.measure_responsiveness(&target, LoadDirection::Upload).await {
+            warn!("Upload responsiveness test failed: {}", e);
+        }
+        
         result.upload_mbps = 50.0; // Mock value
-        result.jitter_ms = 5.0;
         result.test_duration_ms = 10000;
         
         // STAGE 4: Calculate Results
@@ -133,52 +229,94 @@ pub async fn websocket_enhanced_test(
         // Calculate loaded latency results
         let loaded_latency = latency_tester.calculate_results();
         result.latency_ms = loaded_latency.idle_avg_ms;
+        result.jitter_ms = loaded_latency.idle_jitter.rfc3550_jitter_ms;
         
         // Calculate AIM scores
-        let aim_scores = AIMCalculator::calculate_all_scores(&result, &loaded_latency);
+        let aim_scores = AIMCalculator::calculate_weighted(
+            &scoring_profile,
+            &overall_weights,
+            &result,
+            &loaded_latency,
+            loaded_latency.idle_packet_loss.as_ref(),
+        );
         
         info!("✅ Test complete - Overall AIM Score: {:.0}/100", aim_scores.overall_score);
         
+        let download_consistency = loaded_latency.throughput_consistency(LoadDirection::Download);
+        let upload_consistency = loaded_latency.throughput_consistency(LoadDirection::Upload);
+        
+        let plan_compliance = if result.measured {
+            match db.find_plan_for(&result).await {
+                Ok(plan) => plan.map(|plan| PlanComplianceCalculator::evaluate(&plan, &result)),
+                Err(e) => {
+                    error!("Failed to look up ISP plan: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        
         // Create enhanced result
         let mut enhanced_result = EnhancedTestResult {
             basic: result.clone(),
+            advice: Some(loaded_latency.advice()),
             loaded_latency: Some(loaded_latency.clone()),
+            download_consistency: download_consistency.clone(),
+            upload_consistency: upload_consistency.clone(),
+            download_stalls: loaded_latency.throughput_stalls(LoadDirection::Download),
+            upload_stalls: loaded_latency.throughput_stalls(LoadDirection::Upload),
             aim_scores: Some(aim_scores.clone()),
+            plan_compliance,
+            // Capacity from placeholder speeds would be made up
+            household_capacity: result.measured.then(|| CapacityCalculator::estimate(&result, &loaded_latency)),
             ai_insights: None,
         };
         
-        // Optional: Generate AI insights (only if enabled)
-        // This is expensive, so only do it on request
-        match AINetworkAnalyzer::from_env() {
-            Ok(ai_analyzer) => {
-                info!("🤖 Generating AI insights...");
-                match ai_analyzer.analyze_network(&result, &loaded_latency, &aim_scores).await {
-                    Ok(insights) => {
-                        info!("✅ AI insights generated successfully");
-                        enhanced_result.ai_insights = Some(insights);
-                    },
-                    Err(e) => {
-                        warn!("AI insights generation failed: {}", e);
-                    }
-                }
-            },
-            Err(e) => {
-                warn!("AI analyzer not available: {}", e);
-            }
+        // Insights from the configured provider; a failing model falls
+        // back to the rule-based insights
+        if let Some(provider) = &insight_provider {
+            info!("🤖 Generating insights with {}...", provider.name());
+            let insights = ai_insights::generate_insights(provider.as_ref(), &result, &loaded_latency, &aim_scores).await;
+            info!("✅ Insights generated by {}", insights.provider);
+            enhanced_result.ai_insights = Some(insights);
         }
         
-        // Save to database
+        // Save to database, with the raw inputs so the test can be re-scored
         if let Err(e) = db.save_test_result(&result).await {
             error!("Failed to save test result: {}", e);
         }
+        let score_inputs = ScoreInputs {
+            test_result: result.clone(),
+            loaded_latency: loaded_latency.clone(),
+            overall_weights: overall_weights.clone(),
+        };
+        if let Err(e) = db.save_score_inputs(&score_inputs).await {
+            error!("Failed to save score inputs: {}", e);
+        }
+        if let Err(e) = db.save_aim_scores(&test_id, &aim_scores).await {
+            error!("Failed to save AIM scores: {}", e);
+        }
+        for (direction, consistency) in [
+            (LoadDirection::Download, &download_consistency),
+            (LoadDirection::Upload, &upload_consistency),
+        ] {
+            if let Some(consistency) = consistency {
+                if let Err(e) = db.save_consistency(&test_id, direction, consistency).await {
+                    error!("Failed to save {:?} consistency: {}", direction, e);
+                }
+            }
+        }
         
         // Send final results
         send_progress(&mut session, TestStage::Complete, 100, "Test complete!").await;
         
+        enhanced_result.localize(locale);
         let result_json = serde_json::to_string(&enhanced_result).unwrap();
         let _ = session.text(result_json).await;
         
         info!("🎉 Enhanced test completed successfully");
+        udp_sessions.remove(&test_id);
         let _ = session.close(None).await;
     });
     
@@ -215,6 +353,7 @@ async fn send_progress(
 
 /// Get enhanced test result with all features
 pub async fn get_enhanced_result(
+    req: HttpRequest,
     path: web::Path<String>,
     db: web::Data<Database>,
     query: web::Query<EnhancedResultQuery>,
@@ -224,9 +363,46 @@ pub async fn get_enhanced_result(
     
     match db.get_test_result(&test_id).await {
         Ok(Some(result)) => {
-            // For now, return basic result
-            // In production, would fetch full enhanced result from database
-            Ok(HttpResponse::Ok().json(result))
+            // AI insights are not persisted; everything else is, for tests
+            // run since score inputs were kept
+            let download_consistency = db.get_consistency(&test_id, LoadDirection::Download).await
+                .unwrap_or_else(|e| { error!("Database error: {}", e); None });
+            let upload_consistency = db.get_consistency(&test_id, LoadDirection::Upload).await
+                .unwrap_or_else(|e| { error!("Database error: {}", e); None });
+            let loaded_latency = db.get_score_inputs(&test_id).await
+                .unwrap_or_else(|e| { error!("Database error: {}", e); None })
+                .map(|inputs| inputs.loaded_latency);
+            let aim_scores = db.get_aim_scores(&test_id).await
+                .unwrap_or_else(|e| { error!("Database error: {}", e); None });
+            
+            let household_capacity = loaded_latency.as_ref()
+                .filter(|_| result.measured)
+                .map(|l| CapacityCalculator::estimate(&result, l));
+            let plan_compliance = if result.measured {
+                db.find_plan_for(&result).await
+                    .unwrap_or_else(|e| { error!("Database error: {}", e); None })
+                    .map(|plan| PlanComplianceCalculator::evaluate(&plan, &result))
+            } else {
+                None
+            };
+            
+            let mut enhanced_result = EnhancedTestResult {
+                basic: result,
+                advice: loaded_latency.as_ref().map(|l| l.advice()),
+                download_consistency,
+                upload_consistency,
+                download_stalls: loaded_latency.as_ref()
+                    .and_then(|l| l.throughput_stalls(LoadDirection::Download)),
+                upload_stalls: loaded_latency.as_ref()
+                    .and_then(|l| l.throughput_stalls(LoadDirection::Upload)),
+                loaded_latency,
+                aim_scores,
+                plan_compliance,
+                household_capacity,
+                ai_insights: None,
+            };
+            enhanced_result.localize(request_locale(&req, query.lang.as_deref()));
+            Ok(HttpResponse::Ok().json(enhanced_result))
         },
         Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
             "error": "Test result not found"
@@ -243,4 +419,43 @@ pub async fn get_enhanced_result(
 #[derive(Debug, serde::Deserialize)]
 pub struct EnhancedResultQuery {
     pub include_ai: Option<bool>,
+    /// Language of the result texts; defaults to `Accept-Language`
+    pub lang: Option<String>,
+}
+
+/// Check whether a mix of streams, calls and games fits a stored test's link,
+/// e.g. `?streams_4k=2&video_calls=1&gaming_sessions=1`
+pub async fn check_household_mix(
+    path: web::Path<String>,
+    mix: web::Query<ActivityMix>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let test_id = path.into_inner();
+    
+    match db.get_score_inputs(&test_id).await {
+        Ok(Some(inputs)) if !inputs.test_result.measured => {
+            Ok(HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                "error": "This test has no measured speeds to check a mix against"
+            })))
+        },
+        Ok(Some(inputs)) => {
+            let capacity = CapacityCalculator::estimate(&inputs.test_result, &inputs.loaded_latency);
+            let check = capacity.check(&mix);
+            info!("🏠 Mix check for {}: {} {}", test_id, mix.describe(),
+                if check.fits { "fits" } else { "does not fit" });
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "check": check,
+                "capacity": capacity,
+            })))
+        },
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No stored measurements for this test"
+        }))),
+        Err(e) => {
+            error!("Database error: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch test measurements"
+            })))
+        }
+    }
 }
@@ -0,0 +1,31 @@
+use actix_web::{web, HttpResponse, Result};
+
+use crate::services::i18n::Locale;
+
+/// Supported locales; `?lang=` and `Accept-Language` pick among these
+pub async fn list_locales() -> Result<HttpResponse> {
+    let locales: Vec<serde_json::Value> = Locale::ALL.iter()
+        .map(|locale| serde_json::json!({
+            "code": locale.as_str(),
+            "label": locale.label(),
+        }))
+        .collect();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "default": Locale::default().as_str(),
+        "locales": locales,
+    })))
+}
+
+/// Every message of a locale by key, so clients can render `{key, params}` themselves
+pub async fn get_catalog(path: web::Path<String>) -> Result<HttpResponse> {
+    let code = path.into_inner();
+    match Locale::from_tag(&code) {
+        Some(locale) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "locale": locale.as_str(),
+            "messages": locale.catalog(),
+        }))),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("Unsupported locale: {}", code)
+        }))),
+    }
+}
@@ -1,10 +1,15 @@
-use actix_web::web;
+use actix_web::{web, HttpRequest};
+
+use crate::services::i18n::Locale;
 
 pub mod health;
 pub mod servers;
 pub mod test;
 pub mod enhanced_test;
 pub mod download;
+pub mod scoring;
+pub mod plans;
+pub mod locales;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -19,10 +24,32 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .route("/test/enhanced/start", web::post().to(enhanced_test::start_enhanced_test))
             .route("/ws/enhanced/{test_id}", web::get().to(enhanced_test::websocket_enhanced_test))
             .route("/api/enhanced/result/{test_id}", web::get().to(enhanced_test::get_enhanced_result))
+            .route("/test/{id}/capacity", web::get().to(enhanced_test::check_household_mix))
+            // Scoring versions and re-scoring of stored tests
+            .route("/scoring/versions", web::get().to(scoring::get_scoring_versions))
+            .route("/test/{id}/rescore", web::get().to(scoring::rescore_test))
+            // ISP plans and compliance against them
+            .route("/plans", web::post().to(plans::register_plan))
+            .route("/plans", web::get().to(plans::list_plans))
+            .route("/plans/{id}", web::get().to(plans::get_plan))
+            .route("/plans/{id}", web::delete().to(plans::delete_plan))
+            .route("/plans/{id}/report", web::get().to(plans::get_compliance_report))
+            .route("/test/{id}/plan-compliance", web::get().to(plans::get_test_plan_compliance))
+            // Message catalogs for localized texts
+            .route("/locales", web::get().to(locales::list_locales))
+            .route("/locales/{code}", web::get().to(locales::get_catalog))
             // HTTP-based speed test endpoints
-            .route("/api/download", web::get().to(download::download_test))
-            .route("/api/upload", web::post().to(download::upload_test)),
+            .route("/download", web::get().to(download::download_test))
+            .route("/upload", web::post().to(download::upload_test)),
     )
     .route("/ws/test/{id}", web::get().to(test::websocket_test))
     .route("/ws/enhanced/{id}", web::get().to(enhanced_test::websocket_enhanced_test));
 }
+
+/// Locale for a response: `lang` if supported, else the `Accept-Language` header
+pub fn request_locale(req: &HttpRequest, lang: Option<&str>) -> Locale {
+    let accept_language = req.headers()
+        .get(actix_web::http::header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok());
+    Locale::negotiate(lang, accept_language)
+}
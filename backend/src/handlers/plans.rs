@@ -0,0 +1,221 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+
+use crate::services::database::Database;
+use crate::services::plan_compliance::{PlanComplianceCalculator, PlanRegistration};
+
+const DEFAULT_REPORT_DAYS: i64 = 30;
+const MAX_REPORT_DAYS: i64 = 365;
+
+/// Register the plan a device or the caller's IP subscribes to. Plans are
+/// owned by the IP that registered them.
+pub async fn register_plan(
+    req: HttpRequest,
+    body: web::Json<PlanRegistration>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let caller_ip = caller_ip(&req);
+
+    let plan = match body.into_inner().into_plan(&caller_ip) {
+        Ok(plan) => plan,
+        Err(e) => {
+            warn!("Rejected ISP plan: {}", e);
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    };
+
+    if let Err(e) = db.save_plan(&plan).await {
+        error!("Failed to save ISP plan: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to save plan"
+        })));
+    }
+    info!("📋 Registered ISP plan {} ({} / {} Mbps)", plan.name, plan.download_mbps, plan.upload_mbps);
+    Ok(HttpResponse::Created().json(plan))
+}
+
+/// List the caller's plans, optionally filtered by `?device_id=`
+pub async fn list_plans(
+    req: HttpRequest,
+    query: web::Query<PlanListQuery>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    match db.list_plans(query.device_id.as_deref(), Some(&caller_ip(&req))).await {
+        Ok(plans) => Ok(HttpResponse::Ok().json(plans)),
+        Err(e) => {
+            error!("Database error: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch plans"
+            })))
+        }
+    }
+}
+
+/// One of the caller's plans; other callers' plans are not found
+pub async fn get_plan(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    match db.get_plan(&path.into_inner(), &caller_ip(&req)).await {
+        Ok(Some(plan)) => Ok(HttpResponse::Ok().json(plan)),
+        Ok(None) => Ok(plan_not_found()),
+        Err(e) => {
+            error!("Database error: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch plan"
+            })))
+        }
+    }
+}
+
+/// Delete one of the caller's plans; other callers' plans are not found
+pub async fn delete_plan(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let plan_id = path.into_inner();
+    match db.delete_plan(&plan_id, &caller_ip(&req)).await {
+        Ok(true) => {
+            info!("🗑️  Deleted ISP plan {}", plan_id);
+            Ok(HttpResponse::NoContent().finish())
+        }
+        Ok(false) => Ok(plan_not_found()),
+        Err(e) => {
+            error!("Database error: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete plan"
+            })))
+        }
+    }
+}
+
+/// Compliance of one of the caller's plans over the last `?days=` (default
+/// 30), as JSON or, with `?format=csv`, as a CSV download to send to the ISP
+pub async fn get_compliance_report(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<ReportQuery>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let plan_id = path.into_inner();
+    let days = query.days.unwrap_or(DEFAULT_REPORT_DAYS);
+    if !(1..=MAX_REPORT_DAYS).contains(&days) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("days: must be between 1 and {}", MAX_REPORT_DAYS)
+        })));
+    }
+    let as_csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unknown format: {} (expected json or csv)", other)
+        }))),
+    };
+
+    let plan = match db.get_plan(&plan_id, &caller_ip(&req)).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return Ok(plan_not_found()),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch plan"
+            })));
+        }
+    };
+
+    let period_end = Utc::now();
+    let period_start = period_end - Duration::days(days);
+    let tests = match db.get_tests_for_plan(&plan, period_start).await {
+        Ok(tests) => tests,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch test history"
+            })));
+        }
+    };
+
+    let report = PlanComplianceCalculator::report(&plan, &tests, period_start, period_end);
+    info!("📑 Compliance report for plan {}: {} over {} tests", plan.name, report.verdict.as_str(), report.tests);
+
+    if as_csv {
+        Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"plan-compliance-{}.csv\"", period_end.format("%Y-%m-%d")),
+            ))
+            .body(report.to_csv()))
+    } else {
+        Ok(HttpResponse::Ok().json(report))
+    }
+}
+
+/// A stored test measured against the plan of its device or client IP
+pub async fn get_test_plan_compliance(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let test_id = path.into_inner();
+
+    let test = match db.get_test_result(&test_id).await {
+        Ok(Some(test)) => test,
+        Ok(None) => return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Test result not found"
+        }))),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch test result"
+            })));
+        }
+    };
+
+    if !test.measured {
+        return Ok(HttpResponse::UnprocessableEntity().json(serde_json::json!({
+            "error": "This test has no measured speeds to compare with a plan"
+        })));
+    }
+
+    match db.find_plan_for(&test).await {
+        Ok(Some(plan)) => Ok(HttpResponse::Ok().json(PlanComplianceCalculator::evaluate(&plan, &test))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No ISP plan registered for this test's device or client IP"
+        }))),
+        Err(e) => {
+            error!("Database error: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch plan"
+            })))
+        }
+    }
+}
+
+fn caller_ip(req: &HttpRequest) -> String {
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn plan_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "Plan not found"
+    }))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PlanListQuery {
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ReportQuery {
+    pub days: Option<i64>,
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+}
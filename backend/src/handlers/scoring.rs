@@ -0,0 +1,70 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use log::{error, info};
+
+use crate::handlers::request_locale;
+use crate::services::database::Database;
+use crate::services::scoring_versions::{RescoreComparison, ScoringVersions};
+
+/// List the scoring versions a test can be re-scored with
+pub async fn get_scoring_versions(versions: web::Data<ScoringVersions>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "active": versions.active().version,
+        "versions": versions.versions(),
+    })))
+}
+
+/// Re-score a stored test with `?version=` (default: the active version)
+/// and return the original and new scores side by side, in `?lang=`
+pub async fn rescore_test(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<RescoreQuery>,
+    db: web::Data<Database>,
+    versions: web::Data<ScoringVersions>,
+) -> Result<HttpResponse> {
+    let test_id = path.into_inner();
+    let profile = match query.version.as_deref() {
+        Some(version) => match versions.get(version) {
+            Some(profile) => profile,
+            None => {
+                let supported: Vec<String> = versions.versions().into_iter().map(|v| v.version).collect();
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": format!("Unknown scoring version: {}", version),
+                    "supported_versions": supported,
+                })));
+            }
+        },
+        None => versions.active(),
+    };
+    info!("🔁 Re-scoring test {} with {}", test_id, profile.version);
+
+    let inputs = match db.get_score_inputs(&test_id).await {
+        Ok(Some(inputs)) => inputs,
+        Ok(None) => return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No stored score inputs for this test"
+        }))),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch score inputs"
+            })));
+        }
+    };
+    let locale = request_locale(&req, query.lang.as_deref());
+    let mut original = db.get_aim_scores(&test_id).await
+        .unwrap_or_else(|e| { error!("Database error: {}", e); None });
+    if let Some(original) = &mut original {
+        original.localize(locale);
+    }
+
+    let mut rescored = inputs.score(profile);
+    rescored.localize(locale);
+    Ok(HttpResponse::Ok().json(RescoreComparison::new(&test_id, original, rescored)))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RescoreQuery {
+    pub version: Option<String>,
+    /// Language of the score texts; defaults to `Accept-Language`
+    pub lang: Option<String>,
+}
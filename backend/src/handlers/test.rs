@@ -1,30 +1,57 @@
 use actix_web::{web, HttpRequest, HttpResponse, Result, Error};
-use actix_ws::Message;
-use log::{info, error};
+use log::{info, error, warn};
 use uuid::Uuid;
 
 use crate::config::AppConfig;
-use crate::models::{StartTestRequest, StartTestResponse, TestResult};
+use crate::models::{StartTestRequest, StartTestResponse, TestSocketQuery};
+use crate::services::plan_compliance;
+use crate::services::udp_echo::UdpSessionRegistry;
 use crate::services::database::Database;
-use crate::services::measurement::MeasurementEngine;
 use crate::services::real_measurement::RealMeasurementEngine;
 
 pub async fn start_test(
     req: web::Json<StartTestRequest>,
     config: web::Data<AppConfig>,
+    udp_sessions: web::Data<UdpSessionRegistry>,
 ) -> Result<HttpResponse> {
     info!("Starting new speed test");
     
     let test_id = Uuid::new_v4().to_string();
-    let duration_ms = req
+    let _duration_ms = req
         .duration_ms
         .unwrap_or(config.default_test_duration_ms)
         .clamp(config.min_test_duration_ms, config.max_test_duration_ms);
     
+    if let Some(device_id) = &req.device_id {
+        if let Err(e) = plan_compliance::validate_device_id(device_id) {
+            warn!("Rejected device id: {}", e);
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    }
+    
+    let udp_secret = match udp_sessions.register(&test_id) {
+        Ok(secret) => secret,
+        Err(e) => {
+            warn!("Refused test start: {}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    };
+    
+    let mut websocket_url = format!("ws://{}:{}/ws/test/{}", config.server_ip, config.bind_port, test_id);
+    if let Some(device_id) = &req.device_id {
+        websocket_url.push_str(&format!("?device_id={}", device_id));
+    }
+    
     let response = StartTestResponse {
         test_id: test_id.clone(),
         server_id: config.server_id.clone(),
-        websocket_url: format!("ws://{}:{}/ws/test/{}", config.server_ip, config.bind_port, test_id),
+        websocket_url,
+        udp_echo_port: config.udp_echo_port,
+        udp_secret,
     };
     
     Ok(HttpResponse::Ok().json(response))
@@ -34,8 +61,10 @@ pub async fn websocket_test(
     req: HttpRequest,
     stream: web::Payload,
     path: web::Path<String>,
+    query: web::Query<TestSocketQuery>,
     config: web::Data<AppConfig>,
     db: web::Data<Database>,
+    udp_sessions: web::Data<UdpSessionRegistry>,
 ) -> Result<HttpResponse, Error> {
     let test_id = path.into_inner();
     info!("WebSocket connection established for test: {}", test_id);
@@ -44,6 +73,10 @@ pub async fn websocket_test(
     
     let config = config.get_ref().clone();
     let db = db.get_ref().clone();
+    let udp_sessions = udp_sessions.get_ref().clone();
+    
+    let device_id = query.into_inner().device_id
+        .filter(|id| plan_compliance::validate_device_id(id).is_ok());
     
     // Get client IP
     let client_ip = req
@@ -53,14 +86,15 @@ pub async fn websocket_test(
     
     // Spawn a task to handle the test
     actix_web::rt::spawn(async move {
-        let mut stream = stream;
+        let _stream = stream;
         
         // Use REAL measurement engine with actual data transfer
         let engine = RealMeasurementEngine::new(config.clone());
         
         // Run the speed test with REAL bytes
         match engine.run_full_test(&test_id, &mut session, client_ip).await {
-            Ok(result) => {
+            Ok(mut result) => {
+                result.device_id = device_id;
                 info!("Test completed successfully: {}", test_id);
                 
                 // Save result to database
@@ -79,6 +113,8 @@ pub async fn websocket_test(
             }
         }
         
+        // The test is over, so its echo session is too
+        udp_sessions.remove(&test_id);
         let _ = session.close(None).await;
     });
     
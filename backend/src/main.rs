@@ -3,13 +3,13 @@ use actix_web::{middleware, web, App, HttpServer};
 use dotenv::dotenv;
 use log::info;
 
-mod config;
-mod handlers;
-mod models;
-mod services;
-
-use config::AppConfig;
-use services::database::Database;
+use speedtest_pro_backend::config::AppConfig;
+use speedtest_pro_backend::handlers;
+use speedtest_pro_backend::services::database::Database;
+use speedtest_pro_backend::services::scoring_profile::ScoringProfile;
+use speedtest_pro_backend::services::scoring_versions::ScoringVersions;
+use speedtest_pro_backend::services::udp_echo::{UdpEchoServer, UdpSessionRegistry};
+use speedtest_pro_backend::services::usage_priorities::PendingWeights;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -33,8 +33,38 @@ async fn main() -> std::io::Result<()> {
     
     info!("💾 Database initialized");
     
+    let scoring_profile = ScoringProfile::load(config.scoring_profile_path.as_deref())
+        .map_err(|e| std::io::Error::other(format!("Failed to load scoring profile: {}", e)))?;
+    let scoring_versions = ScoringVersions::new(scoring_profile.clone())
+        .map_err(|e| std::io::Error::other(format!("Invalid scoring profile version: {}", e)))?;
+    
+    let insight_provider = config.insight_provider.build()
+        .map_err(|e| std::io::Error::other(format!("Failed to configure insight provider: {}", e)))?;
+    match &insight_provider {
+        Some(provider) => info!("🤖 Insights by {} ({})", provider.name(), config.insight_provider.as_str()),
+        None => info!("🤖 Insights disabled"),
+    }
+    
+    // Start UDP echo responder for packet loss tests
+    let udp_sessions = UdpSessionRegistry::new();
+    let udp_server = UdpEchoServer::bind(
+        &format!("{}:{}", config.bind_host, config.udp_echo_port),
+        udp_sessions.clone(),
+    ).await?;
+    info!("📡 UDP echo responder on {}", udp_server.local_addr()?);
+    actix_web::rt::spawn(async move {
+        if let Err(e) = udp_server.run().await {
+            log::error!("UDP echo responder stopped: {}", e);
+        }
+    });
+    
     let db_data = web::Data::new(database);
     let config_data = web::Data::new(config.clone());
+    let udp_sessions_data = web::Data::new(udp_sessions);
+    let scoring_profile_data = web::Data::new(scoring_profile);
+    let pending_weights_data = web::Data::new(PendingWeights::new());
+    let scoring_versions_data = web::Data::new(scoring_versions);
+    let insight_provider_data = web::Data::new(insight_provider);
     
     // Start HTTP server
     info!("✅ Server ready at http://{}:{}", config.bind_host, config.bind_port);
@@ -47,9 +77,16 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors)
             .app_data(db_data.clone())
             .app_data(config_data.clone())
+            .app_data(udp_sessions_data.clone())
+            .app_data(scoring_profile_data.clone())
+            .app_data(pending_weights_data.clone())
+            .app_data(scoring_versions_data.clone())
+            .app_data(insight_provider_data.clone())
             .configure(handlers::configure_routes)
     })
-    .bind((config.bind_host.as_str(), config.bind_port))?
+    // Accept both HTTP/1.1 and cleartext HTTP/2 so responsiveness self probes
+    // can be multiplexed onto loaded connections
+    .bind_auto_h2c((config.bind_host.as_str(), config.bind_port))?
     .workers(2) // Limited workers for 1 vCPU server
     .run()
     .await
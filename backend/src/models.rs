@@ -31,10 +31,23 @@ pub struct TestResult {
     pub download_mbps: f64,
     pub upload_mbps: f64,
     pub latency_ms: f64,
+    /// RFC 3550 interarrival jitter of the idle latency probes
+    /// (see `services::jitter` for the per-stage IPDV/PDV metrics)
     pub jitter_ms: f64,
     pub protocol: String,
     pub client_ip: String,
     pub test_duration_ms: u64,
+    /// Client-chosen device identifier, used to match ISP plans
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// False when the speeds are placeholders rather than measured transfers;
+    /// such tests are never judged against an ISP plan
+    #[serde(default = "default_measured")]
+    pub measured: bool,
+}
+
+fn default_measured() -> bool {
+    true
 }
 
 /// Enhanced test result with all advanced features
@@ -47,13 +60,59 @@ pub struct EnhancedTestResult {
     // Loaded latency results
     pub loaded_latency: Option<crate::services::loaded_latency::LoadedLatencyResult>,
     
+    // Bufferbloat and packet loss advice, in the response's locale
+    #[serde(default)]
+    pub advice: Option<crate::services::loaded_latency::LatencyAdvice>,
+    
+    // Throughput stability over the saturated intervals of each direction
+    #[serde(default)]
+    pub download_consistency: Option<crate::services::consistency::ConsistencyScore>,
+    #[serde(default)]
+    pub upload_consistency: Option<crate::services::consistency::ConsistencyScore>,
+    
+    // Stalls and sudden drops during the same intervals
+    #[serde(default)]
+    pub download_stalls: Option<crate::services::stall_detection::StallAnalysis>,
+    #[serde(default)]
+    pub upload_stalls: Option<crate::services::stall_detection::StallAnalysis>,
+    
     // AIM scores
     pub aim_scores: Option<crate::services::aim_scoring::AIMScores>,
     
+    // Measured speeds against the device's registered ISP plan
+    #[serde(default)]
+    pub plan_compliance: Option<crate::services::plan_compliance::PlanEvaluation>,
+    
+    // Simultaneous streams, calls and games the link sustains
+    #[serde(default)]
+    pub household_capacity: Option<crate::services::household_capacity::HouseholdCapacity>,
+    
     // AI insights (optional - only if user requests)
     pub ai_insights: Option<crate::services::ai_insights::AIInsights>,
 }
 
+impl EnhancedTestResult {
+    /// Re-render assessments, advice and rule-based insights in `locale`;
+    /// insights written by a language model are left as they came back
+    pub fn localize(&mut self, locale: crate::services::i18n::Locale) {
+        if let Some(aim_scores) = &mut self.aim_scores {
+            aim_scores.localize(locale);
+        }
+        if let Some(advice) = &mut self.advice {
+            advice.localize(locale);
+        }
+        if let (Some(insights), Some(loaded_latency), Some(aim_scores)) =
+            (&mut self.ai_insights, &self.loaded_latency, &self.aim_scores)
+        {
+            if insights.provider == "rules" {
+                *insights = crate::services::rule_insights::RuleBasedInsights::analyze_in(
+                    &self.basic, loaded_latency, aim_scores, locale,
+                );
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestProgress {
     pub stage: String, // "latency", "download", "upload", "complete"
@@ -75,6 +134,16 @@ pub struct HealthStatus {
 pub struct StartTestRequest {
     pub duration_ms: Option<u64>,
     pub protocol: Option<String>,
+    /// Ties the result to a device's registered ISP plan
+    pub device_id: Option<String>,
+}
+
+/// Query string of the test WebSocket URLs
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TestSocketQuery {
+    pub device_id: Option<String>,
+    /// Language of the result texts, e.g. "hi"
+    pub lang: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +151,10 @@ pub struct StartTestResponse {
     pub test_id: String,
     pub server_id: String,
     pub websocket_url: String,
+    /// UDP echo port for packet loss probes tagged with `test_id`
+    pub udp_echo_port: u16,
+    /// Secret every probe for `test_id` must carry
+    pub udp_secret: String,
 }
 
 impl TestResult {
@@ -97,6 +170,8 @@ impl TestResult {
             protocol: "TCP".to_string(),
             client_ip,
             test_duration_ms: 0,
+            device_id: None,
+            measured: true,
         }
     }
 }
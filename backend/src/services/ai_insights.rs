@@ -1,27 +1,39 @@
-/// AI-Powered Network Intelligence
-/// 
-/// Uses OpenAI GPT to provide:
-/// - Natural language explanations of test results
-/// - Intelligent troubleshooting recommendations
-/// - Personalized network optimization advice
-/// - Predictive issue detection
+//! AI-Powered Network Intelligence
+//! 
+//! Insights come from an `InsightProvider`: an OpenAI-compatible model, or
+//! the offline rule-based provider in `rule_insights`. The model provides:
+//! - Natural language explanations of test results
+//! - Intelligent troubleshooting recommendations
+//! - Personalized network optimization advice
+//! - Predictive issue detection
 
 use async_openai::{
     Client,
+    config::{Config, OPENAI_API_BASE},
     types::{
+        ChatCompletionRequestAssistantMessageArgs,
         ChatCompletionRequestMessage, 
         ChatCompletionRequestSystemMessageArgs,
         ChatCompletionRequestUserMessageArgs,
+        ChatCompletionResponseFormat,
+        ChatCompletionResponseFormatType,
         CreateChatCompletionRequestArgs,
-        Role,
+        FinishReason,
     },
 };
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::sync::Arc;
 
 use crate::models::TestResult;
 use crate::services::loaded_latency::LoadedLatencyResult;
 use crate::services::aim_scoring::AIMScores;
+use crate::services::responsiveness::LoadDirection;
+use crate::services::household_capacity::CapacityCalculator;
+use crate::services::rule_insights::RuleBasedInsights;
 
 /// AI-generated insights about network performance
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +52,10 @@ pub struct AIInsights {
     
     /// Conversational explanation (ELI5 style)
     pub simple_explanation: String,
+    
+    /// Provider that wrote these insights: "openai" or "rules"
+    #[serde(default)]
+    pub provider: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,7 +67,8 @@ pub struct AIRecommendation {
     pub difficulty: Difficulty,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Declaration order is urgency order
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Priority {
     Critical,
     High,
@@ -67,42 +84,248 @@ pub enum Difficulty {
     Advanced,  // Requires significant expertise
 }
 
+/// Source of `AIInsights` for a finished test
+#[async_trait(?Send)]
+pub trait InsightProvider: Send + Sync {
+    /// Short identifier, recorded in `AIInsights::provider`
+    fn name(&self) -> &str;
+    
+    async fn generate(
+        &self,
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        aim_scores: &AIMScores,
+    ) -> Result<AIInsights, Box<dyn std::error::Error>>;
+}
+
+/// The provider shared by all tests; `None` when insights are off
+pub type SharedInsightProvider = Option<Arc<dyn InsightProvider>>;
+
+/// Which provider writes insights, from `INSIGHT_PROVIDER`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InsightProviderKind {
+    /// OpenAI when `OPENAI_API_KEY` or `OPENAI_BASE_URL` is set, rules otherwise
+    #[default]
+    Auto,
+    #[serde(rename = "openai")]
+    OpenAI,
+    /// Offline, deterministic; never calls out
+    Rules,
+    /// No insights at all
+    Off,
+}
+
+impl InsightProviderKind {
+    pub const ALL: [InsightProviderKind; 4] = [
+        InsightProviderKind::Auto,
+        InsightProviderKind::OpenAI,
+        InsightProviderKind::Rules,
+        InsightProviderKind::Off,
+    ];
+    
+    pub fn as_str(&self) -> &str {
+        match self {
+            InsightProviderKind::Auto => "auto",
+            InsightProviderKind::OpenAI => "openai",
+            InsightProviderKind::Rules => "rules",
+            InsightProviderKind::Off => "off",
+        }
+    }
+    
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+    
+    /// The configured provider; `None` when insights are off. `OpenAI`
+    /// without credentials is an error, so a misconfiguration fails at startup.
+    pub fn build(&self) -> Result<SharedInsightProvider, Box<dyn std::error::Error>> {
+        Ok(match self {
+            InsightProviderKind::Off => None,
+            InsightProviderKind::Rules => Some(Arc::new(RuleBasedInsights)),
+            InsightProviderKind::OpenAI => Some(Arc::new(AINetworkAnalyzer::from_env()?)),
+            InsightProviderKind::Auto => match AINetworkAnalyzer::from_env() {
+                Ok(analyzer) => Some(Arc::new(analyzer)),
+                Err(_) => Some(Arc::new(RuleBasedInsights)),
+            },
+        })
+    }
+}
+
+/// Insights from `provider`, or from the rules when it fails
+pub async fn generate_insights(
+    provider: &dyn InsightProvider,
+    test_result: &TestResult,
+    loaded_latency: &LoadedLatencyResult,
+    aim_scores: &AIMScores,
+) -> AIInsights {
+    match provider.generate(test_result, loaded_latency, aim_scores).await {
+        Ok(insights) => insights,
+        Err(e) => {
+            log::warn!("⚠️ {} insights failed, using rule-based insights: {}", provider.name(), e);
+            RuleBasedInsights::analyze(test_result, loaded_latency, aim_scores)
+        }
+    }
+}
+
+/// Shape of the reply the model must produce, quoted in the system prompt
+const INSIGHTS_SCHEMA: &str = r#"{
+  "summary": "2-3 sentences with the most important findings",
+  "detailed_analysis": "what the metrics mean, the issues found and their impact",
+  "recommendations": [
+    {
+      "priority": "Critical | High | Medium | Low",
+      "title": "short title",
+      "description": "what to do",
+      "expected_improvement": "what gets better, with numbers where possible",
+      "difficulty": "Easy | Medium | Hard | Advanced"
+    }
+  ],
+  "predictions": ["a real-world issue the user is likely to see"],
+  "simple_explanation": "friendly, jargon-free explanation; analogies welcome"
+}"#;
+
+/// Recommendations and predictions the reply must hold
+const RECOMMENDATIONS_RANGE: std::ops::RangeInclusive<usize> = 1..=5;
+const PREDICTIONS_RANGE: std::ops::RangeInclusive<usize> = 1..=5;
+
 pub struct AINetworkAnalyzer {
-    client: Client<async_openai::config::OpenAIConfig>,
+    client: Client<CompatibleConfig>,
     model: String,
     max_tokens: u16,
     temperature: f32,
+    /// Requests per analysis, counting re-asks after malformed replies
+    max_attempts: u32,
+}
+
+/// Where and how to reach an OpenAI-compatible chat completions API
+#[derive(Debug, Clone)]
+pub struct OpenAISettings {
+    /// API root, e.g. "http://localhost:11434/v1" for Ollama
+    pub base_url: String,
+    /// Not needed by servers that run without auth
+    pub api_key: Option<String>,
+    /// "Authorization" sends `Bearer <key>`; any other header sends the bare key
+    pub auth_header: String,
+    pub model: String,
+    pub max_tokens: u16,
+    pub temperature: f32,
+    pub max_attempts: u32,
+}
+
+impl Default for OpenAISettings {
+    fn default() -> Self {
+        Self {
+            base_url: OPENAI_API_BASE.to_string(),
+            api_key: None,
+            auth_header: AUTHORIZATION.to_string(),
+            model: "gpt-4o-mini".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_attempts: 3,
+        }
+    }
+}
+
+impl OpenAISettings {
+    /// Settings from `OPENAI_*` variables. A key is required only for the
+    /// default endpoint; a custom `OPENAI_BASE_URL` may run without one.
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let defaults = Self::default();
+        let api_key = env::var("OPENAI_API_KEY").ok().filter(|key| !key.is_empty());
+        let base_url = env::var("OPENAI_BASE_URL").ok().filter(|url| !url.is_empty());
+        if api_key.is_none() && base_url.is_none() {
+            return Err("OPENAI_API_KEY not set in environment".into());
+        }
+        
+        Ok(Self {
+            base_url: base_url.unwrap_or(defaults.base_url),
+            api_key,
+            auth_header: env::var("OPENAI_AUTH_HEADER").unwrap_or(defaults.auth_header),
+            model: env::var("OPENAI_MODEL").unwrap_or(defaults.model),
+            max_tokens: env::var("OPENAI_MAX_TOKENS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_tokens),
+            temperature: env::var("OPENAI_TEMPERATURE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.temperature),
+            max_attempts: env::var("OPENAI_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|attempts| *attempts > 0)
+                .unwrap_or(defaults.max_attempts),
+        })
+    }
+}
+
+/// Client config for any OpenAI-compatible server. Unlike `OpenAIConfig` it
+/// sends no auth header without a key and lets the header name vary.
+#[derive(Clone)]
+struct CompatibleConfig {
+    api_base: String,
+    api_key: Secret<String>,
+    auth_header: HeaderName,
+}
+
+impl Config for CompatibleConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let key = self.api_key.expose_secret();
+        if key.is_empty() {
+            return headers;
+        }
+        let value = if self.auth_header == AUTHORIZATION {
+            format!("Bearer {}", key)
+        } else {
+            key.clone()
+        };
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(self.auth_header.clone(), value);
+        }
+        headers
+    }
+    
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.api_base, path)
+    }
+    
+    fn query(&self) -> Vec<(&str, &str)> {
+        vec![]
+    }
+    
+    fn api_base(&self) -> &str {
+        &self.api_base
+    }
+    
+    fn api_key(&self) -> &Secret<String> {
+        &self.api_key
+    }
 }
 
 impl AINetworkAnalyzer {
     /// Create new AI analyzer from environment variables
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        let api_key = env::var("OPENAI_API_KEY")
-            .map_err(|_| "OPENAI_API_KEY not set in environment")?;
-        
-        let model = env::var("OPENAI_MODEL")
-            .unwrap_or_else(|_| "gpt-4o-mini".to_string());
-        
-        let max_tokens: u16 = env::var("OPENAI_MAX_TOKENS")
-            .unwrap_or_else(|_| "2000".to_string())
-            .parse()
-            .unwrap_or(2000);
-        
-        let temperature: f32 = env::var("OPENAI_TEMPERATURE")
-            .unwrap_or_else(|_| "0.7".to_string())
-            .parse()
-            .unwrap_or(0.7);
-        
-        let config = async_openai::config::OpenAIConfig::new()
-            .with_api_key(api_key);
-        
-        let client = Client::with_config(config);
+        Self::new(OpenAISettings::from_env()?)
+    }
+    
+    pub fn new(settings: OpenAISettings) -> Result<Self, Box<dyn std::error::Error>> {
+        let auth_header = HeaderName::from_bytes(settings.auth_header.trim().as_bytes())
+            .map_err(|_| format!("Invalid auth header name: {}", settings.auth_header))?;
+        let config = CompatibleConfig {
+            api_base: settings.base_url.trim_end_matches('/').to_string(),
+            api_key: Secret::new(settings.api_key.unwrap_or_default()),
+            auth_header,
+        };
         
         Ok(Self {
-            client,
-            model,
-            max_tokens,
-            temperature,
+            client: Client::with_config(config),
+            model: settings.model,
+            max_tokens: settings.max_tokens,
+            temperature: settings.temperature,
+            max_attempts: settings.max_attempts.max(1),
         })
     }
     
@@ -118,22 +341,65 @@ impl AINetworkAnalyzer {
         // Create detailed context for AI
         let context = self.create_context(test_result, loaded_latency, aim_scores);
         
-        // Generate insights
-        let summary = self.generate_summary(&context).await?;
-        let detailed_analysis = self.generate_detailed_analysis(&context).await?;
-        let recommendations = self.generate_recommendations(&context).await?;
-        let predictions = self.generate_predictions(&context).await?;
-        let simple_explanation = self.generate_simple_explanation(&context).await?;
+        // One request for the whole object; malformed replies are re-asked
+        // with the validation error, up to `max_attempts` requests in total
+        let mut messages = vec![
+            ChatCompletionRequestMessage::System(
+                ChatCompletionRequestSystemMessageArgs::default()
+                    .content(format!(
+                        "You are a network performance expert helping users understand \
+                         their internet connection quality. Be clear, accurate, and actionable. \
+                         Focus on real-world impact and practical solutions, especially bufferbloat, \
+                         latency under load and use-case suitability.\n\n\
+                         Reply with a single JSON object and nothing else, shaped like this:\n{}\n\n\
+                         Give {}-{} recommendations, most urgent first, and {}-{} predictions.",
+                        INSIGHTS_SCHEMA,
+                        RECOMMENDATIONS_RANGE.start(), RECOMMENDATIONS_RANGE.end(),
+                        PREDICTIONS_RANGE.start(), PREDICTIONS_RANGE.end()
+                    ))
+                    .build()?
+            ),
+            ChatCompletionRequestMessage::User(
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(context)
+                    .build()?
+            ),
+        ];
         
-        log::info!("✅ AI insights generated successfully");
+        for attempt in 1..=self.max_attempts {
+            let (reply, finish_reason) = self.call_openai(&messages).await?;
+            
+            let parsed = if finish_reason == Some(FinishReason::Length) {
+                Err("the reply was cut off at the token limit; keep it shorter".to_string())
+            } else {
+                AIInsights::parse_reply(&reply)
+            };
+            match parsed {
+                Ok(mut insights) => {
+                    insights.provider = self.name().to_string();
+                    log::info!("✅ AI insights generated successfully (attempt {})", attempt);
+                    return Ok(insights);
+                }
+                Err(e) => {
+                    log::warn!("⚠️ Malformed AI insights (attempt {}/{}): {}", attempt, self.max_attempts, e);
+                    messages.push(ChatCompletionRequestMessage::Assistant(
+                        ChatCompletionRequestAssistantMessageArgs::default()
+                            .content(reply)
+                            .build()?
+                    ));
+                    messages.push(ChatCompletionRequestMessage::User(
+                        ChatCompletionRequestUserMessageArgs::default()
+                            .content(format!(
+                                "That reply is invalid: {}. Reply again with only the corrected JSON object.",
+                                e
+                            ))
+                            .build()?
+                    ));
+                }
+            }
+        }
         
-        Ok(AIInsights {
-            summary,
-            detailed_analysis,
-            recommendations,
-            predictions,
-            simple_explanation,
-        })
+        Err(format!("no valid insights after {} attempts", self.max_attempts).into())
     }
     
     /// Create rich context from test results
@@ -143,7 +409,7 @@ impl AINetworkAnalyzer {
         loaded_latency: &LoadedLatencyResult,
         aim_scores: &AIMScores,
     ) -> String {
-        format!(
+        let mut context = format!(
             "Network Speed Test Results:\n\
             \n\
             === Basic Metrics ===\n\
@@ -160,12 +426,10 @@ impl AINetworkAnalyzer {
             Grade: {}\n\
             Download Increase: +{:.0}% ({:.1}ms)\n\
             Upload Increase: +{:.0}% ({:.1}ms)\n\
+            Packet Loss Under Load: {}\n\
             \n\
             === Use-Case Scores (AIM) ===\n\
-            Gaming: {:.0}/100 ({})\n\
-            Streaming: {:.0}/100 ({})\n\
-            Video Conferencing: {:.0}/100 ({})\n\
-            General Browsing: {:.0}/100 ({})\n\
+            {}\
             Overall: {:.0}/100 ({})\n\
             \n\
             === Connection Details ===\n\
@@ -186,141 +450,115 @@ impl AINetworkAnalyzer {
             loaded_latency.bufferbloat_download_ms,
             loaded_latency.bufferbloat_upload_ratio * 100.0,
             loaded_latency.bufferbloat_upload_ms,
-            aim_scores.gaming.score,
-            aim_scores.gaming.grade.as_str(),
-            aim_scores.streaming.score,
-            aim_scores.streaming.grade.as_str(),
-            aim_scores.video_conferencing.score,
-            aim_scores.video_conferencing.grade.as_str(),
-            aim_scores.general_browsing.score,
-            aim_scores.general_browsing.grade.as_str(),
+            loaded_latency.loaded_loss_percentage()
+                .map(|loss| format!("{:.2}%", loss))
+                .unwrap_or_else(|| "not measured".to_string()),
+            aim_scores.use_cases.iter()
+                .map(|s| format!("{}: {:.0}/100 ({})\n", s.label, s.score, s.grade.as_str()))
+                .collect::<String>(),
             aim_scores.overall_score,
             aim_scores.overall_grade.as_str(),
             test_result.protocol,
             test_result.server_id,
             test_result.test_duration_ms
-        )
-    }
-    
-    /// Generate natural language summary
-    async fn generate_summary(&self, context: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let prompt = format!(
-            "Based on these network test results, provide a concise 2-3 sentence summary \
-            highlighting the most important findings. Be direct and actionable.\n\n{}",
-            context
-        );
-        
-        self.call_openai(&prompt).await
-    }
-    
-    /// Generate detailed technical analysis
-    async fn generate_detailed_analysis(&self, context: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let prompt = format!(
-            "Provide a detailed technical analysis of these network test results. \
-            Explain what the metrics mean, identify any issues, and explain their impact. \
-            Focus on bufferbloat, latency under load, and use-case suitability.\n\n{}",
-            context
-        );
-        
-        self.call_openai(&prompt).await
-    }
-    
-    /// Generate prioritized recommendations
-    async fn generate_recommendations(&self, context: &str) -> Result<Vec<AIRecommendation>, Box<dyn std::error::Error>> {
-        let prompt = format!(
-            "Based on these network test results, provide 3-5 prioritized recommendations \
-            for improving network performance. For each recommendation, specify:\n\
-            1. Priority (Critical/High/Medium/Low)\n\
-            2. Title (brief)\n\
-            3. Description (what to do)\n\
-            4. Expected improvement\n\
-            5. Difficulty (Easy/Medium/Hard/Advanced)\n\
-            \n\
-            Format as JSON array with fields: priority, title, description, expected_improvement, difficulty\n\n{}",
-            context
         );
         
-        let response = self.call_openai(&prompt).await?;
+        if let Some(one_way) = &loaded_latency.upload_one_way {
+            context.push_str(&format!(
+                "\n\n=== One-Way Delay While Uploading ===\n\
+                Upstream: {:.1}ms (jitter {:.1}ms)\n\
+                Downstream: {:.1}ms (jitter {:.1}ms)",
+                one_way.upstream.avg_ms, one_way.upstream.jitter_ms,
+                one_way.downstream.avg_ms, one_way.downstream.jitter_ms
+            ));
+        }
         
-        // Parse JSON response
-        match serde_json::from_str::<Vec<AIRecommendation>>(&response) {
-            Ok(recs) => Ok(recs),
-            Err(_) => {
-                // Fallback if JSON parsing fails
-                Ok(vec![AIRecommendation {
-                    priority: Priority::Medium,
-                    title: "Network Optimization Needed".to_string(),
-                    description: response,
-                    expected_improvement: "Improved network performance".to_string(),
-                    difficulty: Difficulty::Medium,
-                }])
+        for direction in [LoadDirection::Download, LoadDirection::Upload] {
+            let consistency = loaded_latency.throughput_consistency(direction);
+            let stalls = loaded_latency.throughput_stalls(direction);
+            if consistency.is_none() && stalls.is_none() {
+                continue;
+            }
+            context.push_str(&format!("\n\n=== {:?} Throughput Stability ===", direction));
+            if let Some(consistency) = consistency {
+                context.push_str(&format!(
+                    "\nMedian: {:.1} Mbps, p10-p90 {:.1}-{:.1} Mbps\n\
+                    Robust Variation: {:.1}% ({})",
+                    consistency.median_speed,
+                    consistency.p10_speed, consistency.p90_speed,
+                    consistency.robust_variation, consistency.stability_grade.label()
+                ));
+            }
+            if let Some(stalls) = stalls {
+                context.push_str(&format!(
+                    "\nStalls: {} ({} ms total, longest {} ms), sudden drops: {}",
+                    stalls.stall_count, stalls.total_stall_ms, stalls.longest_stall_ms, stalls.drop_count
+                ));
             }
         }
-    }
-    
-    /// Predict potential issues
-    async fn generate_predictions(&self, context: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let prompt = format!(
-            "Based on these network test results, predict 2-3 potential issues that may occur. \
-            Focus on real-world impact: gaming lag, video call freezing, streaming buffering, etc.\n\n{}",
-            context
-        );
         
-        let response = self.call_openai(&prompt).await?;
+        if !aim_scores.voice_calls.is_empty() {
+            context.push_str("\n\n=== Voice Calls (ITU-T G.107 E-model) ===");
+            for call in &aim_scores.voice_calls {
+                context.push_str(&format!(
+                    "\n{}: MOS {:.2}, R-factor {:.0} ({})",
+                    call.codec.as_str(), call.mos, call.r_factor, call.rating.description()
+                ));
+            }
+        }
         
-        // Split into bullet points
-        let predictions: Vec<String> = response
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| line.trim().to_string())
-            .collect();
+        let capacity = CapacityCalculator::estimate(test_result, loaded_latency);
+        context.push_str(&format!("\n\n=== Household Capacity ===\n{}", capacity.summary));
         
-        Ok(predictions)
-    }
-    
-    /// Generate simple explanation (ELI5)
-    async fn generate_simple_explanation(&self, context: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let prompt = format!(
-            "Explain these network test results in simple terms that anyone can understand. \
-            Use analogies if helpful. Focus on what the user can actually do with their connection. \
-            Make it friendly and conversational.\n\n{}",
-            context
-        );
+        let caveats = loaded_latency.caveats();
+        if !caveats.is_empty() {
+            context.push_str("\n\n=== Measurement Quality ===\n");
+            for caveat in &caveats {
+                context.push_str(&format!("- {}\n", caveat));
+            }
+            context.push_str("Treat conclusions drawn from these stages as tentative.");
+        }
         
-        self.call_openai(&prompt).await
+        context
     }
     
-    /// Call OpenAI API with retry logic
-    async fn call_openai(&self, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    /// One chat completion; returns the reply and why generation stopped
+    async fn call_openai(
+        &self,
+        messages: &[ChatCompletionRequestMessage],
+    ) -> Result<(String, Option<FinishReason>), Box<dyn std::error::Error>> {
         let request = CreateChatCompletionRequestArgs::default()
             .model(&self.model)
             .max_tokens(self.max_tokens)
             .temperature(self.temperature)
-            .messages(vec![
-                ChatCompletionRequestMessage::System(
-                    ChatCompletionRequestSystemMessageArgs::default()
-                        .content("You are a network performance expert helping users understand \
-                                 their internet connection quality. Be clear, accurate, and actionable. \
-                                 Focus on real-world impact and practical solutions.")
-                        .build()?
-                ),
-                ChatCompletionRequestMessage::User(
-                    ChatCompletionRequestUserMessageArgs::default()
-                        .content(prompt)
-                        .build()?
-                ),
-            ])
+            .response_format(ChatCompletionResponseFormat {
+                r#type: ChatCompletionResponseFormatType::JsonObject,
+            })
+            .messages(messages.to_vec())
             .build()?;
         
         let response = self.client.chat().create(request).await?;
         
-        let content = response
-            .choices
-            .first()
-            .and_then(|choice| choice.message.content.clone())
-            .ok_or("No response from OpenAI")?;
+        let choice = response.choices.into_iter().next().ok_or("No response from OpenAI")?;
+        let content = choice.message.content.ok_or("No response from OpenAI")?;
         
-        Ok(content)
+        Ok((content, choice.finish_reason))
+    }
+}
+
+#[async_trait(?Send)]
+impl InsightProvider for AINetworkAnalyzer {
+    fn name(&self) -> &str {
+        "openai"
+    }
+    
+    async fn generate(
+        &self,
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        aim_scores: &AIMScores,
+    ) -> Result<AIInsights, Box<dyn std::error::Error>> {
+        self.analyze_network(test_result, loaded_latency, aim_scores).await
     }
 }
 
@@ -365,6 +603,48 @@ impl Difficulty {
 }
 
 impl AIInsights {
+    /// Parse and validate a model reply
+    pub fn parse_reply(reply: &str) -> Result<Self, String> {
+        let insights: AIInsights = serde_json::from_str(reply.trim())
+            .map_err(|e| format!("not an insights object: {}", e))?;
+        insights.validate()?;
+        Ok(insights)
+    }
+    
+    /// Check that every text is filled in and the lists have sensible lengths
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, text) in [
+            ("summary", &self.summary),
+            ("detailed_analysis", &self.detailed_analysis),
+            ("simple_explanation", &self.simple_explanation),
+        ] {
+            if text.trim().is_empty() {
+                return Err(format!("{}: must not be empty", field));
+            }
+        }
+        if !RECOMMENDATIONS_RANGE.contains(&self.recommendations.len()) {
+            return Err(format!(
+                "recommendations: expected {}-{}, got {}",
+                RECOMMENDATIONS_RANGE.start(), RECOMMENDATIONS_RANGE.end(), self.recommendations.len()
+            ));
+        }
+        for (i, rec) in self.recommendations.iter().enumerate() {
+            if rec.title.trim().is_empty() || rec.description.trim().is_empty() {
+                return Err(format!("recommendations[{}]: title and description must not be empty", i));
+            }
+        }
+        if !PREDICTIONS_RANGE.contains(&self.predictions.len()) {
+            return Err(format!(
+                "predictions: expected {}-{}, got {}",
+                PREDICTIONS_RANGE.start(), PREDICTIONS_RANGE.end(), self.predictions.len()
+            ));
+        }
+        if self.predictions.iter().any(|p| p.trim().is_empty()) {
+            return Err("predictions: entries must not be empty".to_string());
+        }
+        Ok(())
+    }
+    
     /// Get formatted display of AI insights
     pub fn display(&self) -> String {
         let mut output = String::new();
@@ -395,7 +675,7 @@ impl AIInsights {
         for prediction in &self.predictions {
             output.push_str(&format!("• {}\n", prediction));
         }
-        output.push_str("\n");
+        output.push('\n');
         
         output.push_str("💬 SIMPLE EXPLANATION\n");
         output.push_str(&format!("{}\n", self.simple_explanation));
@@ -407,6 +687,9 @@ impl AIInsights {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::aim_scoring::AIMCalculator;
+    use crate::services::loaded_latency::LoadedLatencyTester;
+    use crate::services::mock_completion::{MockCompletionServer, MockReply, RequestLog};
     
     #[test]
     fn test_priority_display() {
@@ -419,4 +702,104 @@ mod tests {
         assert_eq!(Difficulty::Easy.as_str(), "Easy");
         assert_eq!(Difficulty::Easy.emoji(), "✅");
     }
+    
+    #[test]
+    fn test_provider_kind_names_match_serde() {
+        for kind in InsightProviderKind::ALL {
+            assert_eq!(serde_json::to_string(&kind).unwrap(), format!("\"{}\"", kind.as_str()));
+            assert_eq!(InsightProviderKind::from_name(kind.as_str()), Some(kind));
+        }
+        assert_eq!(InsightProviderKind::from_name("open_ai"), None);
+    }
+    
+    #[test]
+    fn test_parse_reply_validates_against_types() {
+        let reply = serde_json::json!({
+            "summary": "Fast, but latency triples under upload.",
+            "detailed_analysis": "Upload bufferbloat grade D.",
+            "recommendations": [{
+                "priority": "Critical",
+                "title": "Enable SQM",
+                "description": "Turn on Smart Queue Management in the router.",
+                "expected_improvement": "Upload latency below 50ms",
+                "difficulty": "Medium"
+            }],
+            "predictions": ["Video calls freeze during uploads"],
+            "simple_explanation": "Your connection is a wide road with a narrow on-ramp."
+        });
+        let insights = AIInsights::parse_reply(&reply.to_string()).unwrap();
+        assert_eq!(insights.recommendations[0].priority, Priority::Critical);
+        
+        let mut wrong_priority = reply.clone();
+        wrong_priority["recommendations"][0]["priority"] = "urgent".into();
+        assert!(AIInsights::parse_reply(&wrong_priority.to_string()).unwrap_err().contains("urgent"));
+        
+        let mut no_recommendations = reply.clone();
+        no_recommendations["recommendations"] = serde_json::json!([]);
+        assert!(AIInsights::parse_reply(&no_recommendations.to_string()).unwrap_err().starts_with("recommendations"));
+        
+        assert!(AIInsights::parse_reply("1. Enable SQM\n2. Use Ethernet").is_err());
+    }
+    
+    async fn mock_analyzer(replies: Vec<MockReply>, api_key: Option<&str>, auth_header: &str) -> (AINetworkAnalyzer, RequestLog) {
+        let server = MockCompletionServer::bind("127.0.0.1:0", replies).await.unwrap();
+        let settings = OpenAISettings {
+            base_url: server.base_url().unwrap(),
+            api_key: api_key.map(str::to_string),
+            auth_header: auth_header.to_string(),
+            model: "local-model".to_string(),
+            ..OpenAISettings::default()
+        };
+        let log = server.requests();
+        tokio::spawn(server.run());
+        (AINetworkAnalyzer::new(settings).unwrap(), log)
+    }
+    
+    fn sample_inputs() -> (TestResult, LoadedLatencyResult, AIMScores) {
+        let mut test_result = TestResult::new("test".to_string(), "127.0.0.1".to_string());
+        test_result.download_mbps = 200.0;
+        test_result.upload_mbps = 20.0;
+        let loaded_latency = LoadedLatencyTester::new().calculate_results();
+        let aim_scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        (test_result, loaded_latency, aim_scores)
+    }
+    
+    #[tokio::test]
+    async fn test_self_hosted_endpoint_without_key() {
+        let (analyzer, log) = mock_analyzer(
+            vec![MockReply::truncated("{\"summary\": \"Fast downloads"), MockReply::content("Enable SQM.")],
+            None,
+            "Authorization",
+        ).await;
+        let (test_result, loaded_latency, aim_scores) = sample_inputs();
+        
+        let insights = analyzer.analyze_network(&test_result, &loaded_latency, &aim_scores).await.unwrap();
+        assert_eq!(insights.provider, "openai");
+        assert_eq!(insights.recommendations[0].priority, Priority::High);
+        
+        // Truncated and malformed replies were each re-asked
+        let requests = log.all();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].path, "/v1/chat/completions");
+        assert!(!requests[0].headers.contains_key("authorization"));
+        assert_eq!(requests[0].body["model"], "local-model");
+        assert_eq!(requests[0].body["response_format"]["type"], "json_object");
+        assert_eq!(requests[2].body["messages"].as_array().unwrap().len(), 6);
+    }
+    
+    #[tokio::test]
+    async fn test_custom_auth_header_and_fallback() {
+        let (analyzer, log) = mock_analyzer(vec![MockReply::content("not json"); 3], Some("secret"), "api-key").await;
+        let (test_result, loaded_latency, aim_scores) = sample_inputs();
+        
+        let insights = generate_insights(&analyzer, &test_result, &loaded_latency, &aim_scores).await;
+        assert_eq!(insights.provider, "rules");
+        
+        let requests = log.all();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|request| request.headers.get("api-key").map(String::as_str) == Some("secret")));
+        assert!(!requests[0].headers.contains_key("authorization"));
+        
+        assert!(AINetworkAnalyzer::new(OpenAISettings { auth_header: "bad header".to_string(), ..OpenAISettings::default() }).is_err());
+    }
 }
@@ -1,53 +1,200 @@
-/// AIM (Aggregated Internet Measurement) Scoring System
-/// 
-/// Based on Cloudflare's approach to translate raw metrics into
-/// use-case specific quality scores that users can understand.
-/// 
-/// Instead of showing raw numbers, we answer:
-/// - "Is my internet good for gaming?"
-/// - "Can I stream 4K video?"
-/// - "Will video calls work smoothly?"
+//! AIM (Aggregated Internet Measurement) Scoring System
+//! 
+//! Based on Cloudflare's approach to translate raw metrics into
+//! use-case specific quality scores that users can understand.
+//! 
+//! Instead of showing raw numbers, we answer:
+//! - "Is my internet good for gaming?"
+//! - "Can I stream 4K video?"
+//! - "Will video calls work smoothly?"
+//! - "Will my backups finish overnight?"
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 use crate::models::TestResult;
+use crate::services::i18n::{self, Locale, Message};
 use crate::services::loaded_latency::LoadedLatencyResult;
+use crate::services::packet_loss::PacketLossResult;
+use crate::services::responsiveness::LoadDirection;
+use crate::services::scoring_profile::{FactorProfile, Metric, ScoringProfile, UseCaseProfile};
+use crate::services::stall_detection::StallAnalysis;
+use crate::services::usage_priorities::{OverallWeights, UsagePreset};
+use crate::services::voip_quality::{VoiceQualityEstimate, VoipQualityCalculator};
 
 /// Complete AIM scores for all use cases
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIMScores {
-    pub gaming: UseCaseScore,
-    pub streaming: UseCaseScore,
-    pub video_conferencing: UseCaseScore,
-    pub general_browsing: UseCaseScore,
+    /// One score per use case in the scoring profile, in `UseCase` order
+    pub use_cases: Vec<UseCaseScore>,
+    /// E-model (G.107) call quality per voice codec; empty when packet loss
+    /// was not measured
+    #[serde(default)]
+    pub voice_calls: Vec<VoiceQualityEstimate>,
     
     /// Overall quality score (0-100)
     pub overall_score: f64,
     pub overall_grade: QualityGrade,
+    /// Use-case weights behind the overall score
+    #[serde(default)]
+    pub overall_weights: OverallWeights,
+    /// Scoring profile version that produced these scores, e.g. "aim-v3"
+    #[serde(default)]
+    pub scoring_version: String,
 }
 
 /// Score for a specific use case
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UseCaseScore {
+    pub use_case: UseCase,
+    
+    /// Display name, e.g. "Cloud Gaming"
+    pub label: String,
+    
     /// Numerical score (0-100)
     pub score: f64,
     
+    /// Some factors were not measured and the score covers only the rest;
+    /// `caveats` says which
+    #[serde(default)]
+    pub partial: bool,
+    
     /// Letter grade
     pub grade: QualityGrade,
     
     /// Human-readable assessment
-    pub assessment: String,
+    pub assessment: Message,
     
     /// Detailed explanation
-    pub explanation: String,
+    pub explanation: Message,
     
     /// Emoji indicator
     pub emoji: String,
     
     /// What works / what doesn't
-    pub capabilities: Vec<String>,
+    pub capabilities: Vec<Message>,
     
     /// Recommendations for improvement
-    pub recommendations: Vec<String>,
+    pub recommendations: Vec<Message>,
+    
+    /// Latency stages behind this score that were noisy or under-sampled
+    #[serde(default)]
+    pub caveats: Vec<Message>,
+    
+    /// How each factor contributed to `score`
+    #[serde(default)]
+    pub factors: Vec<FactorScore>,
+}
+
+/// One scored factor of a use case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorScore {
+    pub metric: Metric,
+    /// Display name, e.g. "Jitter"
+    pub label: String,
+    pub unit: String,
+    /// `None` when the metric was not measured
+    pub value: Option<f64>,
+    /// Share of the use-case score this factor accounts for, in percent
+    pub weight: f64,
+    pub points_earned: f64,
+    pub points_possible: f64,
+    /// Threshold band the value fell into, e.g. "20–50 ms"; `None` when unmeasured
+    pub band: Option<String>,
+}
+
+/// What a connection is scored for; declaration order is report order
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum UseCase {
+    Gaming,
+    Streaming,
+    VideoConferencing,
+    GeneralBrowsing,
+    /// Game streaming services such as GeForce NOW or Xbox Cloud Gaming
+    CloudGaming,
+    /// RDP, Citrix and other VDI sessions
+    RemoteDesktop,
+    /// Large backups and file sync, i.e. sustained upload
+    CloudBackup,
+    /// Always-on IoT devices, cameras and voice assistants
+    SmartHome,
+}
+
+impl UseCase {
+    pub const ALL: [UseCase; 8] = [
+        UseCase::Gaming,
+        UseCase::Streaming,
+        UseCase::VideoConferencing,
+        UseCase::GeneralBrowsing,
+        UseCase::CloudGaming,
+        UseCase::RemoteDesktop,
+        UseCase::CloudBackup,
+        UseCase::SmartHome,
+    ];
+    
+    pub fn as_str(&self) -> &str {
+        match self {
+            UseCase::Gaming => "gaming",
+            UseCase::Streaming => "streaming",
+            UseCase::VideoConferencing => "video_conferencing",
+            UseCase::GeneralBrowsing => "general_browsing",
+            UseCase::CloudGaming => "cloud_gaming",
+            UseCase::RemoteDesktop => "remote_desktop",
+            UseCase::CloudBackup => "cloud_backup",
+            UseCase::SmartHome => "smart_home",
+        }
+    }
+    
+    pub fn label(&self) -> &str {
+        match self {
+            UseCase::Gaming => "Gaming",
+            UseCase::Streaming => "Streaming",
+            UseCase::VideoConferencing => "Video Conferencing",
+            UseCase::GeneralBrowsing => "General Browsing",
+            UseCase::CloudGaming => "Cloud Gaming",
+            UseCase::RemoteDesktop => "Remote Desktop",
+            UseCase::CloudBackup => "Cloud Backup",
+            UseCase::SmartHome => "Smart Home",
+        }
+    }
+    
+    pub fn emoji(&self) -> &str {
+        match self {
+            UseCase::Gaming => "🎮",
+            UseCase::Streaming => "📺",
+            UseCase::VideoConferencing => "💼",
+            UseCase::GeneralBrowsing => "🌐",
+            UseCase::CloudGaming => "🕹️",
+            UseCase::RemoteDesktop => "🖥️",
+            UseCase::CloudBackup => "☁️",
+            UseCase::SmartHome => "🏠",
+        }
+    }
+    
+    /// Parse an identifier or a common short name ("video", "vdi", "iot", ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let use_case = match name.as_str() {
+            "conferencing" | "video" => UseCase::VideoConferencing,
+            "browsing" | "general" => UseCase::GeneralBrowsing,
+            "remote" | "vdi" => UseCase::RemoteDesktop,
+            "backup" | "sync" => UseCase::CloudBackup,
+            "iot" => UseCase::SmartHome,
+            _ => return Self::ALL.into_iter().find(|u| u.as_str() == name),
+        };
+        Some(use_case)
+    }
+    
+    /// One-line verdict for a grade
+    pub fn assessment(&self, grade: &QualityGrade) -> Message {
+        Message::new(format!("assessment.{}.{}", self.as_str(), grade.key()), &[])
+    }
+    
+    /// Display name in `locale`
+    pub fn localized_label(&self, locale: Locale) -> String {
+        i18n::translate(locale, &format!("use_case.{}", self.as_str()), &BTreeMap::new())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -80,6 +227,17 @@ impl QualityGrade {
         }
     }
     
+    /// Catalog key part, e.g. "very_poor"
+    pub fn key(&self) -> &str {
+        match self {
+            QualityGrade::Excellent => "excellent",
+            QualityGrade::Good => "good",
+            QualityGrade::Fair => "fair",
+            QualityGrade::Poor => "poor",
+            QualityGrade::VeryPoor => "very_poor",
+        }
+    }
+    
     pub fn emoji(&self) -> &str {
         match self {
             QualityGrade::Excellent => "⭐",
@@ -91,28 +249,77 @@ impl QualityGrade {
     }
 }
 
+/// Scores are computed from a `ScoringProfile`. All jitter inputs are
+/// `TestResult::jitter_ms`, i.e. the RFC 3550 interarrival jitter of the
+/// idle latency probes
 pub struct AIMCalculator;
 
+/// A metric value plus the extra placeholders its band texts may use
+struct MetricReading {
+    value: f64,
+    placeholders: Vec<(&'static str, String)>,
+}
+
+/// Points and texts gathered from the factors of one use case
+struct Evaluation {
+    score: f64,
+    partial: bool,
+    capabilities: Vec<Message>,
+    recommendations: Vec<Message>,
+    caveats: Vec<Message>,
+    factors: Vec<FactorScore>,
+}
+
 impl AIMCalculator {
-    /// Calculate all AIM scores from test results
+    /// Calculate all AIM scores with the built-in profile
+    /// `packet_loss` is the idle UDP loss test; pass `None` when it was not run
     pub fn calculate_all_scores(
         test_result: &TestResult,
         loaded_latency: &LoadedLatencyResult,
+        packet_loss: Option<&PacketLossResult>,
     ) -> AIMScores {
-        log::info!("📊 Calculating AIM scores for all use cases...");
-        
-        let gaming = Self::calculate_gaming_score(test_result, loaded_latency);
-        let streaming = Self::calculate_streaming_score(test_result, loaded_latency);
-        let video_conferencing = Self::calculate_conferencing_score(test_result, loaded_latency);
-        let general_browsing = Self::calculate_browsing_score(test_result, loaded_latency);
-        
-        // Overall score is weighted average
-        let overall_score = (
-            gaming.score * 0.25 +
-            streaming.score * 0.25 +
-            video_conferencing.score * 0.25 +
-            general_browsing.score * 0.25
-        );
+        Self::calculate_with_profile(ScoringProfile::builtin(), test_result, loaded_latency, packet_loss)
+    }
+    
+    /// Calculate the AIM scores of every use case in `profile`
+    pub fn calculate_with_profile(
+        profile: &ScoringProfile,
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        packet_loss: Option<&PacketLossResult>,
+    ) -> AIMScores {
+        let weights = OverallWeights::from_profile(profile);
+        Self::calculate_weighted(profile, &weights, test_result, loaded_latency, packet_loss)
+    }
+    
+    /// Calculate the AIM scores of every use case in `profile`, weighting the
+    /// overall score by the household's priorities
+    pub fn calculate_weighted(
+        profile: &ScoringProfile,
+        weights: &OverallWeights,
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        packet_loss: Option<&PacketLossResult>,
+    ) -> AIMScores {
+        log::info!("📊 Calculating AIM scores for {} use cases ('{}' profile {}, {} weights)...",
+            profile.use_cases.len(), profile.name, profile.version, weights.label());
+        
+        let use_cases: Vec<UseCaseScore> = profile.use_cases.iter()
+            .map(|(use_case, use_case_profile)| {
+                Self::calculate_use_case_score(*use_case, use_case_profile, test_result, loaded_latency, packet_loss)
+            })
+            .collect();
+        let voice_calls = VoipQualityCalculator::estimate_all(test_result, loaded_latency, packet_loss);
+        
+        // Overall score is the weighted average of the scored use cases
+        let total_weight: f64 = use_cases.iter().map(|s| weights.weight(s.use_case)).sum();
+        let overall_score = if total_weight > 0.0 {
+            use_cases.iter()
+                .map(|s| s.score * weights.weight(s.use_case))
+                .sum::<f64>() / total_weight
+        } else {
+            0.0
+        };
         
         let overall_grade = QualityGrade::from_score(overall_score);
         
@@ -120,488 +327,297 @@ impl AIMCalculator {
             overall_score, overall_grade.as_str());
         
         AIMScores {
-            gaming,
-            streaming,
-            video_conferencing,
-            general_browsing,
+            use_cases,
+            voice_calls,
             overall_score,
             overall_grade,
+            overall_weights: weights.clone(),
+            scoring_version: profile.version.clone(),
         }
     }
     
-    /// Gaming Score - Latency and jitter are MOST important
-    /// 
-    /// Weights:
-    /// - Loaded latency: 50% (critical for responsiveness)
-    /// - Jitter: 25% (causes inconsistent gameplay)
-    /// - Packet loss: 15% (causes rubber-banding)
-    /// - Download speed: 10% (less critical - games use <10 Mbps)
-    pub fn calculate_gaming_score(
+    /// Score one use case against its factors
+    pub fn calculate_use_case_score(
+        use_case: UseCase,
+        profile: &UseCaseProfile,
         test_result: &TestResult,
         loaded_latency: &LoadedLatencyResult,
+        packet_loss: Option<&PacketLossResult>,
     ) -> UseCaseScore {
-        let mut score = 100.0;
-        let mut issues = Vec::new();
-        let mut capabilities = Vec::new();
-        let mut recommendations = Vec::new();
+        let evaluation = Self::evaluate(profile, test_result, loaded_latency, packet_loss);
+        let grade = QualityGrade::from_score(evaluation.score);
         
-        // 1. Loaded Latency (50 points) - CRITICAL for gaming
-        let worst_latency = f64::max(
-            loaded_latency.download_avg_ms,
-            loaded_latency.upload_avg_ms
-        );
-        
-        let latency_score = match worst_latency {
-            l if l < 20.0 => {
-                capabilities.push("Perfect for competitive gaming (esports-level)".to_string());
-                50.0
-            },
-            l if l < 50.0 => {
-                capabilities.push("Excellent for online gaming".to_string());
-                45.0
-            },
-            l if l < 80.0 => {
-                capabilities.push("Good for most online games".to_string());
-                issues.push(format!("Latency {}ms - noticeable in fast-paced games", l as u32));
-                35.0
-            },
-            l if l < 100.0 => {
-                capabilities.push("Playable for casual games".to_string());
-                issues.push(format!("Latency {}ms - not ideal for competitive play", l as u32));
-                recommendations.push("Reduce bufferbloat to improve latency".to_string());
-                25.0
-            },
-            l if l < 150.0 => {
-                issues.push(format!("High latency {}ms - lag will be noticeable", l as u32));
-                recommendations.push("Enable SQM/QoS on router".to_string());
-                recommendations.push("Consider wired connection instead of WiFi".to_string());
-                15.0
-            },
-            l => {
-                issues.push(format!("Very high latency {}ms - gaming will be frustrating", l as u32));
-                recommendations.push("Check for network congestion".to_string());
-                recommendations.push("Contact ISP about high latency".to_string());
-                5.0
-            }
-        };
-        score = latency_score;
-        
-        // 2. Jitter (25 points)
-        let jitter_score = match test_result.jitter_ms {
-            j if j < 5.0 => {
-                capabilities.push("Consistent performance - no lag spikes".to_string());
-                25.0
-            },
-            j if j < 15.0 => {
-                20.0
-            },
-            j if j < 30.0 => {
-                issues.push(format!("Jitter {}ms - occasional lag spikes", j as u32));
-                15.0
-            },
-            j => {
-                issues.push(format!("High jitter {}ms - frequent lag spikes", j as u32));
-                recommendations.push("Check WiFi signal strength".to_string());
-                5.0
-            }
-        };
-        score += jitter_score;
-        
-        // 3. Packet Loss (15 points)
-        // For now, we'll assume 0% - will implement in Phase 3
-        let packet_loss_pct = 0.0;
-        let packet_loss_score = match packet_loss_pct {
-            p if p < 0.1 => 15.0,
-            p if p < 1.0 => 10.0,
-            p if p < 3.0 => {
-                issues.push(format!("Packet loss {:.1}% - rubber-banding may occur", p));
-                5.0
-            },
-            p => {
-                issues.push(format!("High packet loss {:.1}% - game will be unplayable", p));
-                0.0
-            }
+        let emoji = match use_case {
+            UseCase::Gaming => Self::gaming_emoji(&grade),
+            _ => use_case.emoji().to_string(),
         };
-        score += packet_loss_score;
-        
-        // 4. Download Speed (10 points) - least important for gaming
-        let speed_score = match test_result.download_mbps {
-            s if s >= 25.0 => {
-                10.0
-            },
-            s if s >= 10.0 => {
-                8.0
-            },
-            s if s >= 5.0 => {
-                issues.push("Low bandwidth may cause download delays".to_string());
-                5.0
-            },
-            _ => {
-                issues.push("Very low bandwidth - game downloads will be slow".to_string());
-                2.0
-            }
-        };
-        score += speed_score;
-        
-        let grade = QualityGrade::from_score(score);
-        
-        let assessment = match grade {
-            QualityGrade::Excellent => "Perfect for competitive gaming - esports ready!",
-            QualityGrade::Good => "Great for online gaming - smooth experience",
-            QualityGrade::Fair => "Playable but not ideal - casual gaming okay",
-            QualityGrade::Poor => "Poor gaming experience - lag will be noticeable",
-            QualityGrade::VeryPoor => "Not suitable for online gaming",
-        }.to_string();
-        
-        let explanation = format!(
-            "Gaming requires low latency ({:.0}ms) and stable connection. {}",
-            worst_latency,
-            if worst_latency < 50.0 { "Your connection is excellent for gaming." }
-            else if worst_latency < 100.0 { "Your connection is acceptable but could be better." }
-            else { "High latency will cause noticeable lag." }
-        );
         
-        let emoji = Self::gaming_emoji(&grade);
-
         UseCaseScore {
-            score,
+            use_case,
+            label: use_case.label().to_string(),
+            score: evaluation.score,
+            partial: evaluation.partial,
+            assessment: use_case.assessment(&grade),
+            explanation: Self::explanation(use_case, test_result, loaded_latency),
             grade,
-            assessment,
-            explanation,
             emoji,
-            capabilities,
-            recommendations,
+            capabilities: evaluation.capabilities,
+            recommendations: evaluation.recommendations,
+            caveats: evaluation.caveats,
+            factors: evaluation.factors,
         }
     }
     
-    /// Streaming Score - Download speed and consistency matter most
-    /// 
-    /// Weights:
-    /// - Download speed: 40%
-    /// - Download loaded latency: 30% (buffering prevention)
-    /// - Jitter: 20% (stream stability)
-    /// - Packet loss: 10%
-    pub fn calculate_streaming_score(
-        test_result: &TestResult,
-        loaded_latency: &LoadedLatencyResult,
-    ) -> UseCaseScore {
-        let mut score = 100.0;
-        let mut capabilities = Vec::new();
-        let mut recommendations = Vec::new();
+    fn explanation(use_case: UseCase, test_result: &TestResult, loaded_latency: &LoadedLatencyResult) -> Message {
+        let ms = |value: f64| format!("{:.0}", value);
+        let mbps = |value: f64| format!("{:.1}", value);
         
-        // 1. Download Speed (40 points)
-        let speed_score = match test_result.download_mbps {
-            s if s >= 100.0 => {
-                capabilities.push("8K streaming on multiple devices".to_string());
-                capabilities.push("4K 60fps streaming with headroom".to_string());
-                40.0
-            },
-            s if s >= 50.0 => {
-                capabilities.push("4K streaming on 2-3 devices".to_string());
-                capabilities.push("HD streaming on many devices".to_string());
-                38.0
-            },
-            s if s >= 25.0 => {
-                capabilities.push("4K streaming on 1 device".to_string());
-                capabilities.push("HD streaming on 2-3 devices".to_string());
-                35.0
-            },
-            s if s >= 15.0 => {
-                capabilities.push("HD (1080p) streaming reliably".to_string());
-                30.0
-            },
-            s if s >= 10.0 => {
-                capabilities.push("HD streaming on 1 device".to_string());
-                recommendations.push("4K may buffer occasionally".to_string());
-                25.0
-            },
-            s if s >= 5.0 => {
-                capabilities.push("SD/HD streaming works".to_string());
-                recommendations.push("Avoid 4K streaming".to_string());
-                15.0
-            },
-            s => {
-                recommendations.push(format!("Speed {:.1} Mbps too low for HD", s));
-                recommendations.push("Upgrade plan for better streaming".to_string());
-                5.0
+        match use_case {
+            UseCase::Gaming => {
+                let worst_latency = f64::max(loaded_latency.download_avg_ms, loaded_latency.upload_avg_ms);
+                let verdict = if worst_latency < 50.0 { "low_latency" }
+                    else if worst_latency < 100.0 { "moderate_latency" }
+                    else { "high_latency" };
+                Message::new(format!("explanation.gaming.{}", verdict), &[("latency_ms", ms(worst_latency))])
             }
-        };
-        score = speed_score;
-        
-        // 2. Download Loaded Latency (30 points)
-        let latency_score = match loaded_latency.download_avg_ms {
-            l if l < 50.0 => 30.0,
-            l if l < 100.0 => 25.0,
-            l if l < 200.0 => {
-                recommendations.push("High latency may cause buffering".to_string());
-                20.0
-            },
-            _ => {
-                recommendations.push("Reduce bufferbloat for smoother streaming".to_string());
-                10.0
+            UseCase::Streaming => {
+                let verdict = if test_result.download_mbps >= 25.0 { "fast" }
+                    else if test_result.download_mbps >= 10.0 { "adequate" }
+                    else { "slow" };
+                Message::new(format!("explanation.streaming.{}", verdict), &[("download_mbps", mbps(test_result.download_mbps))])
             }
-        };
-        score += latency_score;
-        
-        // 3. Jitter (20 points)
-        let jitter_score = match test_result.jitter_ms {
-            j if j < 10.0 => 20.0,
-            j if j < 30.0 => 15.0,
-            j if j < 50.0 => 10.0,
-            _ => 5.0,
-        };
-        score += jitter_score;
-        
-        // 4. Packet Loss (10 points)
-        score += 10.0; // Assume good for now
-        
-        let grade = QualityGrade::from_score(score);
-        
-        let assessment = match grade {
-            QualityGrade::Excellent => "Perfect for 4K/8K streaming on multiple devices",
-            QualityGrade::Good => "Great for 4K streaming and HD on multiple devices",
-            QualityGrade::Fair => "HD streaming works, 4K may buffer occasionally",
-            QualityGrade::Poor => "SD/HD only, frequent buffering possible",
-            QualityGrade::VeryPoor => "Streaming will be problematic",
-        }.to_string();
-        
-        let explanation = format!(
-            "Streaming quality depends on download speed ({:.1} Mbps) and stability. {}",
-            test_result.download_mbps,
-            if test_result.download_mbps >= 25.0 { "Your speed is excellent for streaming." }
-            else if test_result.download_mbps >= 10.0 { "Your speed is adequate for HD streaming." }
-            else { "Your speed may struggle with HD content." }
-        );
-        
-        UseCaseScore {
-            score,
-            grade,
-            assessment,
-            explanation,
-            emoji: "📺".to_string(),
-            capabilities,
-            recommendations,
+            UseCase::VideoConferencing => {
+                let verdict = if loaded_latency.upload_avg_ms < 80.0 && test_result.upload_mbps >= 5.0 {
+                    "great"
+                } else if loaded_latency.upload_avg_ms > 150.0 {
+                    "high_latency"
+                } else {
+                    "workable"
+                };
+                Message::new(format!("explanation.video_conferencing.{}", verdict), &[
+                    ("upload_mbps", mbps(test_result.upload_mbps)),
+                    ("latency_ms", ms(loaded_latency.upload_avg_ms)),
+                ])
+            }
+            UseCase::GeneralBrowsing => Message::new("explanation.general_browsing", &[
+                ("download_mbps", mbps(test_result.download_mbps)),
+                ("latency_ms", ms(loaded_latency.idle_avg_ms)),
+            ]),
+            UseCase::CloudGaming => Message::new("explanation.cloud_gaming", &[
+                ("download_mbps", mbps(test_result.download_mbps)),
+                ("latency_ms", ms(loaded_latency.download_avg_ms)),
+            ]),
+            UseCase::RemoteDesktop => Message::new("explanation.remote_desktop", &[
+                ("latency_ms", ms(f64::max(loaded_latency.download_avg_ms, loaded_latency.upload_avg_ms))),
+                ("download_mbps", mbps(test_result.download_mbps)),
+            ]),
+            UseCase::CloudBackup => Message::new("explanation.cloud_backup", &[
+                ("upload_mbps", mbps(test_result.upload_mbps)),
+                ("gb_per_hour", format!("{:.1}", Self::gb_per_hour(test_result.upload_mbps))),
+            ]),
+            UseCase::SmartHome => Message::new("explanation.smart_home", &[
+                ("latency_ms", ms(loaded_latency.idle_avg_ms)),
+            ]),
         }
     }
     
-    /// Video Conferencing Score - Upload and symmetry matter
-    /// 
-    /// Weights:
-    /// - Upload speed: 30%
-    /// - Upload loaded latency: 30% (critical - frozen video!)
-    /// - Jitter: 25%
-    /// - Download speed: 15%
-    pub fn calculate_conferencing_score(
+    /// Score every factor of a use case against its bands
+    fn evaluate(
+        profile: &UseCaseProfile,
         test_result: &TestResult,
         loaded_latency: &LoadedLatencyResult,
-    ) -> UseCaseScore {
-        let mut score = 100.0;
+        packet_loss: Option<&PacketLossResult>,
+    ) -> Evaluation {
+        let mut earned = 0.0;
         let mut capabilities = Vec::new();
         let mut recommendations = Vec::new();
+        let mut unmeasured = Vec::new();
+        let mut factors = Vec::new();
         
-        // 1. Upload Speed (30 points)
-        let upload_score = match test_result.upload_mbps {
-            u if u >= 20.0 => {
-                capabilities.push("4K video calls with screen sharing".to_string());
-                30.0
-            },
-            u if u >= 10.0 => {
-                capabilities.push("HD video calls with screen sharing".to_string());
-                28.0
-            },
-            u if u >= 5.0 => {
-                capabilities.push("HD video calls work well".to_string());
-                25.0
-            },
-            u if u >= 3.0 => {
-                capabilities.push("HD video calls (may struggle with screen share)".to_string());
-                20.0
-            },
-            u if u >= 1.5 => {
-                capabilities.push("SD video calls work".to_string());
-                recommendations.push("HD may be choppy".to_string());
-                15.0
-            },
-            u => {
-                recommendations.push(format!("Upload {:.1} Mbps too low for video", u));
-                recommendations.push("Use audio-only or upgrade plan".to_string());
-                5.0
-            }
-        };
-        score = upload_score;
-        
-        // 2. Upload Loaded Latency (30 points) - CRITICAL
-        let latency_score = match loaded_latency.upload_avg_ms {
-            l if l < 30.0 => {
-                capabilities.push("Smooth real-time conversation".to_string());
-                30.0
-            },
-            l if l < 80.0 => {
-                25.0
-            },
-            l if l < 150.0 => {
-                recommendations.push("Latency may cause awkward pauses".to_string());
-                20.0
-            },
-            l if l < 250.0 => {
-                recommendations.push(format!("High upload latency {}ms", l as u32));
-                recommendations.push("Enable SQM to reduce bufferbloat".to_string());
-                10.0
-            },
-            l => {
-                recommendations.push(format!("Very high upload latency {}ms", l as u32));
-                recommendations.push("Video will freeze frequently".to_string());
-                5.0
-            }
-        };
-        score += latency_score;
-        
-        // 3. Jitter (25 points)
-        let jitter_score = match test_result.jitter_ms {
-            j if j < 10.0 => 25.0,
-            j if j < 20.0 => 20.0,
-            j if j < 40.0 => {
-                recommendations.push("Jitter may cause choppy audio/video".to_string());
-                15.0
-            },
-            _ => 5.0,
+        let readings: Vec<Option<MetricReading>> = profile.factors.iter()
+            .map(|factor| Self::read_metric(factor.metric, test_result, loaded_latency, packet_loss))
+            .collect();
+        // An unmeasured factor counts only when the profile says what it earns
+        let counts = |factor: &FactorProfile, reading: &Option<MetricReading>| {
+            reading.is_some() || factor.unmeasured_points.is_some()
         };
-        score += jitter_score;
-        
-        // 4. Download Speed (15 points)
-        let download_score = match test_result.download_mbps {
-            d if d >= 10.0 => 15.0,
-            d if d >= 5.0 => 12.0,
-            d if d >= 2.5 => 8.0,
-            _ => 3.0,
-        };
-        score += download_score;
-        
-        let grade = QualityGrade::from_score(score);
-        
-        let assessment = match grade {
-            QualityGrade::Excellent => "Perfect for 4K video calls and screen sharing",
-            QualityGrade::Good => "HD video conferencing works smoothly",
-            QualityGrade::Fair => "Video calls work, occasional quality drops",
-            QualityGrade::Poor => "Video calls may be choppy or freeze",
-            QualityGrade::VeryPoor => "Not suitable for video conferencing",
-        }.to_string();
-        
-        let explanation = format!(
-            "Video calls need good upload ({:.1} Mbps) and low upload latency ({:.0}ms). {}",
-            test_result.upload_mbps,
-            loaded_latency.upload_avg_ms,
-            if loaded_latency.upload_avg_ms < 80.0 && test_result.upload_mbps >= 5.0 {
-                "Your connection is great for video calls."
-            } else if loaded_latency.upload_avg_ms > 150.0 {
-                "High upload latency will cause frozen video."
-            } else {
-                "Your connection should work for video calls."
+        let points_possible: f64 = profile.factors.iter().zip(&readings)
+            .filter(|(factor, reading)| counts(factor, reading))
+            .map(|(factor, _)| factor.weight)
+            .sum();
+        let partial = profile.factors.iter().zip(&readings).any(|(factor, reading)| !counts(factor, reading));
+        
+        for (factor, reading) in profile.factors.iter().zip(readings) {
+            let band = reading.as_ref()
+                .and_then(|r| factor.bands.iter().position(|band| band.matches(r.value)));
+            let possible = if counts(factor, &reading) { factor.weight } else { 0.0 };
+            
+            let points = match (&reading, band) {
+                (Some(reading), Some(index)) => {
+                    let band = &factor.bands[index];
+                    capabilities.extend(band.capabilities.iter().map(|t| Self::render(t, factor.metric, reading)));
+                    recommendations.extend(band.recommendations.iter().map(|t| Self::render(t, factor.metric, reading)));
+                    band.points
+                }
+                (Some(_), None) => 0.0,
+                (None, _) => {
+                    unmeasured.push(factor.metric);
+                    factor.unmeasured_points.unwrap_or(0.0)
+                }
+            };
+            earned += points;
+            
+            factors.push(FactorScore {
+                metric: factor.metric,
+                label: factor.metric.label().to_string(),
+                unit: factor.metric.unit().to_string(),
+                value: reading.map(|r| r.value),
+                weight: if points_possible > 0.0 { possible * 100.0 / points_possible } else { 0.0 },
+                points_earned: points,
+                points_possible: possible,
+                band: band.map(|index| factor.describe_band(index)),
+            });
+        }
+        
+        // Latency stages behind the metrics, in test order
+        let metrics: Vec<Metric> = profile.factors.iter().map(|f| f.metric).collect();
+        let uses = |stage: &[Metric]| metrics.iter().any(|m| stage.contains(m));
+        let mut caveats: Vec<Message> = [
+            uses(&[Metric::IdleLatencyMs, Metric::JitterMs, Metric::ProbeTimeoutPct])
+                .then(|| loaded_latency.idle_quality.caveat("idle")),
+            uses(&[Metric::DownloadLoadedLatencyMs, Metric::WorstLoadedLatencyMs, Metric::ProbeTimeoutPct])
+                .then(|| loaded_latency.download_quality.caveat("download")),
+            uses(&[Metric::UploadLoadedLatencyMs, Metric::WorstLoadedLatencyMs, Metric::ProbeTimeoutPct])
+                .then(|| loaded_latency.upload_quality.caveat("upload")),
+        ].into_iter().flatten().flatten().collect();
+        // The packet loss note covers the whole test, so it goes last
+        unmeasured.sort_by_key(Metric::is_packet_loss);
+        for caveat in unmeasured.iter().filter_map(Metric::missing_caveat) {
+            if !caveats.contains(&caveat) {
+                caveats.push(caveat);
             }
-        );
+        }
         
-        UseCaseScore {
-            score,
-            grade,
-            assessment,
-            explanation,
-            emoji: "💼".to_string(),
+        Evaluation {
+            score: if points_possible > 0.0 { earned * 100.0 / points_possible } else { 0.0 },
+            partial,
             capabilities,
             recommendations,
+            caveats,
+            factors,
         }
     }
     
-    /// General Browsing Score - Overall balance
-    /// 
-    /// Weights:
-    /// - Download speed: 40%
-    /// - Idle latency: 40%
-    /// - Jitter: 10%
-    /// - Packet loss: 10%
-    pub fn calculate_browsing_score(
+    /// Current value of a metric, `None` when it was not measured
+    fn read_metric(
+        metric: Metric,
         test_result: &TestResult,
         loaded_latency: &LoadedLatencyResult,
-    ) -> UseCaseScore {
-        let mut score = 100.0;
-        let mut capabilities = Vec::new();
-        let mut recommendations = Vec::new();
+        packet_loss: Option<&PacketLossResult>,
+    ) -> Option<MetricReading> {
+        let plain = |value: f64| Some(MetricReading { value, placeholders: Vec::new() });
+        let rate = |mbps: f64| Some(MetricReading {
+            value: mbps,
+            placeholders: vec![("gb_per_hour", format!("{:.1}", Self::gb_per_hour(mbps)))],
+        });
         
-        // 1. Download Speed (40 points)
-        let speed_score = match test_result.download_mbps {
-            s if s >= 100.0 => {
-                capabilities.push("Lightning-fast page loads".to_string());
-                capabilities.push("Instant large downloads".to_string());
-                40.0
-            },
-            s if s >= 50.0 => {
-                capabilities.push("Very fast browsing experience".to_string());
-                38.0
-            },
-            s if s >= 25.0 => {
-                capabilities.push("Fast page loads and downloads".to_string());
-                35.0
-            },
-            s if s >= 10.0 => {
-                capabilities.push("Good browsing experience".to_string());
-                30.0
-            },
-            s if s >= 5.0 => {
-                capabilities.push("Adequate for basic browsing".to_string());
-                20.0
-            },
-            s => {
-                recommendations.push(format!("Speed {:.1} Mbps is slow", s));
-                10.0
+        match metric {
+            Metric::DownloadMbps => rate(test_result.download_mbps),
+            Metric::UploadMbps => rate(test_result.upload_mbps),
+            Metric::IdleLatencyMs => plain(loaded_latency.idle_avg_ms),
+            Metric::DownloadLoadedLatencyMs => plain(loaded_latency.download_avg_ms),
+            Metric::UploadLoadedLatencyMs => plain(loaded_latency.upload_avg_ms),
+            Metric::WorstLoadedLatencyMs => plain(f64::max(loaded_latency.download_avg_ms, loaded_latency.upload_avg_ms)),
+            Metric::JitterMs => plain(test_result.jitter_ms),
+            Metric::PacketLossPct => plain(Self::worst_loss(packet_loss, &[])?),
+            Metric::DownloadPacketLossPct => plain(Self::worst_loss(packet_loss, &[&loaded_latency.download_packet_loss])?),
+            Metric::UploadPacketLossPct => plain(Self::worst_loss(packet_loss, &[&loaded_latency.upload_packet_loss])?),
+            Metric::WorstPacketLossPct => plain(Self::worst_loss(
+                packet_loss,
+                &[&loaded_latency.download_packet_loss, &loaded_latency.upload_packet_loss],
+            )?),
+            Metric::DownloadVariationPct => {
+                plain(loaded_latency.throughput_consistency(LoadDirection::Download)?.robust_variation)
             }
-        };
-        score = speed_score;
-        
-        // 2. Idle Latency (40 points)
-        let latency_score = match loaded_latency.idle_avg_ms {
-            l if l < 20.0 => {
-                capabilities.push("Instant page response".to_string());
-                40.0
-            },
-            l if l < 50.0 => 35.0,
-            l if l < 100.0 => 30.0,
-            l if l < 200.0 => {
-                recommendations.push("Pages may feel slightly sluggish".to_string());
-                20.0
-            },
-            _ => 10.0,
-        };
-        score += latency_score;
-        
-        // 3. Jitter + Packet Loss (20 points)
-        score += 20.0; // Assume good for now
-        
-        let grade = QualityGrade::from_score(score);
-        
-        let assessment = match grade {
-            QualityGrade::Excellent => "Outstanding browsing experience - instant and smooth",
-            QualityGrade::Good => "Great browsing - fast page loads",
-            QualityGrade::Fair => "Adequate browsing - some delays",
-            QualityGrade::Poor => "Slow browsing experience",
-            QualityGrade::VeryPoor => "Very slow - frustrating to use",
-        }.to_string();
-        
-        let explanation = format!(
-            "Browsing quality combines speed ({:.1} Mbps) and responsiveness ({:.0}ms latency).",
-            test_result.download_mbps,
-            loaded_latency.idle_avg_ms
-        );
+            Metric::UploadVariationPct => {
+                plain(loaded_latency.throughput_consistency(LoadDirection::Upload)?.robust_variation)
+            }
+            Metric::DownloadStallSeverity => Self::stall_reading(loaded_latency, LoadDirection::Download),
+            Metric::UploadStallSeverity => Self::stall_reading(loaded_latency, LoadDirection::Upload),
+            Metric::ProbeTimeoutPct => {
+                // Probes that errored out never reached the network
+                let stages = [&loaded_latency.idle_quality, &loaded_latency.download_quality, &loaded_latency.upload_quality];
+                let probes: usize = stages.iter().map(|q| q.probes - q.errors).sum();
+                let timeouts: usize = stages.iter().map(|q| q.timeouts).sum();
+                if probes == 0 {
+                    return None;
+                }
+                plain(timeouts as f64 * 100.0 / probes as f64)
+            }
+        }
+    }
+    
+    fn stall_reading(loaded_latency: &LoadedLatencyResult, direction: LoadDirection) -> Option<MetricReading> {
+        let stalls = loaded_latency.throughput_stalls(direction)?;
+        Some(MetricReading {
+            value: Self::stall_severity(&stalls),
+            placeholders: vec![
+                ("stall_count", stalls.stall_count.to_string()),
+                ("total_stall_ms", stalls.total_stall_ms.to_string()),
+                ("longest_stall_ms", stalls.longest_stall_ms.to_string()),
+                ("longest_stall_s", format!("{:.1}", stalls.longest_stall_ms as f64 / 1000.0)),
+                ("drop_count", stalls.drop_count.to_string()),
+            ],
+        })
+    }
+    
+    /// See `Metric::DownloadStallSeverity`
+    fn stall_severity(stalls: &StallAnalysis) -> f64 {
+        if stalls.is_clean() {
+            0.0
+        } else if stalls.stall_count == 0 {
+            1.0
+        } else if stalls.stall_count == 1 && stalls.longest_stall_ms < 1_000 {
+            2.0
+        } else if stalls.longest_stall_ms < 2_000 {
+            3.0
+        } else {
+            4.0
+        }
+    }
+    
+    /// Decimal gigabytes a sustained transfer moves per hour
+    fn gb_per_hour(mbps: f64) -> f64 {
+        mbps * 3600.0 / 8.0 / 1000.0
+    }
+    
+    /// Band texts of the built-in profiles are catalog messages; texts of
+    /// custom profiles are rendered as they are
+    fn render(template: &str, metric: Metric, reading: &MetricReading) -> Message {
+        let mut params = vec![("value", metric.format_value(reading.value))];
+        params.extend(reading.placeholders.iter().cloned());
         
-        UseCaseScore {
-            score,
-            grade,
-            assessment,
-            explanation,
-            emoji: "🌐".to_string(),
-            capabilities,
-            recommendations,
+        match i18n::profile_key(template) {
+            Some(key) => Message::new(key, &params),
+            None => {
+                let mut text = template.to_string();
+                for (name, value) in &params {
+                    text = text.replace(&format!("{{{}}}", name), value);
+                }
+                Message::custom(text)
+            }
         }
     }
     
+    /// Worst loss percentage across the idle test and the given loaded stages
+    fn worst_loss(packet_loss: Option<&PacketLossResult>, loaded: &[&Option<PacketLossResult>]) -> Option<f64> {
+        packet_loss
+            .into_iter()
+            .chain(loaded.iter().filter_map(|loss| loss.as_ref()))
+            .map(|loss| loss.loss_percentage)
+            .reduce(f64::max)
+    }
+    
     fn gaming_emoji(grade: &QualityGrade) -> String {
         match grade {
             QualityGrade::Excellent => "🎮⭐",
@@ -614,33 +630,91 @@ impl AIMCalculator {
 }
 
 impl AIMScores {
+    /// Score of one use case, if the profile scored it
+    pub fn get(&self, use_case: UseCase) -> Option<&UseCaseScore> {
+        self.use_cases.iter().find(|s| s.use_case == use_case)
+    }
+    
+    /// Re-render every text in `locale`
+    pub fn localize(&mut self, locale: Locale) {
+        for score in &mut self.use_cases {
+            score.label = score.use_case.localized_label(locale);
+            score.assessment.localize(locale);
+            score.explanation.localize(locale);
+            for message in score.capabilities.iter_mut()
+                .chain(&mut score.recommendations)
+                .chain(&mut score.caveats)
+            {
+                message.localize(locale);
+            }
+            for factor in &mut score.factors {
+                factor.label = t(locale, &format!("metric.{}", factor.metric.as_str()), &[]);
+            }
+        }
+    }
+    
     /// Get a formatted summary of all scores
     pub fn summary(&self) -> String {
-        format!(
-            "AIM Quality Scores\n\
-             ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
-             🎮 Gaming:            {:.0}/100 {} {}\n\
-             📺 Streaming:         {:.0}/100 {} {}\n\
-             💼 Video Conferencing: {:.0}/100 {} {}\n\
-             🌐 General Browsing:   {:.0}/100 {} {}\n\
-             ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
-             Overall Quality: {:.0}/100 {} {}",
-            self.gaming.score, self.gaming.grade.emoji(), self.gaming.grade.as_str(),
-            self.streaming.score, self.streaming.grade.emoji(), self.streaming.grade.as_str(),
-            self.video_conferencing.score, self.video_conferencing.grade.emoji(), self.video_conferencing.grade.as_str(),
-            self.general_browsing.score, self.general_browsing.grade.emoji(), self.general_browsing.grade.as_str(),
-            self.overall_score, self.overall_grade.emoji(), self.overall_grade.as_str()
-        )
+        self.summary_in(Locale::En)
+    }
+    
+    /// Formatted summary of all scores in `locale`
+    pub fn summary_in(&self, locale: Locale) -> String {
+        let grade = |grade: &QualityGrade| t(locale, &format!("grade.{}", grade.key()), &[]);
+        let mut summary = format!(
+            "{}\n\
+             ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n",
+            t(locale, "report.title", &[])
+        );
+        
+        for score in &self.use_cases {
+            summary.push_str(&format!(
+                "{} {:<20}{:.0}/100 {} {}\n",
+                score.use_case.emoji(), format!("{}:", score.use_case.localized_label(locale)),
+                score.score, score.grade.emoji(), grade(&score.grade)
+            ));
+        }
+        
+        summary.push_str(&format!(
+            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+             {}: {:.0}/100 {} {}",
+            t(locale, "report.overall", &[]),
+            self.overall_score, self.overall_grade.emoji(), grade(&self.overall_grade)
+        ));
+
+        if self.overall_weights.preset != Some(UsagePreset::Balanced) && !self.overall_weights.weights.is_empty() {
+            let shares: Vec<String> = self.overall_weights.weights.iter()
+                .filter(|(_, weight)| **weight > 0.0)
+                .map(|(use_case, weight)| format!("{} {:.0}%", use_case.localized_label(locale), weight * 100.0))
+                .collect();
+            let preset = self.overall_weights.preset.as_ref().map_or("custom", |preset| preset.as_str());
+            summary.push_str(&format!("\n⚖️  {}", t(locale, "report.weighted_for", &[
+                ("preset", t(locale, &format!("preset.{}", preset), &[])),
+                ("shares", shares.join(", ")),
+            ])));
+        }
+
+        for call in &self.voice_calls {
+            summary.push_str(&format!(
+                "\n📞 {}: MOS {:.1} (R {:.0}) {} {}",
+                t(locale, "report.voice_calls", &[("codec", call.codec.as_str().to_string())]),
+                call.mos, call.r_factor, call.rating.emoji(),
+                t(locale, &format!("call_rating.{}", call.rating.as_str()), &[])
+            ));
+        }
+        
+        summary
     }
     
     /// Get detailed report for a specific use case
     pub fn detailed_report(&self, use_case: &str) -> String {
-        let score = match use_case.to_lowercase().as_str() {
-            "gaming" => &self.gaming,
-            "streaming" => &self.streaming,
-            "conferencing" | "video" => &self.video_conferencing,
-            "browsing" | "general" => &self.general_browsing,
-            _ => return "Unknown use case".to_string(),
+        self.detailed_report_in(use_case, Locale::En)
+    }
+    
+    /// Detailed report for a specific use case in `locale`
+    pub fn detailed_report_in(&self, use_case: &str, locale: Locale) -> String {
+        let Some(score) = UseCase::from_name(use_case).and_then(|u| self.get(u)) else {
+            return t(locale, "report.unknown_use_case", &[]);
         };
         
         let mut report = format!(
@@ -649,25 +723,48 @@ impl AIMScores {
              {}\n\n\
              {}\n\n",
             score.emoji,
-            use_case.to_uppercase(),
+            score.use_case.localized_label(locale).to_uppercase(),
             score.score,
             score.grade.emoji(),
-            score.assessment,
-            score.explanation
+            score.assessment.render(locale),
+            score.explanation.render(locale)
         );
         
         if !score.capabilities.is_empty() {
-            report.push_str("✅ What Works:\n");
+            report.push_str(&format!("✅ {}:\n", t(locale, "report.what_works", &[])));
             for cap in &score.capabilities {
-                report.push_str(&format!("   • {}\n", cap));
+                report.push_str(&format!("   • {}\n", cap.render(locale)));
             }
             report.push('\n');
         }
         
         if !score.recommendations.is_empty() {
-            report.push_str("💡 Recommendations:\n");
+            report.push_str(&format!("💡 {}:\n", t(locale, "report.recommendations", &[])));
             for rec in &score.recommendations {
-                report.push_str(&format!("   • {}\n", rec));
+                report.push_str(&format!("   • {}\n", rec.render(locale)));
+            }
+        }
+        
+        if !score.factors.is_empty() {
+            report.push_str(&format!("\n📐 {}:\n", t(locale, "report.score_breakdown", &[])));
+            for factor in &score.factors {
+                let measured = match (factor.value, &factor.band) {
+                    (Some(value), Some(band)) => {
+                        format!("{} {} ({})", factor.metric.format_value(value), factor.unit, band)
+                    }
+                    (Some(value), None) => format!("{} {}", factor.metric.format_value(value), factor.unit),
+                    (None, _) => t(locale, "report.not_measured", &[]),
+                };
+                report.push_str(&format!("   • {}: {} - {:.0}/{:.0}\n",
+                    t(locale, &format!("metric.{}", factor.metric.as_str()), &[]),
+                    measured, factor.points_earned, factor.points_possible));
+            }
+        }
+        
+        if !score.caveats.is_empty() {
+            report.push_str(&format!("\n⚠️ {}:\n", t(locale, "report.caveats", &[])));
+            for caveat in &score.caveats {
+                report.push_str(&format!("   • {}\n", caveat.render(locale)));
             }
         }
         
@@ -675,9 +772,45 @@ impl AIMScores {
     }
 }
 
+/// Shorthand for a catalog text that is not kept as a `Message`
+fn t(locale: Locale, key: &str, params: &[(&str, String)]) -> String {
+    Message::new(key, params).render(locale)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::loaded_latency::LoadedLatencyTester;
+    use crate::services::packet_loss::{BurstLossStats, PacketLossGrade};
+    use crate::services::responsiveness::ResponsivenessResult;
+    use crate::services::stall_detection::ThroughputSample;
+    
+    /// A test at the given speeds with default (idle) latency results
+    fn inputs(download_mbps: f64, upload_mbps: f64) -> (TestResult, LoadedLatencyResult) {
+        let mut test_result = TestResult::new("test".to_string(), "127.0.0.1".to_string());
+        test_result.download_mbps = download_mbps;
+        test_result.upload_mbps = upload_mbps;
+        (test_result, LoadedLatencyTester::new().calculate_results())
+    }
+    
+    /// A saturated 200 Mbps download with the given goodput and throughput series
+    fn download_load(goodput_samples_mbps: Vec<f64>, throughput_series: Vec<ThroughputSample>) -> ResponsivenessResult {
+        ResponsivenessResult {
+            direction: LoadDirection::Download,
+            rpm: 1000.0,
+            foreign_tcp_ms: 20.0,
+            foreign_http_ms: 20.0,
+            self_http_ms: 100.0,
+            saturated: true,
+            load_connections: 4,
+            goodput_mbps: 200.0,
+            goodput_samples_mbps,
+            throughput_series,
+            started_at: chrono::Utc::now(),
+            duration_ms: 8_000,
+            samples: Vec::new(),
+        }
+    }
     
     #[test]
     fn test_quality_grade_from_score() {
@@ -687,4 +820,272 @@ mod tests {
         assert_eq!(QualityGrade::from_score(45.0), QualityGrade::Poor);
         assert_eq!(QualityGrade::from_score(20.0), QualityGrade::VeryPoor);
     }
+    
+    #[test]
+    fn test_packet_loss_lowers_every_use_case() {
+        let (test_result, loaded_latency) = inputs(200.0, 20.0);
+        let lossy = PacketLossResult {
+            sent_packets: 500,
+            received_packets: 475,
+            lost_packets: 25,
+            loss_percentage: 5.0,
+            grade: PacketLossGrade::Poor,
+            test_duration_ms: 10_000,
+            late_packets: 0,
+            duplicate_packets: 0,
+            reordered_packets: 0,
+            bursts: BurstLossStats::default(),
+            timestamps: Vec::new(),
+        };
+        
+        let unmeasured = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        let measured = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, Some(&lossy));
+        
+        assert_eq!(measured.use_cases.len(), UseCase::ALL.len());
+        for (lossy, clean) in measured.use_cases.iter().zip(&unmeasured.use_cases) {
+            assert!(lossy.score < clean.score, "{} ignores packet loss", lossy.label);
+        }
+        assert!(unmeasured.get(UseCase::Gaming).unwrap().caveats.iter().any(|c| c.text.contains("not measured")));
+        assert!(!measured.get(UseCase::Gaming).unwrap().caveats.iter().any(|c| c.text.contains("not measured")));
+    }
+    
+    #[test]
+    fn test_unmeasured_packet_loss_is_left_out_of_the_score() {
+        let (mut test_result, mut loaded_latency) = inputs(50.0, 10.0);
+        test_result.jitter_ms = 8.0;
+        loaded_latency.download_avg_ms = 120.0;
+        loaded_latency.upload_avg_ms = 250.0;
+        let clean = PacketLossResult {
+            sent_packets: 500,
+            received_packets: 500,
+            lost_packets: 0,
+            loss_percentage: 0.0,
+            grade: PacketLossGrade::Excellent,
+            test_duration_ms: 10_000,
+            late_packets: 0,
+            duplicate_packets: 0,
+            reordered_packets: 0,
+            bursts: BurstLossStats::default(),
+            timestamps: Vec::new(),
+        };
+        
+        let unmeasured = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        let measured = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, Some(&clean));
+        let (partial, full) = (unmeasured.get(UseCase::Gaming).unwrap(), measured.get(UseCase::Gaming).unwrap());
+        
+        // Gaming's 15 loss points are neither earned nor possible without a reading
+        assert!(partial.partial && !full.partial);
+        let possible: f64 = partial.factors.iter().map(|f| f.points_possible).sum();
+        let earned: f64 = partial.factors.iter().map(|f| f.points_earned).sum();
+        assert_eq!(possible, 85.0);
+        assert_eq!(partial.score, earned * 100.0 / 85.0);
+        assert_eq!(full.score, (earned + 15.0) * 100.0 / 100.0);
+        assert!(partial.score < full.score);
+        
+        // Frozen versions keep awarding the full weight
+        use crate::services::scoring_versions::ScoringVersions;
+        let versions = ScoringVersions::default();
+        let v2 = AIMCalculator::calculate_with_profile(versions.get("aim-v2").unwrap(), &test_result, &loaded_latency, None);
+        assert!(!v2.get(UseCase::Gaming).unwrap().partial);
+        assert_eq!(v2.get(UseCase::Gaming).unwrap().score, full.score);
+    }
+    
+    #[test]
+    fn test_unstable_throughput_lowers_streaming_and_browsing() {
+        let (test_result, mut loaded_latency) = inputs(200.0, 0.0);
+        let steady = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        
+        loaded_latency.download_responsiveness = Some(download_load(vec![250.0, 80.0, 300.0, 120.0, 260.0], Vec::new()));
+        let unstable = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        
+        assert!(unstable.get(UseCase::Streaming).unwrap().score < steady.get(UseCase::Streaming).unwrap().score);
+        assert!(unstable.get(UseCase::GeneralBrowsing).unwrap().score < steady.get(UseCase::GeneralBrowsing).unwrap().score);
+        assert_eq!(unstable.get(UseCase::Gaming).unwrap().score, steady.get(UseCase::Gaming).unwrap().score);
+        assert!(steady.get(UseCase::Streaming).unwrap().caveats.iter().any(|c| c.text.contains("consistency")));
+        assert!(!unstable.get(UseCase::Streaming).unwrap().caveats.iter().any(|c| c.text.contains("consistency")));
+    }
+    
+    #[test]
+    fn test_stalls_lower_streaming_only() {
+        let (test_result, mut loaded_latency) = inputs(200.0, 0.0);
+        let series = |stall: bool| (0..50u64)
+            .map(|i| ThroughputSample {
+                offset_ms: (i + 1) * 100,
+                mbps: if stall && (20..45).contains(&i) { 0.0 } else { 200.0 },
+            })
+            .collect::<Vec<_>>();
+        loaded_latency.download_responsiveness = Some(download_load(Vec::new(), series(false)));
+        let smooth = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        
+        loaded_latency.download_responsiveness = Some(download_load(Vec::new(), series(true)));
+        let stalled = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        
+        // Packet loss and variation are unmeasured, leaving 80 points possible
+        assert_eq!(smooth.get(UseCase::Streaming).unwrap().score - stalled.get(UseCase::Streaming).unwrap().score, 15.0 * 100.0 / 80.0);
+        assert_eq!(smooth.get(UseCase::GeneralBrowsing).unwrap().score, stalled.get(UseCase::GeneralBrowsing).unwrap().score);
+        assert!(stalled.get(UseCase::Streaming).unwrap().recommendations.iter().any(|r| r.text.contains("stalled")));
+    }
+    
+    #[test]
+    fn test_factor_breakdown_adds_up() {
+        let (mut test_result, mut loaded_latency) = inputs(100.0, 0.0);
+        test_result.jitter_ms = 35.0;
+        loaded_latency.download_avg_ms = 30.0;
+        loaded_latency.upload_avg_ms = 40.0;
+        
+        let scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        let gaming = scores.get(UseCase::Gaming).unwrap();
+        
+        let latency = &gaming.factors[0];
+        assert_eq!(latency.metric, Metric::WorstLoadedLatencyMs);
+        assert_eq!(latency.value, Some(40.0));
+        assert_eq!((latency.points_earned, latency.points_possible), (45.0, 50.0));
+        assert_eq!(latency.band.as_deref(), Some("20–50 ms"));
+        
+        let jitter = &gaming.factors[1];
+        assert_eq!(jitter.points_possible - jitter.points_earned, 20.0);
+        
+        let loss = &gaming.factors[2];
+        assert_eq!((loss.value, loss.band.as_deref(), loss.points_earned, loss.points_possible), (None, None, 0.0, 0.0));
+        assert_eq!(loss.weight, 0.0);
+        
+        for score in &scores.use_cases {
+            let earned: f64 = score.factors.iter().map(|f| f.points_earned).sum();
+            let possible: f64 = score.factors.iter().map(|f| f.points_possible).sum();
+            assert!((earned * 100.0 / possible - score.score).abs() < 1e-9);
+            assert!((score.factors.iter().map(|f| f.weight).sum::<f64>() - 100.0).abs() < 1e-9);
+        }
+        assert!(scores.detailed_report("gaming").contains("Worst loaded latency: 40 ms (20–50 ms) - 45/50"));
+    }
+    
+    #[test]
+    fn test_new_use_cases_follow_their_metrics() {
+        // Fast cable download, slow upload, low latency
+        let (mut test_result, mut loaded_latency) = inputs(300.0, 4.0);
+        test_result.jitter_ms = 3.0;
+        loaded_latency.idle_avg_ms = 15.0;
+        loaded_latency.download_avg_ms = 25.0;
+        loaded_latency.upload_avg_ms = 60.0;
+        
+        let scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        let cloud_gaming = scores.get(UseCase::CloudGaming).unwrap();
+        let backup = scores.get(UseCase::CloudBackup).unwrap();
+        
+        assert_eq!(cloud_gaming.grade, QualityGrade::Excellent);
+        assert!(backup.score < cloud_gaming.score - 25.0);
+        assert!(backup.recommendations.iter().any(|r| r.text.contains("only 1.8 GB per hour")));
+        assert_eq!(UseCase::from_name("vdi"), Some(UseCase::RemoteDesktop));
+        
+        // The newer use cases do not move the overall score
+        let legacy: f64 = scores.use_cases[..4].iter().map(|s| s.score).sum::<f64>() / 4.0;
+        assert!((scores.overall_score - legacy).abs() < 1e-9);
+        assert_eq!(serde_json::to_value(&scores).unwrap()["use_cases"][7]["use_case"], "smart_home");
+    }
+
+    #[test]
+    fn test_streamer_weights_ignore_gaming_latency() {
+        use crate::services::usage_priorities::UsagePreset;
+
+        // Plenty of bandwidth, but latency too high for competitive gaming
+        let (mut test_result, mut loaded_latency) = inputs(500.0, 50.0);
+        test_result.jitter_ms = 20.0;
+        loaded_latency.idle_avg_ms = 90.0;
+        loaded_latency.download_avg_ms = 180.0;
+        loaded_latency.upload_avg_ms = 180.0;
+
+        let profile = ScoringProfile::builtin();
+        let streamer = OverallWeights::from_preset(profile, UsagePreset::Streamer).unwrap();
+        let balanced = AIMCalculator::calculate_with_profile(profile, &test_result, &loaded_latency, None);
+        let weighted = AIMCalculator::calculate_weighted(profile, &streamer, &test_result, &loaded_latency, None);
+
+        assert!(weighted.overall_score > balanced.overall_score + 10.0);
+        assert_eq!(weighted.use_cases.len(), balanced.use_cases.len());
+        assert_eq!(weighted.overall_weights, streamer);
+        assert_eq!(balanced.overall_weights.preset, Some(UsagePreset::Balanced));
+        assert!(weighted.summary().contains("Weighted for Streamer: Streaming 70%"));
+
+        let json = serde_json::to_value(&weighted).unwrap();
+        assert_eq!(json["overall_weights"]["preset"], "streamer");
+        assert!((json["overall_weights"]["weights"]["streaming"].as_f64().unwrap() - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_profile_changes_breakpoints_and_weights() {
+        let (test_result, loaded_latency) = inputs(30.0, 0.0);
+        
+        // A stricter streaming profile that only cares about speed, and an
+        // overall score that is streaming alone
+        let mut profile = ScoringProfile::default();
+        let streaming = profile.use_cases.get_mut(&UseCase::Streaming).unwrap();
+        streaming.factors.retain(|f| f.metric == Metric::DownloadMbps);
+        streaming.factors[0].bands[2].at_least = Some(40.0);
+        profile.use_cases.remove(&UseCase::SmartHome);
+        profile.overall_weights = [(UseCase::Streaming, 1.0)].into_iter().collect();
+        profile.validate().unwrap();
+        
+        let scores = AIMCalculator::calculate_with_profile(&profile, &test_result, &loaded_latency, None);
+        
+        assert_eq!(scores.get(UseCase::Streaming).unwrap().score, 26.0 * 100.0 / 35.0);
+        assert!(scores.get(UseCase::SmartHome).is_none());
+        assert_eq!(scores.overall_score, scores.get(UseCase::Streaming).unwrap().score);
+        assert_eq!(
+            scores.get(UseCase::Gaming).unwrap().score,
+            AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None).get(UseCase::Gaming).unwrap().score
+        );
+    }
+    
+    #[test]
+    fn test_default_profile_matches_hardcoded_scores() {
+        // Gaming, streaming, conferencing and browsing scores from before the
+        // breakpoints moved into a profile; aim-v2 still awards unmeasured
+        // factors their full weight, as the hardcoded scoring did
+        use crate::services::scoring_versions::ScoringVersions;
+        let versions = ScoringVersions::default();
+        let profile = versions.get("aim-v2").unwrap();
+        let pinned = [
+            ((300.0, 50.0, 2.0, 12.0, 25.0, 40.0), [95.0, 100.0, 95.0, 100.0]),
+            ((50.0, 10.0, 8.0, 35.0, 120.0, 250.0), [50.0, 91.0, 73.0, 93.0]),
+            ((8.0, 1.0, 25.0, 80.0, 400.0, 900.0), [40.0, 61.0, 43.0, 70.0]),
+        ];
+        for ((download, upload, jitter, idle_ms, download_ms, upload_ms), expected) in pinned {
+            let (mut test_result, mut loaded_latency) = inputs(download, upload);
+            test_result.jitter_ms = jitter;
+            loaded_latency.idle_avg_ms = idle_ms;
+            loaded_latency.download_avg_ms = download_ms;
+            loaded_latency.upload_avg_ms = upload_ms;
+            
+            let scores = AIMCalculator::calculate_with_profile(profile, &test_result, &loaded_latency, None);
+            let use_cases = [UseCase::Gaming, UseCase::Streaming, UseCase::VideoConferencing, UseCase::GeneralBrowsing];
+            for (use_case, expected) in use_cases.into_iter().zip(expected) {
+                assert_eq!(scores.get(use_case).unwrap().score, expected, "{:?} at {} Mbps", use_case, download);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_builtin_texts_are_catalog_messages() {
+        use crate::services::scoring_versions::ScoringVersions;
+        
+        let versions = ScoringVersions::default();
+        for info in versions.versions() {
+            let profile = versions.get(&info.version).unwrap();
+            for factor in profile.use_cases.values().flat_map(|u| &u.factors) {
+                for text in factor.bands.iter().flat_map(|b| b.capabilities.iter().chain(&b.recommendations)) {
+                    assert!(i18n::profile_key(text).is_some(), "{}: no catalog key for {:?}", info.version, text);
+                }
+            }
+        }
+        
+        let (test_result, loaded_latency) = inputs(4.0, 0.0);
+        let mut scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        let english = scores.get(UseCase::Streaming).unwrap().clone();
+        
+        scores.localize(Locale::Hi);
+        let hindi = scores.get(UseCase::Streaming).unwrap();
+        assert_eq!(hindi.label, "स्ट्रीमिंग");
+        assert_eq!(hindi.assessment.key, english.assessment.key);
+        assert_ne!(hindi.assessment.text, english.assessment.text);
+        assert_ne!(hindi.recommendations[0].text, english.recommendations[0].text);
+        assert!(scores.summary_in(Locale::Hi).starts_with("AIM क्वालिटी स्कोर"));
+    }
 }
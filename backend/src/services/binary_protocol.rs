@@ -1,18 +1,22 @@
-/// Binary WebSocket Protocol
-/// 
-/// Replaces JSON with MessagePack for 30-50% size reduction and faster serialization.
-/// Optimized for real-time speed test progress updates.
-/// 
-/// Benefits:
-/// - 30-50% smaller message size vs JSON
-/// - 2-3x faster serialization/deserialization
-/// - Lower CPU usage on server
-/// - Better mobile performance
-/// - More efficient batching
+//! Binary WebSocket Protocol
+//! 
+//! Replaces JSON with MessagePack for 30-50% size reduction and faster serialization.
+//! Optimized for real-time speed test progress updates.
+//! 
+//! Benefits:
+//! - 30-50% smaller message size vs JSON
+//! - 2-3x faster serialization/deserialization
+//! - Lower CPU usage on server
+//! - Better mobile performance
+//! - More efficient batching
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 use bytes::Bytes;
 
+use crate::services::aim_scoring::{AIMScores, UseCase};
+
 /// Binary message types for WebSocket communication
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -121,11 +125,8 @@ pub struct CompactTestResult {
     pub dl_rpm: u16,
     pub ul_rpm: u16,
     
-    // AIM scores (0-100)
-    pub gaming_score: u8,
-    pub streaming_score: u8,
-    pub video_score: u8,
-    pub browsing_score: u8,
+    // AIM scores (0-100), keyed by use case id
+    pub use_case_scores: BTreeMap<UseCase, u8>,
     pub overall_score: u8,
     
     // Metadata
@@ -133,6 +134,15 @@ pub struct CompactTestResult {
     pub timestamp: u64,
 }
 
+impl CompactTestResult {
+    /// Rounded-down use-case scores for `use_case_scores`
+    pub fn compact_scores(aim_scores: &AIMScores) -> BTreeMap<UseCase, u8> {
+        aim_scores.use_cases.iter()
+            .map(|s| (s.use_case, s.score as u8))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ErrorCode {
     InvalidConfig,
@@ -205,6 +215,11 @@ impl MessageBatch {
         true
     }
     
+    /// Messages currently held in the batch
+    pub fn messages(&self) -> &[BinaryMessage] {
+        &self.messages
+    }
+    
     /// Check if batch is ready to send (full or timeout)
     pub fn is_ready(&self) -> bool {
         self.messages.len() >= self.max_size
@@ -320,35 +335,6 @@ impl SizeComparison {
     }
 }
 
-/// Compression helper for large batches
-pub struct MessageCompression;
-
-impl MessageCompression {
-    /// Compress batch with gzip (optional, for very large batches)
-    #[cfg(feature = "compression")]
-    pub fn compress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
-        use flate2::Compression;
-        use flate2::write::GzEncoder;
-        use std::io::Write;
-        
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
-        encoder.write_all(data)?;
-        encoder.finish()
-    }
-    
-    /// Decompress gzip data
-    #[cfg(feature = "compression")]
-    pub fn decompress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
-        use flate2::read::GzDecoder;
-        use std::io::Read;
-        
-        let mut decoder = GzDecoder::new(data);
-        let mut decompressed = Vec::new();
-        decoder.read_to_end(&mut decompressed)?;
-        Ok(decompressed)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -390,10 +376,12 @@ mod tests {
                 idle_rpm: 4000,
                 dl_rpm: 2400,
                 ul_rpm: 1714,
-                gaming_score: 85,
-                streaming_score: 95,
-                video_score: 80,
-                browsing_score: 90,
+                use_case_scores: [
+                    (UseCase::Gaming, 85),
+                    (UseCase::Streaming, 95),
+                    (UseCase::VideoConferencing, 80),
+                    (UseCase::GeneralBrowsing, 90),
+                ].into_iter().collect(),
                 overall_score: 87,
                 duration_ms: 10000,
                 timestamp: 1700000000,
@@ -1,31 +1,154 @@
-/// Bandwidth Consistency Score Calculator
-/// 
-/// Measures how stable the connection speed is over time.
-/// A consistent connection is better than an inconsistent fast connection.
+//! Bandwidth Consistency Score Calculator
+//!
+//! Measures how stable the connection speed is over time.
+//! A consistent connection is better than an inconsistent fast connection.
+//!
+//! The grade uses robust statistics (median and MAD), so a single slow or
+//! fast interval cannot flip it the way it moves mean and standard deviation.
 
 use serde::{Deserialize, Serialize};
 
+/// Serialized as the descriptive labels the frontend has always received
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StabilityGrade {
+    #[serde(rename = "Excellent - Very stable")]
+    Excellent,  // robust CV < 5%
+    #[serde(rename = "Good - Minor fluctuations")]
+    Good,       // < 15%
+    #[serde(rename = "Fair - Noticeable variation")]
+    Fair,       // < 30%
+    #[serde(rename = "Poor - Highly unstable")]
+    Poor,       // >= 30%
+    #[serde(rename = "Unknown")]
+    Unknown,    // no measurements
+}
+
+impl StabilityGrade {
+    pub const ALL: [StabilityGrade; 5] = [
+        StabilityGrade::Excellent,
+        StabilityGrade::Good,
+        StabilityGrade::Fair,
+        StabilityGrade::Poor,
+        StabilityGrade::Unknown,
+    ];
+
+    pub fn from_variation(cv: f64) -> Self {
+        match cv {
+            x if x < 5.0 => StabilityGrade::Excellent,
+            x if x < 15.0 => StabilityGrade::Good,
+            x if x < 30.0 => StabilityGrade::Fair,
+            _ => StabilityGrade::Poor,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            StabilityGrade::Excellent => "Excellent",
+            StabilityGrade::Good => "Good",
+            StabilityGrade::Fair => "Fair",
+            StabilityGrade::Poor => "Poor",
+            StabilityGrade::Unknown => "Unknown",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            StabilityGrade::Excellent => "Very stable",
+            StabilityGrade::Good => "Minor fluctuations",
+            StabilityGrade::Fair => "Noticeable variation",
+            StabilityGrade::Poor => "Highly unstable",
+            StabilityGrade::Unknown => "Not measured",
+        }
+    }
+
+    pub fn emoji(&self) -> &str {
+        match self {
+            StabilityGrade::Excellent => "⭐",
+            StabilityGrade::Good => "✓",
+            StabilityGrade::Fair => "⚠️",
+            StabilityGrade::Poor => "🔴",
+            StabilityGrade::Unknown => "❔",
+        }
+    }
+
+    /// The serialized label, e.g. "Good - Minor fluctuations"
+    pub fn label(&self) -> String {
+        match self {
+            StabilityGrade::Unknown => self.as_str().to_string(),
+            _ => format!("{} - {}", self.as_str(), self.description()),
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|grade| grade.label() == label)
+            .unwrap_or(StabilityGrade::Unknown)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsistencyScore {
     pub coefficient_of_variation: f64,
-    pub stability_grade: String,
+    pub stability_grade: StabilityGrade,
     pub mean_speed: f64,
     pub std_deviation: f64,
     pub measurements_range: (f64, f64),
+    #[serde(default)]
+    pub samples: usize,
+
+    // Robust dispersion
+    #[serde(default)]
+    pub median_speed: f64,
+    /// Median absolute deviation from the median
+    #[serde(default)]
+    pub mad: f64,
+    /// Interquartile range (p75 - p25)
+    #[serde(default)]
+    pub iqr: f64,
+    #[serde(default)]
+    pub p10_speed: f64,
+    #[serde(default)]
+    pub p90_speed: f64,
+    /// MAD scaled to a standard deviation, relative to the median; the grade
+    /// is based on this
+    #[serde(default)]
+    pub robust_variation: f64,
 }
 
 pub struct ConsistencyCalculator;
 
 impl ConsistencyCalculator {
+    /// Fewer throughput samples than this say nothing about stability
+    pub const MIN_SAMPLES: usize = 3;
+
+    /// Scales MAD to the standard deviation of normally distributed data
+    const MAD_TO_STD: f64 = 1.4826;
+
+    /// Consistency of per-interval throughput, if there are enough samples
+    pub fn from_samples(samples: &[f64]) -> Option<ConsistencyScore> {
+        if samples.len() < Self::MIN_SAMPLES {
+            return None;
+        }
+        Some(Self::calculate(samples))
+    }
+
     /// Calculate consistency score from speed measurements
     pub fn calculate(measurements: &[f64]) -> ConsistencyScore {
         if measurements.is_empty() {
             return ConsistencyScore {
                 coefficient_of_variation: 0.0,
-                stability_grade: "Unknown".to_string(),
+                stability_grade: StabilityGrade::Unknown,
                 mean_speed: 0.0,
                 std_deviation: 0.0,
                 measurements_range: (0.0, 0.0),
+                samples: 0,
+                median_speed: 0.0,
+                mad: 0.0,
+                iqr: 0.0,
+                p10_speed: 0.0,
+                p90_speed: 0.0,
+                robust_variation: 0.0,
             };
         }
 
@@ -37,24 +160,31 @@ impl ConsistencyCalculator {
             0.0
         };
 
-        let grade = Self::grade_consistency(cv);
-        
-        let min = measurements.iter()
-            .min_by(|a, b| a.partial_cmp(b).unwrap())
-            .copied()
-            .unwrap_or(0.0);
-        
-        let max = measurements.iter()
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .copied()
-            .unwrap_or(0.0);
+        let mut sorted = measurements.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = Self::percentile(&sorted, 50.0);
+        let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - median).abs()).collect();
+        deviations.sort_by(|a, b| a.total_cmp(b));
+        let mad = Self::percentile(&deviations, 50.0);
+        let robust_cv = if median > 0.0 {
+            Self::MAD_TO_STD * mad / median * 100.0
+        } else {
+            0.0
+        };
 
         ConsistencyScore {
             coefficient_of_variation: cv,
-            stability_grade: grade,
+            stability_grade: StabilityGrade::from_variation(robust_cv),
             mean_speed: mean,
             std_deviation: std_dev,
-            measurements_range: (min, max),
+            measurements_range: (sorted[0], sorted[sorted.len() - 1]),
+            samples: measurements.len(),
+            median_speed: median,
+            mad,
+            iqr: Self::percentile(&sorted, 75.0) - Self::percentile(&sorted, 25.0),
+            p10_speed: Self::percentile(&sorted, 10.0),
+            p90_speed: Self::percentile(&sorted, 90.0),
+            robust_variation: robust_cv,
         }
     }
 
@@ -69,46 +199,41 @@ impl ConsistencyCalculator {
                 diff * diff
             })
             .sum::<f64>() / values.len() as f64;
-        
+
         variance.sqrt()
     }
 
-    fn grade_consistency(cv: f64) -> String {
-        match cv {
-            x if x < 5.0 => "Excellent - Very stable".to_string(),
-            x if x < 15.0 => "Good - Minor fluctuations".to_string(),
-            x if x < 30.0 => "Fair - Noticeable variation".to_string(),
-            _ => "Poor - Highly unstable".to_string(),
-        }
+    /// Linearly interpolated percentile of sorted, non-empty values
+    fn percentile(sorted: &[f64], p: f64) -> f64 {
+        let rank = p / 100.0 * (sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
     }
 }
 
 impl ConsistencyScore {
     /// Generate user-friendly summary
     pub fn summary(&self) -> String {
-        let grade_emoji = if self.coefficient_of_variation < 5.0 {
-            "⭐"
-        } else if self.coefficient_of_variation < 15.0 {
-            "✓"
-        } else if self.coefficient_of_variation < 30.0 {
-            "⚠️"
-        } else {
-            "🔴"
-        };
-
         format!(
             "📊 Connection Consistency Analysis\n\
              ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
              Grade:              {} {}\n\
-             Mean Speed:         {:.2} Mbps\n\
-             Std Deviation:      {:.2} Mbps\n\
-             Coefficient of Variation: {:.1}%\n\
+             Median Speed:       {:.2} Mbps (mean {:.2})\n\
+             MAD / IQR:          {:.2} / {:.2} Mbps\n\
+             Robust Variation:   {:.1}% (CV {:.1}%)\n\
+             p10 - p90:          {:.2} - {:.2} Mbps\n\
              Speed Range:        {:.2} - {:.2} Mbps",
-            self.stability_grade,
-            grade_emoji,
+            self.stability_grade.label(),
+            self.stability_grade.emoji(),
+            self.median_speed,
             self.mean_speed,
-            self.std_deviation,
+            self.mad,
+            self.iqr,
+            self.robust_variation,
             self.coefficient_of_variation,
+            self.p10_speed,
+            self.p90_speed,
             self.measurements_range.0,
             self.measurements_range.1
         )
@@ -116,26 +241,32 @@ impl ConsistencyScore {
 
     /// Get recommendation based on consistency
     pub fn recommendation(&self) -> &str {
-        if self.coefficient_of_variation < 5.0 {
-            "Excellent consistency! Your connection is very stable, ideal for all online activities."
-        } else if self.coefficient_of_variation < 15.0 {
-            "Good consistency with minor fluctuations. Suitable for most activities."
-        } else if self.coefficient_of_variation < 30.0 {
-            "Fair consistency with noticeable variations. May experience occasional slowdowns. \
-             Consider:\n\
-             1. Checking for background downloads\n\
-             2. Reducing number of connected devices\n\
-             3. Moving closer to WiFi router"
-        } else {
-            "Poor consistency! Your connection speed varies significantly. This indicates:\n\
-             1. Network congestion\n\
-             2. Weak WiFi signal\n\
-             3. ISP issues\n\
-             4. Interference from other devices\n\n\
-             Recommendations:\n\
-             - Use wired Ethernet connection\n\
-             - Test at different times\n\
-             - Contact your ISP"
+        match self.stability_grade {
+            StabilityGrade::Excellent => {
+                "Excellent consistency! Your connection is very stable, ideal for all online activities."
+            }
+            StabilityGrade::Good => {
+                "Good consistency with minor fluctuations. Suitable for most activities."
+            }
+            StabilityGrade::Fair => {
+                "Fair consistency with noticeable variations. May experience occasional slowdowns. \
+                 Consider:\n\
+                 1. Checking for background downloads\n\
+                 2. Reducing number of connected devices\n\
+                 3. Moving closer to WiFi router"
+            }
+            StabilityGrade::Poor => {
+                "Poor consistency! Your connection speed varies significantly. This indicates:\n\
+                 1. Network congestion\n\
+                 2. Weak WiFi signal\n\
+                 3. ISP issues\n\
+                 4. Interference from other devices\n\n\
+                 Recommendations:\n\
+                 - Use wired Ethernet connection\n\
+                 - Test at different times\n\
+                 - Contact your ISP"
+            }
+            StabilityGrade::Unknown => "Not enough measurements to judge consistency.",
         }
     }
 }
@@ -150,13 +281,35 @@ mod tests {
         let stable = vec![100.0, 101.0, 99.0, 100.5, 99.5];
         let score = ConsistencyCalculator::calculate(&stable);
         assert!(score.coefficient_of_variation < 5.0);
-        assert_eq!(score.stability_grade, "Excellent - Very stable");
+        assert_eq!(score.stability_grade, StabilityGrade::Excellent);
 
         // Unstable connection
         let unstable = vec![100.0, 50.0, 150.0, 75.0, 125.0];
         let score = ConsistencyCalculator::calculate(&unstable);
         assert!(score.coefficient_of_variation > 30.0);
-        assert_eq!(score.stability_grade, "Poor - Highly unstable");
+        assert_eq!(score.stability_grade, StabilityGrade::Poor);
+    }
+
+    #[test]
+    fn test_single_outlier_keeps_grade() {
+        let samples = vec![100.0, 101.0, 99.0, 100.5, 99.5, 100.0, 10.0, 100.2, 99.8, 100.1];
+        let score = ConsistencyCalculator::calculate(&samples);
+
+        assert!(score.coefficient_of_variation > 25.0);
+        assert_eq!(score.stability_grade, StabilityGrade::Excellent);
+        assert!((score.median_speed - 100.0).abs() < 0.1);
+        assert!(score.p10_speed > 85.0);
+
+        // Wire format is unchanged for the frontend
+        let json = serde_json::to_value(&score).unwrap();
+        assert_eq!(json["stability_grade"], "Excellent - Very stable");
+        assert_eq!(StabilityGrade::from_label("Excellent - Very stable"), StabilityGrade::Excellent);
+    }
+
+    #[test]
+    fn test_too_few_samples() {
+        assert!(ConsistencyCalculator::from_samples(&[100.0, 20.0]).is_none());
+        assert_eq!(ConsistencyCalculator::from_samples(&[100.0, 20.0, 60.0]).unwrap().samples, 3);
     }
 
     #[test]
@@ -1,8 +1,13 @@
-use sqlx::{sqlite::{SqlitePool, SqliteConnectOptions}, Row};
+use sqlx::{sqlite::{SqlitePool, SqliteConnectOptions, SqliteRow}, Row};
 use std::path::Path;
-use log::{info, error};
+use log::info;
 
 use crate::models::TestResult;
+use crate::services::aim_scoring::AIMScores;
+use crate::services::plan_compliance::IspPlan;
+use crate::services::consistency::{ConsistencyScore, StabilityGrade};
+use crate::services::responsiveness::LoadDirection;
+use crate::services::scoring_versions::ScoreInputs;
 
 #[derive(Clone)]
 pub struct Database {
@@ -23,7 +28,7 @@ impl Database {
             None
         } else {
             let path = Path::new(fs_path);
-            let mut path_buf = if path.is_absolute() {
+            let path_buf = if path.is_absolute() {
                 path.to_path_buf()
             } else {
                 std::env::current_dir()?.join(path)
@@ -76,6 +81,95 @@ impl Database {
         .execute(&pool)
         .await;
         
+        // Device ids were added to match tests against ISP plans
+        let _ = sqlx::query(
+            "ALTER TABLE test_results ADD COLUMN device_id TEXT"
+        )
+        .execute(&pool)
+        .await;
+        
+        // Tests with placeholder speeds are kept out of plan compliance
+        let _ = sqlx::query(
+            "ALTER TABLE test_results ADD COLUMN measured INTEGER NOT NULL DEFAULT 1"
+        )
+        .execute(&pool)
+        .await;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS bandwidth_consistency (
+                test_id TEXT NOT NULL,
+                direction TEXT NOT NULL,
+                mean_mbps REAL NOT NULL,
+                std_deviation REAL NOT NULL,
+                coefficient_of_variation REAL NOT NULL,
+                min_mbps REAL NOT NULL,
+                max_mbps REAL NOT NULL,
+                samples INTEGER NOT NULL,
+                stability_grade TEXT NOT NULL,
+                median_mbps REAL NOT NULL,
+                mad_mbps REAL NOT NULL,
+                iqr_mbps REAL NOT NULL,
+                p10_mbps REAL NOT NULL,
+                p90_mbps REAL NOT NULL,
+                robust_variation REAL NOT NULL,
+                PRIMARY KEY (test_id, direction)
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        
+        // Raw measurements behind the AIM scores, kept so tests can be re-scored
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS score_inputs (
+                test_id TEXT PRIMARY KEY,
+                loaded_latency TEXT NOT NULL,
+                overall_weights TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        
+        // Scores as computed, one row per scoring version
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS aim_scores (
+                test_id TEXT NOT NULL,
+                scoring_version TEXT NOT NULL,
+                overall_score REAL NOT NULL,
+                overall_grade TEXT NOT NULL,
+                scores TEXT NOT NULL,
+                scored_at TEXT NOT NULL,
+                PRIMARY KEY (test_id, scoring_version)
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        
+        // Registered ISP plans, matched to tests by device id or client IP
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS isp_plans (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                isp TEXT,
+                download_mbps REAL NOT NULL,
+                upload_mbps REAL NOT NULL,
+                latency_sla_ms REAL,
+                minimum_pct REAL NOT NULL,
+                device_id TEXT,
+                client_ip TEXT,
+                created_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        
         // Create index for faster queries
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_timestamp ON test_results(timestamp DESC)"
@@ -92,8 +186,8 @@ impl Database {
         sqlx::query(
             r#"
             INSERT INTO test_results 
-            (id, server_id, timestamp, download_mbps, upload_mbps, latency_ms, jitter_ms, protocol, client_ip, test_duration_ms)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            (id, server_id, timestamp, download_mbps, upload_mbps, latency_ms, jitter_ms, protocol, client_ip, test_duration_ms, device_id, measured)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&result.id)
@@ -106,12 +200,155 @@ impl Database {
         .bind(&result.protocol)
         .bind(&result.client_ip)
         .bind(result.test_duration_ms as i64)
+        .bind(&result.device_id)
+        .bind(result.measured)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn save_consistency(
+        &self,
+        test_id: &str,
+        direction: LoadDirection,
+        score: &ConsistencyScore,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO bandwidth_consistency
+            (test_id, direction, mean_mbps, std_deviation, coefficient_of_variation, min_mbps, max_mbps, samples, stability_grade,
+             median_mbps, mad_mbps, iqr_mbps, p10_mbps, p90_mbps, robust_variation)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(test_id)
+        .bind(Self::direction_key(direction))
+        .bind(score.mean_speed)
+        .bind(score.std_deviation)
+        .bind(score.coefficient_of_variation)
+        .bind(score.measurements_range.0)
+        .bind(score.measurements_range.1)
+        .bind(score.samples as i64)
+        .bind(score.stability_grade.label())
+        .bind(score.median_speed)
+        .bind(score.mad)
+        .bind(score.iqr)
+        .bind(score.p10_speed)
+        .bind(score.p90_speed)
+        .bind(score.robust_variation)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn get_consistency(
+        &self,
+        test_id: &str,
+        direction: LoadDirection,
+    ) -> Result<Option<ConsistencyScore>, Box<dyn std::error::Error>> {
+        let row = sqlx::query(
+            "SELECT * FROM bandwidth_consistency WHERE test_id = ? AND direction = ? LIMIT 1"
+        )
+        .bind(test_id)
+        .bind(Self::direction_key(direction))
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(row.map(|row| ConsistencyScore {
+            coefficient_of_variation: row.get("coefficient_of_variation"),
+            stability_grade: StabilityGrade::from_label(row.get("stability_grade")),
+            mean_speed: row.get("mean_mbps"),
+            std_deviation: row.get("std_deviation"),
+            measurements_range: (row.get("min_mbps"), row.get("max_mbps")),
+            samples: row.get::<i64, _>("samples") as usize,
+            median_speed: row.get("median_mbps"),
+            mad: row.get("mad_mbps"),
+            iqr: row.get("iqr_mbps"),
+            p10_speed: row.get("p10_mbps"),
+            p90_speed: row.get("p90_mbps"),
+            robust_variation: row.get("robust_variation"),
+        }))
+    }
+    
+    pub async fn save_score_inputs(&self, inputs: &ScoreInputs) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO score_inputs (test_id, loaded_latency, overall_weights) VALUES (?, ?, ?)"
+        )
+        .bind(&inputs.test_result.id)
+        .bind(serde_json::to_string(&inputs.loaded_latency)?)
+        .bind(serde_json::to_string(&inputs.overall_weights)?)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Inputs for re-scoring; `None` for tests stored before inputs were kept
+    pub async fn get_score_inputs(&self, test_id: &str) -> Result<Option<ScoreInputs>, Box<dyn std::error::Error>> {
+        let Some(test_result) = self.get_test_result(test_id).await? else {
+            return Ok(None);
+        };
+        let row = sqlx::query(
+            "SELECT loaded_latency, overall_weights FROM score_inputs WHERE test_id = ? LIMIT 1"
+        )
+        .bind(test_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(ScoreInputs {
+            test_result,
+            loaded_latency: serde_json::from_str(row.get("loaded_latency"))?,
+            overall_weights: serde_json::from_str(row.get("overall_weights"))?,
+        }))
+    }
+    
+    pub async fn save_aim_scores(&self, test_id: &str, scores: &AIMScores) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO aim_scores
+            (test_id, scoring_version, overall_score, overall_grade, scores, scored_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(test_id)
+        .bind(&scores.scoring_version)
+        .bind(scores.overall_score)
+        .bind(scores.overall_grade.as_str())
+        .bind(serde_json::to_string(scores)?)
+        .bind(chrono::Utc::now().to_rfc3339())
         .execute(&self.pool)
         .await?;
         
         Ok(())
     }
     
+    /// The scores a test was first given, whatever version that was
+    pub async fn get_aim_scores(&self, test_id: &str) -> Result<Option<AIMScores>, Box<dyn std::error::Error>> {
+        let row = sqlx::query(
+            "SELECT scores FROM aim_scores WHERE test_id = ? ORDER BY scored_at ASC LIMIT 1"
+        )
+        .bind(test_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        match row {
+            Some(row) => Ok(Some(serde_json::from_str(row.get("scores"))?)),
+            None => Ok(None),
+        }
+    }
+    
+    fn direction_key(direction: LoadDirection) -> &'static str {
+        match direction {
+            LoadDirection::Download => "download",
+            LoadDirection::Upload => "upload",
+        }
+    }
+    
     pub async fn get_test_result(&self, test_id: &str) -> Result<Option<TestResult>, Box<dyn std::error::Error>> {
         let row = sqlx::query(
             "SELECT * FROM test_results WHERE id = ? LIMIT 1"
@@ -121,24 +358,7 @@ impl Database {
         .await?;
         
         match row {
-            Some(row) => {
-                let timestamp_str: String = row.get("timestamp");
-                let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)?
-                    .with_timezone(&chrono::Utc);
-                
-                Ok(Some(TestResult {
-                    id: row.get("id"),
-                    server_id: row.get("server_id"),
-                    timestamp,
-                    download_mbps: row.get("download_mbps"),
-                    upload_mbps: row.get("upload_mbps"),
-                    latency_ms: row.get("latency_ms"),
-                    jitter_ms: row.get("jitter_ms"),
-                    protocol: row.get("protocol"),
-                    client_ip: row.get("client_ip"),
-                    test_duration_ms: row.get::<i64, _>("test_duration_ms") as u64,
-                }))
-            }
+            Some(row) => Ok(Some(Self::test_result_from_row(&row)?)),
             None => Ok(None),
         }
     }
@@ -151,26 +371,235 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
         
-        let mut results = Vec::new();
-        for row in rows {
-            let timestamp_str: String = row.get("timestamp");
-            let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)?
-                .with_timezone(&chrono::Utc);
-            
-            results.push(TestResult {
-                id: row.get("id"),
-                server_id: row.get("server_id"),
-                timestamp,
-                download_mbps: row.get("download_mbps"),
-                upload_mbps: row.get("upload_mbps"),
-                latency_ms: row.get("latency_ms"),
-                jitter_ms: row.get("jitter_ms"),
-                protocol: row.get("protocol"),
-                client_ip: row.get("client_ip"),
-                test_duration_ms: row.get::<i64, _>("test_duration_ms") as u64,
-            });
+        rows.iter().map(Self::test_result_from_row).collect()
+    }
+    
+    /// Measured tests a plan applies to since `since`, oldest first. Tests
+    /// must come from the plan's client IP; like `find_plan_for`, an IP plan
+    /// leaves out tests from devices with a plan of their own.
+    pub async fn get_tests_for_plan(
+        &self,
+        plan: &IspPlan,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<TestResult>, Box<dyn std::error::Error>> {
+        let Some(ip) = &plan.client_ip else {
+            return Ok(Vec::new());
+        };
+        let (filter, values) = match &plan.device_id {
+            Some(device_id) => ("device_id = ? AND client_ip = ?", [device_id, ip]),
+            None => (
+                "client_ip = ? AND (device_id IS NULL OR device_id NOT IN \
+                 (SELECT device_id FROM isp_plans WHERE device_id IS NOT NULL AND client_ip = ?))",
+                [ip, ip],
+            ),
+        };
+        let query = format!(
+            "SELECT * FROM test_results WHERE {} AND measured = 1 AND timestamp >= ? ORDER BY timestamp ASC", filter
+        );
+        let rows = sqlx::query(&query)
+            .bind(values[0])
+            .bind(values[1])
+            .bind(since.to_rfc3339())
+            .fetch_all(&self.pool)
+            .await?;
+        
+        rows.iter().map(Self::test_result_from_row).collect()
+    }
+    
+    fn test_result_from_row(row: &SqliteRow) -> Result<TestResult, Box<dyn std::error::Error>> {
+        let timestamp_str: String = row.get("timestamp");
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)?
+            .with_timezone(&chrono::Utc);
+        
+        Ok(TestResult {
+            id: row.get("id"),
+            server_id: row.get("server_id"),
+            timestamp,
+            download_mbps: row.get("download_mbps"),
+            upload_mbps: row.get("upload_mbps"),
+            latency_ms: row.get("latency_ms"),
+            jitter_ms: row.get("jitter_ms"),
+            protocol: row.get("protocol"),
+            client_ip: row.get("client_ip"),
+            test_duration_ms: row.get::<i64, _>("test_duration_ms") as u64,
+            device_id: row.get("device_id"),
+            measured: row.get("measured"),
+        })
+    }
+    
+    pub async fn save_plan(&self, plan: &IspPlan) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            r#"
+            INSERT INTO isp_plans
+            (id, name, isp, download_mbps, upload_mbps, latency_sla_ms, minimum_pct, device_id, client_ip, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&plan.id)
+        .bind(&plan.name)
+        .bind(&plan.isp)
+        .bind(plan.download_mbps)
+        .bind(plan.upload_mbps)
+        .bind(plan.latency_sla_ms)
+        .bind(plan.minimum_pct)
+        .bind(&plan.device_id)
+        .bind(&plan.client_ip)
+        .bind(plan.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// A plan registered from `client_ip`
+    pub async fn get_plan(&self, plan_id: &str, client_ip: &str) -> Result<Option<IspPlan>, Box<dyn std::error::Error>> {
+        let row = sqlx::query(
+            "SELECT * FROM isp_plans WHERE id = ? AND client_ip = ? LIMIT 1"
+        )
+        .bind(plan_id)
+        .bind(client_ip)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        match row {
+            Some(row) => Ok(Some(Self::plan_from_row(&row)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Plans registered for a device id and/or client IP, newest first
+    pub async fn list_plans(
+        &self,
+        device_id: Option<&str>,
+        client_ip: Option<&str>,
+    ) -> Result<Vec<IspPlan>, Box<dyn std::error::Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM isp_plans
+            WHERE (? IS NULL OR device_id = ?) AND (? IS NULL OR client_ip = ?)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(device_id)
+        .bind(device_id)
+        .bind(client_ip)
+        .bind(client_ip)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter().map(Self::plan_from_row).collect()
+    }
+    
+    /// The plan a test is judged against: the latest plan its device
+    /// registered from the same IP, or else the latest plan registered for
+    /// the client IP without a device. Device ids are chosen by clients and
+    /// collide across households, so they never match on their own.
+    pub async fn find_plan_for(&self, test: &TestResult) -> Result<Option<IspPlan>, Box<dyn std::error::Error>> {
+        if let Some(device_id) = &test.device_id {
+            if let Some(plan) = self.list_plans(Some(device_id), Some(&test.client_ip)).await?.into_iter().next() {
+                return Ok(Some(plan));
+            }
         }
+        Ok(self.list_plans(None, Some(&test.client_ip)).await?
+            .into_iter()
+            .find(|plan| plan.device_id.is_none()))
+    }
+    
+    /// Delete a plan registered from `client_ip`
+    pub async fn delete_plan(&self, plan_id: &str, client_ip: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let result = sqlx::query("DELETE FROM isp_plans WHERE id = ? AND client_ip = ?")
+            .bind(plan_id)
+            .bind(client_ip)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    fn plan_from_row(row: &SqliteRow) -> Result<IspPlan, Box<dyn std::error::Error>> {
+        let created_at: String = row.get("created_at");
         
-        Ok(results)
+        Ok(IspPlan {
+            id: row.get("id"),
+            name: row.get("name"),
+            isp: row.get("isp"),
+            download_mbps: row.get("download_mbps"),
+            upload_mbps: row.get("upload_mbps"),
+            latency_sla_ms: row.get("latency_sla_ms"),
+            minimum_pct: row.get("minimum_pct"),
+            device_id: row.get("device_id"),
+            client_ip: row.get("client_ip"),
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&chrono::Utc),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::plan_compliance::PlanRegistration;
+
+    fn registration(device_id: Option<&str>) -> PlanRegistration {
+        PlanRegistration {
+            name: "Fiber 300".to_string(),
+            isp: None,
+            download_mbps: 300.0,
+            upload_mbps: 50.0,
+            latency_sla_ms: None,
+            minimum_pct: None,
+            device_id: device_id.map(str::to_string),
+            client_ip: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plans_match_their_ip_and_measured_speeds_only() {
+        let path = std::env::temp_dir().join(format!("speedtest-{}.db", uuid::Uuid::new_v4()));
+        let db = Database::new(path.to_str().unwrap()).await.unwrap();
+        let ip = "203.0.113.7";
+
+        let ip_plan = registration(None).into_plan(ip).unwrap();
+        db.save_plan(&ip_plan).await.unwrap();
+        let device_plan = registration(Some("living-room-pc")).into_plan(ip).unwrap();
+        db.save_plan(&device_plan).await.unwrap();
+
+        let test = |client_ip: &str, device_id: Option<&str>, measured: bool| {
+            let mut test = TestResult::new("mumbai-01".to_string(), client_ip.to_string());
+            test.device_id = device_id.map(str::to_string);
+            test.measured = measured;
+            test
+        };
+        let plain = test(ip, None, true);
+        let unplanned_device = test(ip, Some("phone"), true);
+        let planned_device = test(ip, Some("living-room-pc"), true);
+        // Same device id from another household
+        let impostor = test("198.51.100.2", Some("living-room-pc"), true);
+        for result in [&plain, &unplanned_device, &planned_device, &impostor, &test(ip, None, false)] {
+            db.save_test_result(result).await.unwrap();
+        }
+
+        let since = chrono::Utc::now() - chrono::Duration::days(1);
+        let mut ids: Vec<String> = db.get_tests_for_plan(&ip_plan, since).await.unwrap()
+            .into_iter()
+            .map(|test| test.id)
+            .collect();
+        ids.sort();
+        let mut expected = vec![plain.id, unplanned_device.id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let device_tests = db.get_tests_for_plan(&device_plan, since).await.unwrap();
+        assert_eq!(device_tests.len(), 1);
+        assert_eq!(device_tests[0].id, planned_device.id);
+        assert!(db.find_plan_for(&impostor).await.unwrap().is_none());
+        assert_eq!(db.find_plan_for(&planned_device).await.unwrap().unwrap().id, device_plan.id);
+
+        // Only the registering IP can read or delete a plan
+        assert!(db.get_plan(&ip_plan.id, "198.51.100.2").await.unwrap().is_none());
+        assert!(db.get_plan(&ip_plan.id, ip).await.unwrap().is_some());
+        assert!(!db.delete_plan(&ip_plan.id, "198.51.100.2").await.unwrap());
+        assert!(db.delete_plan(&ip_plan.id, ip).await.unwrap());
+
+        let _ = std::fs::remove_file(path);
     }
 }
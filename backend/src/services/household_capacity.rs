@@ -0,0 +1,402 @@
+//! Household Concurrency Capacity
+//!
+//! Estimates how many 4K streams, HD video calls and online games a link
+//! sustains at the same time, from measured throughput and latency under
+//! load, and checks whether a given mix of them fits. Model:
+//! - Each session needs a fixed bitrate per direction (see `Activity`)
+//! - Only 80% of the measured throughput is budgeted; the rest absorbs
+//!   bitrate peaks, TCP ramp-up and background traffic
+//! - Calls and games also have a round-trip budget. When latency under load
+//!   exceeds it, queues build as the link fills, so those sessions only work
+//!   while the link stays at most half used
+//! - If even the idle latency exceeds the budget, the session does not fit
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::TestResult;
+use crate::services::loaded_latency::LoadedLatencyResult;
+
+/// Share of the measured throughput sessions may use
+const HEADROOM_FACTOR: f64 = 0.8;
+/// Share of the budget usable by latency-sensitive mixes on a bufferbloated link
+const BLOATED_UTILIZATION: f64 = 0.5;
+/// Most sessions of one activity reported, so the search stays bounded on
+/// multi-gigabit (or bogus) measurements
+const MAX_SESSIONS: u32 = 1000;
+
+/// A kind of concurrent household session
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Activity {
+    #[serde(rename = "stream_4k")]
+    Stream4k,
+    HdVideoCall,
+    Gaming,
+}
+
+impl Activity {
+    pub const ALL: [Activity; 3] = [Activity::Stream4k, Activity::HdVideoCall, Activity::Gaming];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Activity::Stream4k => "stream_4k",
+            Activity::HdVideoCall => "hd_video_call",
+            Activity::Gaming => "gaming",
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Activity::Stream4k => "4K stream",
+            Activity::HdVideoCall => "HD video call",
+            Activity::Gaming => "online game",
+        }
+    }
+
+    pub fn emoji(&self) -> &str {
+        match self {
+            Activity::Stream4k => "📺",
+            Activity::HdVideoCall => "💼",
+            Activity::Gaming => "🎮",
+        }
+    }
+
+    /// Download per session: 4K HDR streaming, a 1080p group call, a game plus voice chat
+    pub fn download_mbps(&self) -> f64 {
+        match self {
+            Activity::Stream4k => 25.0,
+            Activity::HdVideoCall => 3.0,
+            Activity::Gaming => 3.0,
+        }
+    }
+
+    /// Upload per session; streams only send acknowledgements
+    pub fn upload_mbps(&self) -> f64 {
+        match self {
+            Activity::Stream4k => 0.5,
+            Activity::HdVideoCall => 3.8,
+            Activity::Gaming => 1.0,
+        }
+    }
+
+    /// Highest round trip the session tolerates; streams buffer instead
+    pub fn latency_budget_ms(&self) -> Option<f64> {
+        match self {
+            Activity::Stream4k => None,
+            Activity::HdVideoCall => Some(150.0),
+            Activity::Gaming => Some(100.0),
+        }
+    }
+
+    fn plural(&self, count: u32) -> String {
+        if count == 1 {
+            format!("1 {}", self.label())
+        } else {
+            format!("{} {}s", count, self.label())
+        }
+    }
+}
+
+/// What stops one more session from fitting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CapacityLimit {
+    Download,
+    Upload,
+    Latency,
+}
+
+impl CapacityLimit {
+    pub fn description(&self) -> &str {
+        match self {
+            CapacityLimit::Download => "Download bandwidth",
+            CapacityLimit::Upload => "Upload bandwidth",
+            CapacityLimit::Latency => "Latency under load (bufferbloat)",
+        }
+    }
+}
+
+/// How many sessions of one activity fit on their own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityCapacity {
+    pub activity: Activity,
+    pub label: String,
+    pub download_mbps_each: f64,
+    pub upload_mbps_each: f64,
+    pub latency_budget_ms: Option<f64>,
+    pub max_sessions: u32,
+    pub limited_by: CapacityLimit,
+}
+
+/// Simultaneous sessions of each activity
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ActivityMix {
+    #[serde(default)]
+    pub streams_4k: u32,
+    #[serde(default)]
+    pub video_calls: u32,
+    #[serde(default)]
+    pub gaming_sessions: u32,
+}
+
+impl ActivityMix {
+    pub fn count(&self, activity: Activity) -> u32 {
+        match activity {
+            Activity::Stream4k => self.streams_4k,
+            Activity::HdVideoCall => self.video_calls,
+            Activity::Gaming => self.gaming_sessions,
+        }
+    }
+
+    fn with(mut self, activity: Activity, count: u32) -> Self {
+        match activity {
+            Activity::Stream4k => self.streams_4k = count,
+            Activity::HdVideoCall => self.video_calls = count,
+            Activity::Gaming => self.gaming_sessions = count,
+        }
+        self
+    }
+
+    /// "2 4K streams + 1 HD video call"
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = Activity::ALL.iter()
+            .filter(|activity| self.count(**activity) > 0)
+            .map(|activity| activity.plural(self.count(*activity)))
+            .collect();
+        if parts.is_empty() {
+            "nothing".to_string()
+        } else {
+            parts.join(" + ")
+        }
+    }
+}
+
+/// Whether a mix fits the link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixCheck {
+    pub mix: ActivityMix,
+    pub fits: bool,
+    /// Share of the download budget the mix uses, in percent
+    pub download_used_pct: f64,
+    pub upload_used_pct: f64,
+    /// Why it does not fit; `None` when it does
+    pub limited_by: Option<CapacityLimit>,
+}
+
+/// Concurrency estimate for a test, with what is needed to check other mixes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HouseholdCapacity {
+    /// Throughput available to sessions after headroom
+    pub download_budget_mbps: f64,
+    pub upload_budget_mbps: f64,
+    pub idle_latency_ms: f64,
+    /// The worse of download and upload loaded latency
+    pub loaded_latency_ms: f64,
+    /// Budget share usable by calls and games when `loaded_latency_ms` exceeds their latency budget
+    pub bloated_utilization_pct: f64,
+    pub activities: Vec<ActivityCapacity>,
+    /// The most 4K streams that fit next to common call and gaming loads
+    pub suggested_mixes: Vec<MixCheck>,
+    /// One or two sentences support staff can quote
+    pub summary: String,
+}
+
+impl HouseholdCapacity {
+    /// Check whether `mix` runs smoothly at the same time
+    pub fn check(&self, mix: &ActivityMix) -> MixCheck {
+        let needs = |activity: Activity| mix.count(activity) > 0;
+        let download_used: f64 = Activity::ALL.iter()
+            .map(|a| mix.count(*a) as f64 * a.download_mbps())
+            .sum();
+        let upload_used: f64 = Activity::ALL.iter()
+            .map(|a| mix.count(*a) as f64 * a.upload_mbps())
+            .sum();
+        // A dead link still gets a finite (huge) percentage so results serialize
+        let percent = |used: f64, budget: f64| used / budget.max(1e-3) * 100.0;
+        let download_used_pct = percent(download_used, self.download_budget_mbps);
+        let upload_used_pct = percent(upload_used, self.upload_budget_mbps);
+
+        let budgets: Vec<f64> = Activity::ALL.iter()
+            .filter(|a| needs(**a))
+            .filter_map(|a| a.latency_budget_ms())
+            .collect();
+        let idle_too_slow = budgets.iter().any(|budget| self.idle_latency_ms > *budget);
+        let utilization = if budgets.iter().any(|budget| self.loaded_latency_ms > *budget) {
+            self.bloated_utilization_pct
+        } else {
+            100.0
+        };
+
+        let limited_by = if idle_too_slow {
+            Some(CapacityLimit::Latency)
+        } else if download_used_pct > 100.0 + 1e-9 {
+            Some(CapacityLimit::Download)
+        } else if upload_used_pct > 100.0 + 1e-9 {
+            Some(CapacityLimit::Upload)
+        } else if download_used_pct.max(upload_used_pct) > utilization + 1e-9 {
+            Some(CapacityLimit::Latency)
+        } else {
+            None
+        };
+
+        MixCheck {
+            mix: *mix,
+            fits: limited_by.is_none(),
+            download_used_pct,
+            upload_used_pct,
+            limited_by,
+        }
+    }
+
+    /// Most sessions of `activity` that fit alongside `base`, up to `MAX_SESSIONS`
+    fn max_alongside(&self, base: ActivityMix, activity: Activity) -> (u32, CapacityLimit) {
+        let mut count = base.count(activity);
+        while count < MAX_SESSIONS {
+            let check = self.check(&base.with(activity, count + 1));
+            if let Some(limit) = check.limited_by {
+                return (count, limit);
+            }
+            count += 1;
+        }
+        
+        // Capped: report the direction closest to running out
+        let check = self.check(&base.with(activity, count));
+        let limit = if check.download_used_pct >= check.upload_used_pct {
+            CapacityLimit::Download
+        } else {
+            CapacityLimit::Upload
+        };
+        (count, limit)
+    }
+}
+
+pub struct CapacityCalculator;
+
+impl CapacityCalculator {
+    pub fn estimate(test_result: &TestResult, loaded_latency: &LoadedLatencyResult) -> HouseholdCapacity {
+        // A non-finite speed is a measurement failure, not an infinite link
+        let budget = |mbps: f64| if mbps.is_finite() { mbps.max(0.0) * HEADROOM_FACTOR } else { 0.0 };
+        let mut capacity = HouseholdCapacity {
+            download_budget_mbps: budget(test_result.download_mbps),
+            upload_budget_mbps: budget(test_result.upload_mbps),
+            idle_latency_ms: loaded_latency.idle_avg_ms,
+            loaded_latency_ms: loaded_latency.download_avg_ms.max(loaded_latency.upload_avg_ms),
+            bloated_utilization_pct: BLOATED_UTILIZATION * 100.0,
+            activities: Vec::new(),
+            suggested_mixes: Vec::new(),
+            summary: String::new(),
+        };
+
+        capacity.activities = Activity::ALL.iter()
+            .map(|activity| {
+                let (max_sessions, limited_by) = capacity.max_alongside(ActivityMix::default(), *activity);
+                ActivityCapacity {
+                    activity: *activity,
+                    label: activity.label().to_string(),
+                    download_mbps_each: activity.download_mbps(),
+                    upload_mbps_each: activity.upload_mbps(),
+                    latency_budget_ms: activity.latency_budget_ms(),
+                    max_sessions,
+                    limited_by,
+                }
+            })
+            .collect();
+
+        for (video_calls, gaming_sessions) in [(1, 0), (0, 1), (1, 1), (2, 1)] {
+            let base = ActivityMix { streams_4k: 0, video_calls, gaming_sessions };
+            if !capacity.check(&base).fits {
+                continue;
+            }
+            let (streams_4k, _) = capacity.max_alongside(base, Activity::Stream4k);
+            capacity.suggested_mixes.push(capacity.check(&ActivityMix { streams_4k, ..base }));
+        }
+
+        capacity.summary = Self::summarize(&capacity);
+        capacity
+    }
+
+    fn summarize(capacity: &HouseholdCapacity) -> String {
+        let alone: Vec<String> = capacity.activities.iter()
+            .map(|a| a.activity.plural(a.max_sessions))
+            .collect();
+        let mut summary = format!("Supports up to {} at once", alone.join(", or "));
+
+        // Quote the busiest suggested mix that still includes a stream
+        if let Some(mix) = capacity.suggested_mixes.iter()
+            .filter(|check| check.mix.streams_4k > 0)
+            .max_by_key(|check| check.mix.video_calls + check.mix.gaming_sessions)
+        {
+            summary.push_str(&format!("; for example {} together", mix.mix.describe()));
+        }
+        summary.push('.');
+
+        if capacity.activities.iter().any(|a| a.limited_by == CapacityLimit::Latency) {
+            summary.push_str(" Latency under load limits calls and games; enabling SQM/QoS on the router would raise this.");
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::loaded_latency::LoadedLatencyTester;
+
+    fn link(download: f64, upload: f64, idle_ms: f64, loaded_ms: f64) -> HouseholdCapacity {
+        let mut test_result = TestResult::new("test".to_string(), "127.0.0.1".to_string());
+        test_result.download_mbps = download;
+        test_result.upload_mbps = upload;
+        let mut loaded_latency = LoadedLatencyTester::new().calculate_results();
+        loaded_latency.idle_avg_ms = idle_ms;
+        loaded_latency.download_avg_ms = loaded_ms;
+        loaded_latency.upload_avg_ms = loaded_ms;
+        CapacityCalculator::estimate(&test_result, &loaded_latency)
+    }
+
+    #[test]
+    fn test_capacity_follows_download_upload_and_latency() {
+        // 100/10 cable with a well-behaved queue: 80/8 Mbps budget
+        let cable = link(100.0, 10.0, 15.0, 40.0);
+        let max = |c: &HouseholdCapacity, a: Activity| c.activities.iter().find(|x| x.activity == a).unwrap().max_sessions;
+        assert_eq!(max(&cable, Activity::Stream4k), 3);
+        assert_eq!(max(&cable, Activity::HdVideoCall), 2);
+        assert_eq!(cable.activities[1].limited_by, CapacityLimit::Upload);
+        assert_eq!(max(&cable, Activity::Gaming), 8);
+
+        let mix = ActivityMix { streams_4k: 2, video_calls: 1, gaming_sessions: 1 };
+        let check = cable.check(&mix);
+        assert!(check.fits);
+        assert!((check.download_used_pct - 70.0).abs() < 1e-9);
+        assert!(!cable.check(&ActivityMix { streams_4k: 3, ..mix }).fits);
+        assert!(cable.summary.contains("3 4K streams, or 2 HD video calls, or 8 online games"));
+        assert_eq!(serde_json::to_value(&cable).unwrap()["activities"][0]["activity"], Activity::Stream4k.as_str());
+
+        // Same speeds, but the queue bloats to 250 ms when full
+        let bloated = link(100.0, 10.0, 15.0, 250.0);
+        assert_eq!(max(&bloated, Activity::Stream4k), 3);
+        assert_eq!(max(&bloated, Activity::HdVideoCall), 1);
+        assert_eq!(bloated.activities[1].limited_by, CapacityLimit::Latency);
+        assert_eq!(bloated.check(&mix).limited_by, Some(CapacityLimit::Latency));
+        assert!(bloated.summary.contains("SQM"));
+
+        // Satellite: no call or game fits at any load
+        let satellite = link(100.0, 10.0, 600.0, 700.0);
+        assert_eq!(max(&satellite, Activity::Gaming), 0);
+        assert!(satellite.suggested_mixes.is_empty());
+    }
+
+    #[test]
+    fn test_capacity_is_bounded() {
+        let max = |c: &HouseholdCapacity, a: Activity| c.activities.iter().find(|x| x.activity == a).unwrap().max_sessions;
+
+        let broken = link(f64::INFINITY, f64::NAN, 15.0, 40.0);
+        assert_eq!(broken.download_budget_mbps, 0.0);
+        assert_eq!(broken.upload_budget_mbps, 0.0);
+        assert_eq!(max(&broken, Activity::Stream4k), 0);
+
+        let huge = link(1e9, 1e9, 5.0, 10.0);
+        assert_eq!(max(&huge, Activity::Gaming), MAX_SESSIONS);
+        assert_eq!(huge.activities[2].limited_by, CapacityLimit::Download);
+        assert!(serde_json::to_string(&huge).is_ok());
+    }
+}
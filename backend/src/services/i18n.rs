@@ -0,0 +1,272 @@
+//! Localized Messages
+//!
+//! User-facing assessments and recommendations are looked up by a stable key
+//! in a per-locale message catalog (`locales/*.toml`) and filled with named
+//! `{placeholders}`. Every text carries its key and parameters next to the
+//! rendered string, so clients can translate with their own catalog, and
+//! stored results can be re-rendered in the locale negotiated per request
+//! from `?lang=` or `Accept-Language`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+const EN_CATALOG: &str = include_str!("../../locales/en.toml");
+const HI_CATALOG: &str = include_str!("../../locales/hi.toml");
+
+/// Key of texts without a catalog entry; its template is `{text}`
+pub const CUSTOM_KEY: &str = "custom";
+
+static CATALOGS: Lazy<HashMap<Locale, BTreeMap<String, String>>> = Lazy::new(|| {
+    Locale::ALL.into_iter()
+        .map(|locale| {
+            let source = match locale {
+                Locale::En => EN_CATALOG,
+                Locale::Hi => HI_CATALOG,
+            };
+            let table: toml::Table = toml::from_str(source)
+                .unwrap_or_else(|e| panic!("built-in {} catalog is valid TOML: {}", locale.as_str(), e));
+            let mut messages = BTreeMap::new();
+            flatten("", table, &mut messages);
+            (locale, messages)
+        })
+        .collect()
+});
+
+/// English texts without placeholders to their keys, for stored plain strings
+static ENGLISH_KEYS: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    CATALOGS[&Locale::En].iter()
+        .filter(|(key, text)| key.as_str() != CUSTOM_KEY && !text.contains('{'))
+        .map(|(key, text)| (text.as_str(), key.as_str()))
+        .collect()
+});
+
+/// English scoring-profile band texts to their keys
+static PROFILE_KEYS: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    CATALOGS[&Locale::En].iter()
+        .filter(|(key, _)| key.starts_with("profile."))
+        .map(|(key, text)| (text.as_str(), key.as_str()))
+        .collect()
+});
+
+fn flatten(prefix: &str, table: toml::Table, messages: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::String(text) => {
+                messages.insert(key, text);
+            }
+            toml::Value::Table(table) => flatten(&key, table, messages),
+            other => panic!("message {}: expected a string, found {}", key, other.type_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Hi,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Hi];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Locale::En => "en",
+            Locale::Hi => "hi",
+        }
+    }
+
+    /// Name of the language in itself
+    pub fn label(&self) -> &str {
+        match self {
+            Locale::En => "English",
+            Locale::Hi => "हिन्दी",
+        }
+    }
+
+    /// Parse a language tag by its primary subtag: "hi", "hi-IN", "en_GB"
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_lowercase();
+        Self::ALL.into_iter().find(|locale| locale.as_str() == primary)
+    }
+
+    /// An explicit `lang` wins if supported; otherwise the supported language
+    /// with the highest `Accept-Language` quality; otherwise English
+    pub fn negotiate(lang: Option<&str>, accept_language: Option<&str>) -> Self {
+        if let Some(locale) = lang.and_then(Self::from_tag) {
+            return locale;
+        }
+
+        let mut ranges: Vec<(&str, f64)> = accept_language.unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f64>().ok())?;
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so equally weighted ranges keep the client's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranges.into_iter()
+            .find_map(|(tag, _)| Self::from_tag(tag))
+            .unwrap_or_default()
+    }
+
+    /// Every message of this locale, falling back to English
+    pub fn catalog(&self) -> BTreeMap<&'static str, &'static str> {
+        let mut messages: BTreeMap<&str, &str> = CATALOGS[&Locale::En].iter()
+            .map(|(key, text)| (key.as_str(), text.as_str()))
+            .collect();
+        messages.extend(CATALOGS[self].iter().map(|(key, text)| (key.as_str(), text.as_str())));
+        messages
+    }
+}
+
+/// Render `key` in `locale`, falling back to English and then to the key itself
+pub fn translate(locale: Locale, key: &str, params: &BTreeMap<String, String>) -> String {
+    let template = CATALOGS[&locale].get(key)
+        .or_else(|| CATALOGS[&Locale::En].get(key))
+        .map_or(key, String::as_str);
+
+    let mut text = template.to_string();
+    for (name, value) in params {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Key of a built-in scoring-profile band text, by its English template
+pub fn profile_key(template: &str) -> Option<&'static str> {
+    PROFILE_KEYS.get(template).copied()
+}
+
+/// A user-facing text with the key and parameters it was rendered from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(from = "MessageRepr")]
+pub struct Message {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+    /// Rendered in the response's locale
+    pub text: String,
+}
+
+/// Results stored before texts were localized hold plain strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MessageRepr {
+    Plain(String),
+    Keyed {
+        key: String,
+        #[serde(default)]
+        params: BTreeMap<String, String>,
+        text: String,
+    },
+}
+
+impl From<MessageRepr> for Message {
+    fn from(repr: MessageRepr) -> Self {
+        match repr {
+            MessageRepr::Plain(text) => match ENGLISH_KEYS.get(text.as_str()) {
+                Some(key) => Message::new(*key, &[]),
+                None => Message::custom(text),
+            },
+            MessageRepr::Keyed { key, params, text } => Message { key, params, text },
+        }
+    }
+}
+
+impl Message {
+    /// A catalog message, rendered in English
+    pub fn new(key: impl Into<String>, params: &[(&str, String)]) -> Self {
+        let key = key.into();
+        let params: BTreeMap<String, String> = params.iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        let text = translate(Locale::En, &key, &params);
+        Message { key, params, text }
+    }
+
+    /// A text no catalog translates
+    pub fn custom(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Message {
+            key: CUSTOM_KEY.to_string(),
+            params: BTreeMap::from([("text".to_string(), text.clone())]),
+            text,
+        }
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        translate(locale, &self.key, &self.params)
+    }
+
+    /// Re-render `text` in `locale`
+    pub fn localize(&mut self, locale: Locale) {
+        self.text = self.render(locale);
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_negotiation() {
+        assert_eq!(Locale::negotiate(Some("hi-IN"), Some("en-US")), Locale::Hi);
+        assert_eq!(Locale::negotiate(Some("fr"), Some("hi;q=0.8, en;q=0.5")), Locale::Hi);
+        assert_eq!(Locale::negotiate(None, Some("fr-FR, en;q=0.4, hi;q=0.9")), Locale::Hi);
+        assert_eq!(Locale::negotiate(None, Some("hi;q=0, en-GB")), Locale::En);
+        assert_eq!(Locale::negotiate(None, Some("de, fr")), Locale::En);
+        assert_eq!(Locale::negotiate(None, None), Locale::En);
+    }
+
+    #[test]
+    fn test_catalogs_agree_and_messages_relocalize() {
+        let english = &CATALOGS[&Locale::En];
+        let placeholders = |text: &str| {
+            let mut names: Vec<String> = text.split('{').skip(1)
+                .filter_map(|rest| rest.split_once('}').map(|(name, _)| name.to_string()))
+                .collect();
+            names.sort();
+            names
+        };
+        for locale in Locale::ALL {
+            for (key, text) in &CATALOGS[&locale] {
+                let source = english.get(key).unwrap_or_else(|| panic!("{}: {} is not an English key", locale.as_str(), key));
+                assert_eq!(placeholders(text), placeholders(source), "{}: {}", locale.as_str(), key);
+            }
+        }
+
+        let key = profile_key("Speed {value} Mbps too low for HD").unwrap();
+        let mut message = Message::new(key, &[("value", "4.2".to_string())]);
+        assert_eq!(message.text, "Speed 4.2 Mbps too low for HD");
+        message.localize(Locale::Hi);
+        assert_eq!(message.text, "स्पीड 4.2 Mbps HD के लिए बहुत कम है");
+
+        // Stored before localization: plain strings, keyed if the catalog has them
+        let legacy: Message = serde_json::from_str("\"Not suitable for online gaming\"").unwrap();
+        assert_eq!(legacy.key, "assessment.gaming.very_poor");
+        let unknown: Message = serde_json::from_str("\"Call your ISP on Tuesdays\"").unwrap();
+        assert_eq!(unknown.key, CUSTOM_KEY);
+        assert_eq!(unknown.render(Locale::Hi), "Call your ISP on Tuesdays");
+        let stored: Message = serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+        assert_eq!(stored, message);
+    }
+}
@@ -0,0 +1,156 @@
+//! Jitter and Packet Delay Variation
+//!
+//! Shared jitter definitions used by every measurement engine:
+//! - RFC 3550 interarrival jitter: the smoothed (1/16 gain) mean deviation
+//!   of the transit-time difference between consecutive received probes
+//! - RFC 5481 IPDV: delay difference between consecutive sequence numbers,
+//!   undefined (skipped) when either probe of the pair was lost
+//! - RFC 5481 PDV: each delay relative to the minimum delay of the stream
+//!
+//! Probes are round trips, so "delay" is the RTT; send-time differences
+//! cancel out of the RFC 3550 transit difference.
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::loaded_latency::LatencySample;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct JitterMetrics {
+    /// RFC 3550 smoothed interarrival jitter
+    pub rfc3550_jitter_ms: f64,
+
+    /// Mean and 95th percentile of |IPDV| over consecutive, both-received pairs
+    pub ipdv_mean_ms: f64,
+    pub ipdv_p95_ms: f64,
+
+    /// PDV percentiles (delay above the stream minimum)
+    pub pdv_p50_ms: f64,
+    pub pdv_p95_ms: f64,
+    pub pdv_p99_ms: f64,
+
+    /// Received probes the metrics were computed from
+    pub samples_used: usize,
+    /// Consecutive pairs that contributed to IPDV
+    pub ipdv_pairs: usize,
+}
+
+pub struct JitterCalculator;
+
+impl JitterCalculator {
+    /// Calculate jitter metrics from sequenced probes, respecting losses
+    pub fn calculate(samples: &[LatencySample]) -> JitterMetrics {
+        let mut received: Vec<(u32, f64)> = samples.iter()
+            .filter_map(|s| s.rtt().map(|rtt| (s.sequence, rtt)))
+            .collect();
+        received.sort_by_key(|(sequence, _)| *sequence);
+
+        Self::calculate_sequenced(&received)
+    }
+
+    /// RFC 3550 interarrival jitter over consecutive RTTs
+    pub fn rfc3550(rtts: &[f64]) -> f64 {
+        rtts.windows(2).fold(0.0, |jitter, pair| {
+            let d = (pair[1] - pair[0]).abs();
+            jitter + (d - jitter) / 16.0
+        })
+    }
+
+    fn calculate_sequenced(received: &[(u32, f64)]) -> JitterMetrics {
+        if received.is_empty() {
+            return JitterMetrics::default();
+        }
+
+        let rtts: Vec<f64> = received.iter().map(|(_, rtt)| *rtt).collect();
+
+        // IPDV is only defined for adjacent sequence numbers
+        let mut ipdv: Vec<f64> = received.windows(2)
+            .filter(|pair| pair[1].0 == pair[0].0 + 1)
+            .map(|pair| (pair[1].1 - pair[0].1).abs())
+            .collect();
+        ipdv.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let min_rtt = rtts.iter().copied().fold(f64::INFINITY, f64::min);
+        let mut pdv: Vec<f64> = rtts.iter().map(|rtt| rtt - min_rtt).collect();
+        pdv.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let ipdv_mean_ms = if ipdv.is_empty() {
+            0.0
+        } else {
+            ipdv.iter().sum::<f64>() / ipdv.len() as f64
+        };
+
+        JitterMetrics {
+            rfc3550_jitter_ms: Self::rfc3550(&rtts),
+            ipdv_mean_ms,
+            ipdv_p95_ms: Self::percentile(&ipdv, 95.0),
+            pdv_p50_ms: Self::percentile(&pdv, 50.0),
+            pdv_p95_ms: Self::percentile(&pdv, 95.0),
+            pdv_p99_ms: Self::percentile(&pdv, 99.0),
+            samples_used: rtts.len(),
+            ipdv_pairs: ipdv.len(),
+        }
+    }
+
+    /// Nearest-rank percentile of an already sorted slice
+    fn percentile(sorted: &[f64], p: f64) -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::loaded_latency::{ProbeOutcome, ProbeType};
+    use chrono::Utc;
+
+    fn sample(sequence: u32, rtt_ms: Option<f64>) -> LatencySample {
+        LatencySample {
+            sequence,
+            sent_at: Utc::now(),
+            probe_type: ProbeType::Foreign,
+            outcome: if rtt_ms.is_some() { ProbeOutcome::Ok } else { ProbeOutcome::Timeout },
+            rtt_ms,
+        }
+    }
+
+    #[test]
+    fn test_rfc3550_smoothing() {
+        // A single 16ms step moves the estimate by 1/16 of it
+        assert!((JitterCalculator::rfc3550(&[10.0, 26.0]) - 1.0).abs() < 0.001);
+        assert_eq!(JitterCalculator::rfc3550(&[10.0, 10.0, 10.0]), 0.0);
+    }
+
+    #[test]
+    fn test_jitter_calculation() {
+        // The latency stage of both measurement engines
+        let latencies = vec![10.0, 11.0, 10.5, 12.0, 11.5];
+        let jitter = JitterCalculator::rfc3550(&latencies);
+
+        // Smoothed, so well below the 1.0ms mean consecutive difference
+        assert!(jitter > 0.0 && jitter < 1.0);
+        assert_eq!(JitterCalculator::rfc3550(&[10.0]), 0.0);
+        assert_eq!(JitterCalculator::rfc3550(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_ipdv_skips_lost_probes() {
+        let samples = vec![
+            sample(0, Some(10.0)),
+            sample(1, Some(12.0)),
+            sample(2, None),
+            sample(3, Some(30.0)),
+            sample(4, Some(31.0)),
+        ];
+
+        let metrics = JitterCalculator::calculate(&samples);
+        // Pairs (0,1) and (3,4) only; the 12 -> 30 jump spans a loss
+        assert_eq!(metrics.ipdv_pairs, 2);
+        assert!((metrics.ipdv_mean_ms - 1.5).abs() < 0.001);
+        assert_eq!(metrics.samples_used, 4);
+        assert_eq!(metrics.pdv_p99_ms, 21.0);
+    }
+}
@@ -1,6 +1,15 @@
 use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::services::consistency::{ConsistencyCalculator, ConsistencyScore};
+use crate::services::i18n::{Locale, Message};
+use crate::services::jitter::{JitterCalculator, JitterMetrics};
+use crate::services::one_way_delay::{ClockModel, OneWayDelayEstimator, OneWayDelayResult};
+use crate::services::packet_loss::PacketLossResult;
+use crate::services::responsiveness::{LoadDirection, ResponsivenessResult, ResponsivenessTester};
+use crate::services::stall_detection::{StallAnalysis, StallDetector};
+
 /// Loaded Latency Test - Measures latency in 3 stages
 /// Based on research from Ookla and Cloudflare (2024)
 /// 
@@ -14,21 +23,55 @@ pub struct LoadedLatencyResult {
     pub idle_max_ms: f64,
     pub idle_avg_ms: f64,
     pub idle_median_ms: f64,
-    pub idle_samples: Vec<f64>,
+    pub idle_samples: Vec<LatencySample>,
+    #[serde(default)]
+    pub idle_jitter: JitterMetrics,
+    #[serde(default)]
+    pub idle_quality: StageQualityReport,
+    /// UDP loss measured concurrently with this stage
+    #[serde(default)]
+    pub idle_packet_loss: Option<PacketLossResult>,
+    /// Upstream vs downstream delay from the UDP probes of this stage
+    #[serde(default)]
+    pub idle_one_way: Option<OneWayDelayResult>,
     
     // Stage 2: Download loaded latency
     pub download_min_ms: f64,
     pub download_max_ms: f64,
     pub download_avg_ms: f64,
     pub download_median_ms: f64,
-    pub download_samples: Vec<f64>,
+    pub download_samples: Vec<LatencySample>,
+    #[serde(default)]
+    pub download_jitter: JitterMetrics,
+    #[serde(default)]
+    pub download_quality: StageQualityReport,
+    /// UDP loss measured concurrently with this stage
+    #[serde(default)]
+    pub download_packet_loss: Option<PacketLossResult>,
+    /// Upstream vs downstream delay from the UDP probes of this stage
+    #[serde(default)]
+    pub download_one_way: Option<OneWayDelayResult>,
     
     // Stage 3: Upload loaded latency
     pub upload_min_ms: f64,
     pub upload_max_ms: f64,
     pub upload_avg_ms: f64,
     pub upload_median_ms: f64,
-    pub upload_samples: Vec<f64>,
+    pub upload_samples: Vec<LatencySample>,
+    #[serde(default)]
+    pub upload_jitter: JitterMetrics,
+    #[serde(default)]
+    pub upload_quality: StageQualityReport,
+    /// UDP loss measured concurrently with this stage
+    #[serde(default)]
+    pub upload_packet_loss: Option<PacketLossResult>,
+    /// Upstream vs downstream delay from the UDP probes of this stage
+    #[serde(default)]
+    pub upload_one_way: Option<OneWayDelayResult>,
+    
+    /// Client/server clock model fitted on the idle UDP probes
+    #[serde(default)]
+    pub clock_model: Option<ClockModel>,
     
     // Derived metrics
     pub bufferbloat_download_ms: f64,    // How much latency increased
@@ -36,22 +79,289 @@ pub struct LoadedLatencyResult {
     pub bufferbloat_download_ratio: f64, // Percentage increase
     pub bufferbloat_upload_ratio: f64,
     pub bufferbloat_grade: BufferbloatGrade,
+    /// Why the grade was chosen (worst direction, and which threshold limited it)
+    #[serde(default)]
+    pub bufferbloat_rationale: Option<BufferbloatRationale>,
     
     // Responsiveness (Apple RPM metric)
     pub idle_rpm: f64,
     pub download_rpm: f64,
     pub upload_rpm: f64,
+    
+    // Spec-conformant responsiveness (IETF draft), when the full test was run
+    #[serde(default)]
+    pub download_responsiveness: Option<ResponsivenessResult>,
+    #[serde(default)]
+    pub upload_responsiveness: Option<ResponsivenessResult>,
 }
 
+/// A single latency probe, kept with enough context to line it up
+/// against throughput samples and to spot lost probes
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LatencySample {
+    /// Monotonic across all stages of one tester, so gaps reveal lost probes
+    pub sequence: u32,
+    /// Wall-clock time the probe was sent
+    pub sent_at: DateTime<Utc>,
+    pub probe_type: ProbeType,
+    pub outcome: ProbeOutcome,
+    /// Round-trip time, only present when the probe succeeded
+    pub rtt_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeType {
+    /// HTTP request on a freshly opened connection (includes TCP setup)
+    Foreign,
+    /// HTTP request on an already established, loaded connection
+    #[serde(rename = "self")]
+    SelfProbe,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeOutcome {
+    Ok,
+    /// No answer before the probe timeout
+    Timeout,
+    /// Failed before timing out, e.g. connection refused
+    Error,
+}
+
+impl LatencySample {
+    /// RTT of a successful probe, `None` for timeouts and errors
+    pub fn rtt(&self) -> Option<f64> {
+        match self.outcome {
+            ProbeOutcome::Ok => self.rtt_ms,
+            ProbeOutcome::Timeout | ProbeOutcome::Error => None,
+        }
+    }
+}
+
+/// How far a stage's statistics can be trusted
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StageQuality {
+    #[default]
+    Ok,
+    /// Many timeouts or outliers; the stats are usable but shaky
+    Noisy,
+    /// Too few successful probes to say anything
+    InsufficientSamples,
+}
+
+impl StageQuality {
+    pub fn as_str(&self) -> &str {
+        match self {
+            StageQuality::Ok => "ok",
+            StageQuality::Noisy => "noisy",
+            StageQuality::InsufficientSamples => "insufficient samples",
+        }
+    }
+}
+
+/// Probe accounting for one latency stage
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StageQualityReport {
+    pub quality: StageQuality,
+    /// Probes sent
+    pub probes: usize,
+    pub timeouts: usize,
+    /// Probes that failed without timing out
+    #[serde(default)]
+    pub errors: usize,
+    /// Successful probes excluded as MAD outliers
+    pub outliers: usize,
+    /// Probes the stage statistics were computed from
+    pub used: usize,
+}
+
+impl StageQualityReport {
+    /// Stages with fewer usable probes are flagged as insufficient
+    pub const MIN_SAMPLES: usize = 5;
+    /// Stages losing more than this share of probes are flagged as noisy
+    pub const MAX_DISCARD_RATIO: f64 = 0.2;
+
+    fn new(probes: usize, timeouts: usize, errors: usize, outliers: usize) -> Self {
+        let used = probes - timeouts - errors - outliers;
+        let quality = if used < Self::MIN_SAMPLES {
+            StageQuality::InsufficientSamples
+        } else if (timeouts + errors + outliers) as f64 / probes as f64 > Self::MAX_DISCARD_RATIO {
+            StageQuality::Noisy
+        } else {
+            StageQuality::Ok
+        };
+
+        Self { quality, probes, timeouts, errors, outliers, used }
+    }
+
+    pub fn is_reliable(&self) -> bool {
+        self.quality == StageQuality::Ok
+    }
+
+    /// One-line description of why the stage ("idle", "download" or
+    /// "upload") is unreliable, if it is
+    pub fn caveat(&self, stage: &str) -> Option<Message> {
+        match self.quality {
+            StageQuality::Ok => None,
+            StageQuality::Noisy => Some(Message::new(format!("caveat.{}_noisy", stage), &[
+                ("timeouts", self.timeouts.to_string()),
+                ("errors", self.errors.to_string()),
+                ("probes", self.probes.to_string()),
+                ("outliers", self.outliers.to_string()),
+            ])),
+            StageQuality::InsufficientSamples => Some(Message::new(format!("caveat.{}_insufficient_samples", stage), &[
+                ("used", self.used.to_string()),
+                ("timeouts", self.timeouts.to_string()),
+                ("errors", self.errors.to_string()),
+            ])),
+        }
+    }
+}
+
+/// Ordered best to worst, so `max` picks the worse grade
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BufferbloatGrade {
     #[serde(rename = "A+")]
-    APlus,      // <50% increase (excellent)
-    A,          // <100% increase (very good)
-    B,          // <200% increase (good)
-    C,          // <400% increase (fair)
-    D,          // <900% increase (poor)
-    F,          // >900% increase (terrible)
+    APlus,      // <5ms increase (excellent)
+    A,          // <30ms increase (very good)
+    B,          // <60ms increase (good)
+    C,          // <200ms increase (fair)
+    D,          // <400ms increase (poor)
+    F,          // >=400ms increase (terrible)
+    /// Idle or both loaded stages had too few samples to grade
+    #[serde(rename = "N/A")]
+    Ungraded,
+}
+
+impl BufferbloatGrade {
+    const ALL: [BufferbloatGrade; 6] = [
+        BufferbloatGrade::APlus,
+        BufferbloatGrade::A,
+        BufferbloatGrade::B,
+        BufferbloatGrade::C,
+        BufferbloatGrade::D,
+        BufferbloatGrade::F,
+    ];
+    
+    /// Grade for the first upper limit `value` stays below, F past the last one
+    fn from_limits(value: f64, limits: &[f64]) -> Self {
+        let index = limits.iter()
+            .position(|limit| value < *limit)
+            .unwrap_or(limits.len());
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+    
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|g| g == self).unwrap_or(0)
+    }
+}
+
+/// Bufferbloat grading thresholds, in the style of the Waveform and
+/// DSLReports tests: the absolute latency increase sets the grade, and the
+/// increase relative to idle latency can lower it once the increase is
+/// large enough to matter
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BufferbloatThresholds {
+    /// Upper limits of the latency increase (ms) for A+, A, B, C and D
+    pub increase_ms: Vec<f64>,
+    /// Upper limits of the increase ratio (0.5 = +50%) for A+, A, B, C and D
+    pub ratio: Vec<f64>,
+    /// Below this increase (ms) the ratio is ignored: +6ms on a 2ms link is fine
+    pub ratio_floor_ms: f64,
+    /// How many grades the ratio may drop below the absolute grade
+    pub max_ratio_penalty: usize,
+}
+
+impl Default for BufferbloatThresholds {
+    fn default() -> Self {
+        Self {
+            increase_ms: vec![5.0, 30.0, 60.0, 200.0, 400.0],
+            ratio: vec![0.5, 1.0, 2.0, 4.0, 9.0],
+            ratio_floor_ms: 30.0,
+            max_ratio_penalty: 1,
+        }
+    }
+}
+
+impl BufferbloatThresholds {
+    /// Grade a single direction from its latency increase and ratio
+    pub fn grade(&self, direction: LoadDirection, increase_ms: f64, ratio: f64) -> BufferbloatRationale {
+        let absolute_grade = BufferbloatGrade::from_limits(increase_ms, &self.increase_ms);
+        let ratio_grade = if increase_ms >= self.ratio_floor_ms {
+            Some(BufferbloatGrade::from_limits(ratio, &self.ratio))
+        } else {
+            None
+        };
+        
+        let (grade, limited_by) = match ratio_grade {
+            Some(ratio_grade) if ratio_grade > absolute_grade => {
+                let capped = ratio_grade.index()
+                    .min(absolute_grade.index() + self.max_ratio_penalty);
+                let grade = BufferbloatGrade::ALL[capped];
+                if grade > absolute_grade {
+                    (grade, GradeBasis::Ratio)
+                } else {
+                    (absolute_grade, GradeBasis::Absolute)
+                }
+            },
+            _ => (absolute_grade, GradeBasis::Absolute),
+        };
+        
+        BufferbloatRationale {
+            grade,
+            direction,
+            increase_ms,
+            ratio,
+            absolute_grade,
+            ratio_grade,
+            limited_by,
+        }
+    }
+}
+
+/// Which threshold decided the bufferbloat grade
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GradeBasis {
+    Absolute,
+    Ratio,
+}
+
+/// The reasoning behind a bufferbloat grade
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BufferbloatRationale {
+    pub grade: BufferbloatGrade,
+    /// Direction with the worst bufferbloat
+    pub direction: LoadDirection,
+    pub increase_ms: f64,
+    pub ratio: f64,
+    /// Grade from the absolute increase alone
+    pub absolute_grade: BufferbloatGrade,
+    /// Grade from the ratio alone, `None` when the increase is below the ratio floor
+    pub ratio_grade: Option<BufferbloatGrade>,
+    pub limited_by: GradeBasis,
+}
+
+impl BufferbloatRationale {
+    /// One-line explanation of the grade
+    pub fn explanation(&self) -> Message {
+        let direction = match self.direction {
+            LoadDirection::Download => "download",
+            LoadDirection::Upload => "upload",
+        };
+        let basis = match self.limited_by {
+            GradeBasis::Absolute => "absolute",
+            GradeBasis::Ratio => "ratio",
+        };
+        
+        Message::new(format!("bufferbloat.rationale.{}_{}", direction, basis), &[
+            ("grade", self.grade.as_str().to_string()),
+            ("increase_ms", format!("{:.0}", self.increase_ms)),
+            ("increase_pct", format!("{:.0}", self.ratio * 100.0)),
+        ])
+    }
 }
 
 impl BufferbloatGrade {
@@ -63,18 +373,21 @@ impl BufferbloatGrade {
             BufferbloatGrade::C => "C",
             BufferbloatGrade::D => "D",
             BufferbloatGrade::F => "F",
+            BufferbloatGrade::Ungraded => "N/A",
         }
     }
     
-    pub fn description(&self) -> &str {
-        match self {
-            BufferbloatGrade::APlus => "Excellent - No bufferbloat detected",
-            BufferbloatGrade::A => "Very Good - Minimal bufferbloat",
-            BufferbloatGrade::B => "Good - Some bufferbloat, usually acceptable",
-            BufferbloatGrade::C => "Fair - Noticeable bufferbloat, may cause lag",
-            BufferbloatGrade::D => "Poor - Significant bufferbloat, expect lag spikes",
-            BufferbloatGrade::F => "Terrible - Severe bufferbloat, gaming/video calls affected",
-        }
+    pub fn description(&self) -> Message {
+        let key = match self {
+            BufferbloatGrade::APlus => "a_plus",
+            BufferbloatGrade::A => "a",
+            BufferbloatGrade::B => "b",
+            BufferbloatGrade::C => "c",
+            BufferbloatGrade::D => "d",
+            BufferbloatGrade::F => "f",
+            BufferbloatGrade::Ungraded => "ungraded",
+        };
+        Message::new(format!("bufferbloat.grade.{}", key), &[])
     }
     
     pub fn emoji(&self) -> &str {
@@ -85,22 +398,48 @@ impl BufferbloatGrade {
             BufferbloatGrade::C => "⚠️",
             BufferbloatGrade::D => "🔴",
             BufferbloatGrade::F => "❌",
+            BufferbloatGrade::Ungraded => "❔",
         }
     }
 }
 
 pub struct LoadedLatencyTester {
-    idle_pings: Vec<f64>,
-    download_pings: Vec<f64>,
-    upload_pings: Vec<f64>,
+    idle_pings: Vec<LatencySample>,
+    download_pings: Vec<LatencySample>,
+    upload_pings: Vec<LatencySample>,
+    next_sequence: u32,
+    thresholds: BufferbloatThresholds,
+    download_responsiveness: Option<ResponsivenessResult>,
+    upload_responsiveness: Option<ResponsivenessResult>,
+    idle_packet_loss: Option<PacketLossResult>,
+    download_packet_loss: Option<PacketLossResult>,
+    upload_packet_loss: Option<PacketLossResult>,
+}
+
+impl Default for LoadedLatencyTester {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LoadedLatencyTester {
     pub fn new() -> Self {
+        Self::with_thresholds(BufferbloatThresholds::default())
+    }
+    
+    /// Create a tester that grades bufferbloat with custom thresholds
+    pub fn with_thresholds(thresholds: BufferbloatThresholds) -> Self {
         Self {
             idle_pings: Vec::new(),
             download_pings: Vec::new(),
             upload_pings: Vec::new(),
+            next_sequence: 0,
+            thresholds,
+            download_responsiveness: None,
+            upload_responsiveness: None,
+            idle_packet_loss: None,
+            download_packet_loss: None,
+            upload_packet_loss: None,
         }
     }
     
@@ -110,57 +449,106 @@ impl LoadedLatencyTester {
         log::info!("📊 Stage 1: Measuring idle latency ({} samples)...", samples);
         
         for i in 0..samples {
-            let latency = self.ping_once(target).await?;
-            self.idle_pings.push(latency);
+            let sample = self.ping_once(target).await;
+            self.idle_pings.push(sample.clone());
             
-            if i % 5 == 0 {
-                log::debug!("Idle ping {}/{}: {:.2}ms", i + 1, samples, latency);
+            match sample.rtt() {
+                Some(latency) if i.is_multiple_of(5) => {
+                    log::debug!("Idle ping {}/{}: {:.2}ms", i + 1, samples, latency);
+                }
+                Some(_) => {}
+                None => log::debug!("Idle ping {}/{} timed out", i + 1, samples),
             }
             
             // Small delay between pings (100ms)
             tokio::time::sleep(Duration::from_millis(100)).await;
         }
         
-        let avg = self.calculate_average(&self.idle_pings);
-        log::info!("✅ Idle latency baseline: {:.2}ms", avg);
+        let stats = self.calculate_stats(&self.idle_pings);
+        log::info!("✅ Idle latency baseline: {:.2}ms ({})", stats.avg, stats.quality.quality.as_str());
         
         Ok(())
     }
     
     /// Stage 2: Measure latency DURING download
     /// Call this repeatedly while download test is running
-    pub async fn measure_download_loaded_latency(&mut self, target: &str) -> Result<f64, Box<dyn std::error::Error>> {
-        let latency = self.ping_once(target).await?;
-        self.download_pings.push(latency);
-        Ok(latency)
+    /// Returns `None` if the probe timed out; it is still recorded
+    pub async fn measure_download_loaded_latency(&mut self, target: &str) -> Option<f64> {
+        let sample = self.ping_once(target).await;
+        self.download_pings.push(sample.clone());
+        sample.rtt()
     }
     
     /// Stage 3: Measure latency DURING upload
     /// Call this repeatedly while upload test is running
-    pub async fn measure_upload_loaded_latency(&mut self, target: &str) -> Result<f64, Box<dyn std::error::Error>> {
-        let latency = self.ping_once(target).await?;
-        self.upload_pings.push(latency);
-        Ok(latency)
+    /// Returns `None` if the probe timed out; it is still recorded
+    pub async fn measure_upload_loaded_latency(&mut self, target: &str) -> Option<f64> {
+        let sample = self.ping_once(target).await;
+        self.upload_pings.push(sample.clone());
+        sample.rtt()
+    }
+    
+    /// Run the IETF responsiveness test, which saturates the link itself
+    /// Returns the spec-conformant RPM for the given direction
+    pub async fn measure_responsiveness(&mut self, target: &str, direction: LoadDirection) -> Result<f64, Box<dyn std::error::Error>> {
+        let result = ResponsivenessTester::new().run(target, direction).await?;
+        let rpm = result.rpm;
+        
+        match direction {
+            LoadDirection::Download => self.download_responsiveness = Some(result),
+            LoadDirection::Upload => self.upload_responsiveness = Some(result),
+        }
+        
+        Ok(rpm)
+    }
+    
+    /// Attach packet loss measured during the idle stage
+    pub fn record_idle_packet_loss(&mut self, result: PacketLossResult) {
+        self.idle_packet_loss = Some(result);
+    }
+    
+    /// Attach packet loss measured while the link was loaded in `direction`
+    pub fn record_loaded_packet_loss(&mut self, direction: LoadDirection, result: PacketLossResult) {
+        match direction {
+            LoadDirection::Download => self.download_packet_loss = Some(result),
+            LoadDirection::Upload => self.upload_packet_loss = Some(result),
+        }
     }
     
     /// Perform a single ping measurement
-    /// Uses HTTP GET as a "ping" - measures round-trip time
-    async fn ping_once(&self, target: &str) -> Result<f64, Box<dyn std::error::Error>> {
+    /// Uses HTTP GET on a new connection as a "ping" - measures round-trip time.
+    /// Failed requests are still recorded, so the sequence stays gap-free: as
+    /// timeouts when the 5 s timeout elapsed, as errors otherwise.
+    async fn ping_once(&mut self, target: &str) -> LatencySample {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let sent_at = Utc::now();
         let start = Instant::now();
         
         // Simple HTTP GET to measure RTT
         // In production, this would be more sophisticated
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(5))
-            .build()?;
-        
         let url = format!("http://{}/api/health", target);
-        let _ = client.get(&url).send().await?;
+        let response = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+        {
+            Ok(client) => client.get(&url).send().await,
+            Err(e) => Err(e),
+        };
         
-        let duration = start.elapsed();
-        let latency_ms = duration.as_secs_f64() * 1000.0;
+        let (outcome, rtt_ms) = match response {
+            Ok(_) => (ProbeOutcome::Ok, Some(start.elapsed().as_secs_f64() * 1000.0)),
+            Err(e) if e.is_timeout() => (ProbeOutcome::Timeout, None),
+            Err(_) => (ProbeOutcome::Error, None),
+        };
         
-        Ok(latency_ms)
+        LatencySample {
+            sequence,
+            sent_at,
+            probe_type: ProbeType::Foreign,
+            outcome,
+            rtt_ms,
+        }
     }
     
     /// Calculate final results and bufferbloat grade
@@ -172,6 +560,11 @@ impl LoadedLatencyTester {
         let download_stats = self.calculate_stats(&self.download_pings);
         let upload_stats = self.calculate_stats(&self.upload_pings);
         
+        // Clock offset comes from the idle stage, where queuing is least
+        // likely to make the path asymmetric
+        let clock = self.idle_packet_loss.as_ref()
+            .and_then(|loss| OneWayDelayEstimator::estimate_clock(&loss.timestamps));
+        
         // Calculate bufferbloat
         let bufferbloat_download_ms = download_stats.avg - idle_stats.avg;
         let bufferbloat_upload_ms = upload_stats.avg - idle_stats.avg;
@@ -188,9 +581,17 @@ impl LoadedLatencyTester {
             0.0
         };
         
-        // Worst-case bufferbloat determines the grade
-        let worst_ratio = f64::max(bufferbloat_download_ratio, bufferbloat_upload_ratio);
-        let bufferbloat_grade = Self::calculate_bufferbloat_grade(worst_ratio);
+        // Worst-case bufferbloat determines the grade. A stage without
+        // enough samples averages 0ms and would read as no bufferbloat, so
+        // only directions whose stages (and idle) have samples are graded.
+        let graded = |stats: &LatencyStats| stats.quality.quality != StageQuality::InsufficientSamples;
+        let rationale = self.calculate_bufferbloat_grade(
+            (graded(&idle_stats) && graded(&download_stats))
+                .then_some((bufferbloat_download_ms, bufferbloat_download_ratio)),
+            (graded(&idle_stats) && graded(&upload_stats))
+                .then_some((bufferbloat_upload_ms, bufferbloat_upload_ratio)),
+        );
+        let bufferbloat_grade = rationale.as_ref().map_or(BufferbloatGrade::Ungraded, |r| r.grade);
         
         // Calculate RPM (Responsiveness Per Minute) - Apple's metric
         let idle_rpm = Self::calculate_rpm(idle_stats.avg);
@@ -205,6 +606,14 @@ impl LoadedLatencyTester {
             upload_stats.avg, upload_rpm, bufferbloat_upload_ratio * 100.0);
         log::info!("   Bufferbloat Grade: {} {}", 
             bufferbloat_grade.emoji(), bufferbloat_grade.as_str());
+        if let Some(rationale) = &rationale {
+            log::info!("   {}", rationale.explanation());
+        }
+        for (stage, stats) in [("idle", &idle_stats), ("download", &download_stats), ("upload", &upload_stats)] {
+            if let Some(caveat) = stats.quality.caveat(stage) {
+                log::warn!("   ⚠️ {}", caveat);
+            }
+        }
         
         LoadedLatencyResult {
             // Idle
@@ -213,6 +622,10 @@ impl LoadedLatencyTester {
             idle_avg_ms: idle_stats.avg,
             idle_median_ms: idle_stats.median,
             idle_samples: self.idle_pings.clone(),
+            idle_jitter: JitterCalculator::calculate(&self.idle_pings),
+            idle_quality: idle_stats.quality,
+            idle_packet_loss: self.idle_packet_loss.clone(),
+            idle_one_way: Self::one_way_delay(clock.as_ref(), &self.idle_packet_loss),
             
             // Download loaded
             download_min_ms: download_stats.min,
@@ -220,6 +633,10 @@ impl LoadedLatencyTester {
             download_avg_ms: download_stats.avg,
             download_median_ms: download_stats.median,
             download_samples: self.download_pings.clone(),
+            download_jitter: JitterCalculator::calculate(&self.download_pings),
+            download_quality: download_stats.quality,
+            download_packet_loss: self.download_packet_loss.clone(),
+            download_one_way: Self::one_way_delay(clock.as_ref(), &self.download_packet_loss),
             
             // Upload loaded
             upload_min_ms: upload_stats.min,
@@ -227,6 +644,12 @@ impl LoadedLatencyTester {
             upload_avg_ms: upload_stats.avg,
             upload_median_ms: upload_stats.median,
             upload_samples: self.upload_pings.clone(),
+            upload_jitter: JitterCalculator::calculate(&self.upload_pings),
+            upload_quality: upload_stats.quality,
+            upload_packet_loss: self.upload_packet_loss.clone(),
+            upload_one_way: Self::one_way_delay(clock.as_ref(), &self.upload_packet_loss),
+            
+            clock_model: clock,
             
             // Bufferbloat
             bufferbloat_download_ms,
@@ -234,28 +657,38 @@ impl LoadedLatencyTester {
             bufferbloat_download_ratio,
             bufferbloat_upload_ratio,
             bufferbloat_grade,
+            bufferbloat_rationale: rationale,
             
             // RPM
             idle_rpm,
             download_rpm,
             upload_rpm,
+            download_responsiveness: self.download_responsiveness.clone(),
+            upload_responsiveness: self.upload_responsiveness.clone(),
         }
     }
     
-    /// Calculate bufferbloat grade based on latency increase ratio
-    fn calculate_bufferbloat_grade(ratio: f64) -> BufferbloatGrade {
-        match ratio {
-            r if r < 0.5 => BufferbloatGrade::APlus,   // <50% increase
-            r if r < 1.0 => BufferbloatGrade::A,       // <100% increase
-            r if r < 2.0 => BufferbloatGrade::B,       // <200% increase
-            r if r < 4.0 => BufferbloatGrade::C,       // <400% increase
-            r if r < 9.0 => BufferbloatGrade::D,       // <900% increase
-            _ => BufferbloatGrade::F,                   // >900% increase
+    fn one_way_delay(clock: Option<&ClockModel>, loss: &Option<PacketLossResult>) -> Option<OneWayDelayResult> {
+        OneWayDelayEstimator::directional(clock?, &loss.as_ref()?.timestamps)
+    }
+    
+    /// Grade the gradable directions from (increase ms, ratio) and keep the
+    /// worse one; `None` when neither can be graded
+    fn calculate_bufferbloat_grade(&self, download: Option<(f64, f64)>, upload: Option<(f64, f64)>) -> Option<BufferbloatRationale> {
+        let download = download.map(|(ms, ratio)| self.thresholds.grade(LoadDirection::Download, ms, ratio));
+        let upload = upload.map(|(ms, ratio)| self.thresholds.grade(LoadDirection::Upload, ms, ratio));
+        
+        match (download, upload) {
+            (Some(download), Some(upload)) if upload.grade > download.grade => Some(upload),
+            (Some(download), _) => Some(download),
+            (None, upload) => upload,
         }
     }
     
     /// Calculate RPM (Roundtrips Per Minute) - Apple's metric
     /// Higher = better responsiveness
+    /// Simple approximation from the average RTT; see `measure_responsiveness`
+    /// for the spec-conformant value
     fn calculate_rpm(latency_ms: f64) -> f64 {
         if latency_ms > 0.0 {
             60_000.0 / latency_ms
@@ -264,42 +697,75 @@ impl LoadedLatencyTester {
         }
     }
     
-    /// Calculate statistics for a set of measurements
-    fn calculate_stats(&self, samples: &[f64]) -> LatencyStats {
-        if samples.is_empty() {
+    /// Calculate statistics over the successful, non-outlier probes of a stage
+    fn calculate_stats(&self, samples: &[LatencySample]) -> LatencyStats {
+        let rtts: Vec<f64> = samples.iter().filter_map(LatencySample::rtt).collect();
+        let errors = samples.iter().filter(|s| s.outcome == ProbeOutcome::Error).count();
+        let timeouts = samples.len() - rtts.len() - errors;
+        let inliers = Self::reject_outliers(&rtts);
+        let quality = StageQualityReport::new(samples.len(), timeouts, errors, rtts.len() - inliers.len());
+        
+        if inliers.is_empty() {
             return LatencyStats {
                 min: 0.0,
                 max: 0.0,
                 avg: 0.0,
                 median: 0.0,
+                quality,
             };
         }
         
-        let mut sorted = samples.to_vec();
+        let mut sorted = inliers.clone();
         sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
         
         let min = *sorted.first().unwrap();
         let max = *sorted.last().unwrap();
-        let avg = samples.iter().sum::<f64>() / samples.len() as f64;
-        let median = if sorted.len() % 2 == 0 {
-            (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
-        } else {
-            sorted[sorted.len() / 2]
-        };
+        let avg = inliers.iter().sum::<f64>() / inliers.len() as f64;
+        let median = Self::median(&sorted);
         
         LatencyStats {
             min,
             max,
             avg,
             median,
+            quality,
+        }
+    }
+    
+    /// Drop latency spikes using the modified z-score (Iglewicz & Hoaglin):
+    /// 0.6745 * (x - median) / MAD > 3.5. Only high values are rejected,
+    /// and sustained increases under load move the median rather than
+    /// being treated as outliers.
+    fn reject_outliers(rtts: &[f64]) -> Vec<f64> {
+        const MIN_SAMPLES: usize = 5;
+        const THRESHOLD: f64 = 3.5;
+        // Keeps a perfectly flat stage from flagging sub-millisecond wobble
+        const MIN_MAD_MS: f64 = 0.5;
+        
+        if rtts.len() < MIN_SAMPLES {
+            return rtts.to_vec();
         }
+        
+        let mut sorted = rtts.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let median = Self::median(&sorted);
+        
+        let mut deviations: Vec<f64> = rtts.iter().map(|rtt| (rtt - median).abs()).collect();
+        deviations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mad = Self::median(&deviations).max(MIN_MAD_MS);
+        
+        rtts.iter()
+            .copied()
+            .filter(|rtt| 0.6745 * (rtt - median) / mad <= THRESHOLD)
+            .collect()
     }
     
-    fn calculate_average(&self, samples: &[f64]) -> f64 {
-        if samples.is_empty() {
-            return 0.0;
+    fn median(sorted: &[f64]) -> f64 {
+        if sorted.len().is_multiple_of(2) {
+            (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
+        } else {
+            sorted[sorted.len() / 2]
         }
-        samples.iter().sum::<f64>() / samples.len() as f64
     }
 }
 
@@ -309,6 +775,7 @@ struct LatencyStats {
     max: f64,
     avg: f64,
     median: f64,
+    quality: StageQualityReport,
 }
 
 impl LoadedLatencyResult {
@@ -323,7 +790,8 @@ impl LoadedLatencyResult {
              ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
              {} Bufferbloat Grade: {} - {}\n\
              Download increase: +{:.0}% ({:.1}ms)\n\
-             Upload increase: +{:.0}% ({:.1}ms)",
+             Upload increase: +{:.0}% ({:.1}ms)\n\
+             Packet loss (idle/download/upload): {}/{}/{}",
             self.idle_avg_ms, self.idle_rpm, "⭐",
             self.download_avg_ms, self.download_rpm,
             if self.bufferbloat_download_ratio < 1.0 { "✅" } else { "⚠️" },
@@ -335,44 +803,117 @@ impl LoadedLatencyResult {
             self.bufferbloat_download_ratio * 100.0,
             self.bufferbloat_download_ms,
             self.bufferbloat_upload_ratio * 100.0,
-            self.bufferbloat_upload_ms
+            self.bufferbloat_upload_ms,
+            Self::loss_label(&self.idle_packet_loss),
+            Self::loss_label(&self.download_packet_loss),
+            Self::loss_label(&self.upload_packet_loss)
         )
     }
     
+    fn loss_label(loss: &Option<PacketLossResult>) -> String {
+        loss.as_ref()
+            .map(|loss| format!("{:.1}%", loss.loss_percentage))
+            .unwrap_or_else(|| "n/a".to_string())
+    }
+    
+    /// Throughput consistency across the saturated intervals of a
+    /// responsiveness run, `None` when it was not run or too short
+    pub fn throughput_consistency(&self, direction: LoadDirection) -> Option<ConsistencyScore> {
+        ConsistencyCalculator::from_samples(&self.responsiveness(direction)?.goodput_samples_mbps)
+    }
+    
+    /// Stalls and drops in the saturated part of a responsiveness run
+    pub fn throughput_stalls(&self, direction: LoadDirection) -> Option<StallAnalysis> {
+        let responsiveness = self.responsiveness(direction)?;
+        StallDetector::new().analyze(&responsiveness.throughput_series, responsiveness.started_at)
+    }
+    
+    fn responsiveness(&self, direction: LoadDirection) -> Option<&ResponsivenessResult> {
+        match direction {
+            LoadDirection::Download => self.download_responsiveness.as_ref(),
+            LoadDirection::Upload => self.upload_responsiveness.as_ref(),
+        }
+    }
+    
+    /// Worst packet loss percentage seen while the link was loaded
+    pub fn loaded_loss_percentage(&self) -> Option<f64> {
+        [&self.download_packet_loss, &self.upload_packet_loss]
+            .into_iter()
+            .flatten()
+            .map(|loss| loss.loss_percentage)
+            .reduce(f64::max)
+    }
+    
+    /// Caveats for stages whose statistics should not be taken at face value
+    pub fn caveats(&self) -> Vec<Message> {
+        [
+            self.idle_quality.caveat("idle"),
+            self.download_quality.caveat("download"),
+            self.upload_quality.caveat("upload"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+    
     /// Get fix recommendations based on bufferbloat grade
-    pub fn recommendations(&self) -> Vec<String> {
-        match self.bufferbloat_grade {
-            BufferbloatGrade::APlus | BufferbloatGrade::A => {
-                vec![
-                    "✅ Your connection has excellent quality!".to_string(),
-                    "No bufferbloat detected - latency stays low under load.".to_string(),
-                ]
-            },
-            BufferbloatGrade::B => {
-                vec![
-                    "✓ Your connection quality is good.".to_string(),
-                    "Minor bufferbloat detected, but should not affect most activities.".to_string(),
-                ]
-            },
-            BufferbloatGrade::C => {
-                vec![
-                    "⚠️ Moderate bufferbloat detected.".to_string(),
-                    "May cause lag in gaming or choppy video calls during uploads/downloads.".to_string(),
-                    "Consider enabling Smart Queue Management (SQM/QoS) on your router.".to_string(),
-                ]
-            },
-            BufferbloatGrade::D | BufferbloatGrade::F => {
-                vec![
-                    "🔴 Significant bufferbloat detected!".to_string(),
-                    "This will cause lag spikes, frozen video calls, and poor gaming experience.".to_string(),
-                    "".to_string(),
-                    "How to fix:".to_string(),
-                    "1. Enable Smart Queue Management (SQM/QoS) in your router settings".to_string(),
-                    "2. Set upload/download limits to 85-90% of your maximum speed".to_string(),
-                    "3. Consider upgrading to a router with better bufferbloat mitigation".to_string(),
-                    "4. Learn more at: https://www.bufferbloat.net".to_string(),
-                ]
-            },
+    pub fn recommendations(&self) -> Vec<Message> {
+        let keys: &[&str] = match self.bufferbloat_grade {
+            BufferbloatGrade::APlus | BufferbloatGrade::A => &["excellent", "no_bufferbloat"],
+            BufferbloatGrade::B => &["good", "minor_bufferbloat"],
+            BufferbloatGrade::C => &["moderate_bufferbloat", "moderate_impact", "consider_sqm"],
+            BufferbloatGrade::D | BufferbloatGrade::F => &[
+                "significant_bufferbloat",
+                "significant_impact",
+                "how_to_fix",
+                "fix_enable_sqm",
+                "fix_set_limits",
+                "fix_upgrade_router",
+                "fix_learn_more",
+            ],
+            BufferbloatGrade::Ungraded => &["ungraded"],
+        };
+        let mut recommendations: Vec<Message> = keys.iter()
+            .map(|key| Message::new(format!("bufferbloat.advice.{}", key), &[]))
+            .collect();
+        
+        if let Some(rationale) = &self.bufferbloat_rationale {
+            recommendations.insert(1, rationale.explanation());
+        }
+        
+        recommendations
+    }
+    
+    /// Every user-facing text about the latency test, for the result JSON
+    pub fn advice(&self) -> LatencyAdvice {
+        LatencyAdvice {
+            bufferbloat: self.bufferbloat_grade.description(),
+            recommendations: self.recommendations(),
+            packet_loss: self.idle_packet_loss.as_ref().map(PacketLossResult::recommendation),
+            caveats: self.caveats(),
+        }
+    }
+}
+
+/// Bufferbloat verdict, fixes, packet loss advice and stage caveats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyAdvice {
+    pub bufferbloat: Message,
+    pub recommendations: Vec<Message>,
+    /// Advice for the idle packet loss, when it was measured
+    pub packet_loss: Option<Message>,
+    pub caveats: Vec<Message>,
+}
+
+impl LatencyAdvice {
+    /// Re-render every text in `locale`
+    pub fn localize(&mut self, locale: Locale) {
+        self.bufferbloat.localize(locale);
+        for message in self.recommendations.iter_mut()
+            .chain(&mut self.packet_loss)
+            .chain(&mut self.caveats)
+        {
+            message.localize(locale);
         }
     }
 }
@@ -383,12 +924,130 @@ mod tests {
     
     #[test]
     fn test_bufferbloat_grading() {
-        assert_eq!(LoadedLatencyTester::calculate_bufferbloat_grade(0.3), BufferbloatGrade::APlus);
-        assert_eq!(LoadedLatencyTester::calculate_bufferbloat_grade(0.8), BufferbloatGrade::A);
-        assert_eq!(LoadedLatencyTester::calculate_bufferbloat_grade(1.5), BufferbloatGrade::B);
-        assert_eq!(LoadedLatencyTester::calculate_bufferbloat_grade(3.0), BufferbloatGrade::C);
-        assert_eq!(LoadedLatencyTester::calculate_bufferbloat_grade(5.0), BufferbloatGrade::D);
-        assert_eq!(LoadedLatencyTester::calculate_bufferbloat_grade(10.0), BufferbloatGrade::F);
+        let thresholds = BufferbloatThresholds::default();
+        let grade = |increase_ms: f64, ratio: f64| {
+            thresholds.grade(LoadDirection::Download, increase_ms, ratio).grade
+        };
+        
+        assert_eq!(grade(3.0, 0.2), BufferbloatGrade::APlus);
+        assert_eq!(grade(20.0, 0.5), BufferbloatGrade::A);
+        assert_eq!(grade(45.0, 0.8), BufferbloatGrade::B);
+        assert_eq!(grade(150.0, 3.0), BufferbloatGrade::C);
+        assert_eq!(grade(300.0, 5.0), BufferbloatGrade::D);
+        assert_eq!(grade(500.0, 10.0), BufferbloatGrade::F);
+    }
+    
+    #[test]
+    fn test_bufferbloat_grading_combines_absolute_and_ratio() {
+        let thresholds = BufferbloatThresholds::default();
+        
+        // 2ms -> 8ms: a big ratio, but a tiny absolute increase
+        let small_link = thresholds.grade(LoadDirection::Download, 6.0, 3.0);
+        assert_eq!(small_link.grade, BufferbloatGrade::A);
+        assert_eq!(small_link.limited_by, GradeBasis::Absolute);
+        assert_eq!(small_link.ratio_grade, None);
+        
+        // 60ms -> 110ms: a modest ratio, but 50ms of queueing
+        let slow_link = thresholds.grade(LoadDirection::Upload, 50.0, 0.83);
+        assert_eq!(slow_link.grade, BufferbloatGrade::B);
+        
+        // 5ms -> 36ms: the ratio lowers the grade by at most one step
+        let fast_link = thresholds.grade(LoadDirection::Upload, 31.0, 6.2);
+        assert_eq!(fast_link.absolute_grade, BufferbloatGrade::B);
+        assert_eq!(fast_link.grade, BufferbloatGrade::C);
+        assert_eq!(fast_link.limited_by, GradeBasis::Ratio);
+    }
+    
+    #[test]
+    fn test_stats_ignore_timeouts() {
+        let sample = |sequence, outcome, rtt_ms| LatencySample {
+            sequence,
+            sent_at: Utc::now(),
+            probe_type: ProbeType::Foreign,
+            outcome,
+            rtt_ms,
+        };
+        let mut tester = LoadedLatencyTester::new();
+        tester.idle_pings = vec![
+            sample(0, ProbeOutcome::Ok, Some(10.0)),
+            sample(1, ProbeOutcome::Timeout, None),
+            sample(2, ProbeOutcome::Ok, Some(20.0)),
+            sample(3, ProbeOutcome::Error, None),
+        ];
+        
+        let result = tester.calculate_results();
+        assert_eq!(result.idle_samples.len(), 4);
+        assert!((result.idle_avg_ms - 15.0).abs() < 0.001);
+        assert_eq!(result.idle_max_ms, 20.0);
+        assert_eq!((result.idle_quality.timeouts, result.idle_quality.errors), (1, 1));
+    }
+    
+    #[tokio::test]
+    async fn test_refused_ping_is_an_error_not_a_timeout() {
+        let mut tester = LoadedLatencyTester::new();
+        let started = Instant::now();
+        let sample = tester.ping_once("127.0.0.1:9").await;
+        assert_eq!(sample.outcome, ProbeOutcome::Error);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+    
+    #[test]
+    fn test_outliers_and_stage_quality() {
+        let sample = |sequence, rtt_ms: Option<f64>| LatencySample {
+            sequence,
+            sent_at: Utc::now(),
+            probe_type: ProbeType::Foreign,
+            outcome: if rtt_ms.is_some() { ProbeOutcome::Ok } else { ProbeOutcome::Timeout },
+            rtt_ms,
+        };
+        let mut tester = LoadedLatencyTester::new();
+        tester.idle_pings = (0..10)
+            .map(|i| sample(i, Some(if i == 4 { 900.0 } else { 10.0 + (i % 3) as f64 })))
+            .collect();
+        tester.download_pings = (0..10)
+            .map(|i| sample(i, if i < 3 { None } else { Some(40.0) }))
+            .collect();
+        tester.upload_pings = vec![sample(0, Some(30.0)), sample(1, None)];
+        
+        let result = tester.calculate_results();
+        // The 900ms spike is dropped instead of dragging the baseline up
+        assert_eq!(result.idle_quality.outliers, 1);
+        assert_eq!(result.idle_quality.quality, StageQuality::Ok);
+        assert!(result.idle_max_ms < 20.0);
+        
+        assert_eq!(result.download_quality.timeouts, 3);
+        assert_eq!(result.download_quality.quality, StageQuality::Noisy);
+        assert_eq!(result.upload_quality.quality, StageQuality::InsufficientSamples);
+        assert_eq!(result.caveats().len(), 2);
+    }
+    
+    #[test]
+    fn test_stages_without_samples_are_not_graded() {
+        let sample = |sequence, rtt_ms: Option<f64>| LatencySample {
+            sequence,
+            sent_at: Utc::now(),
+            probe_type: ProbeType::Foreign,
+            outcome: if rtt_ms.is_some() { ProbeOutcome::Ok } else { ProbeOutcome::Timeout },
+            rtt_ms,
+        };
+        let mut tester = LoadedLatencyTester::new();
+        tester.idle_pings = (0..10).map(|i| sample(i, Some(20.0))).collect();
+        tester.download_pings = (0..10).map(|i| sample(i, None)).collect();
+        
+        // Every loaded probe timed out: no grade rather than A+ from a 0ms average
+        let result = tester.calculate_results();
+        assert_eq!(result.download_quality.quality, StageQuality::InsufficientSamples);
+        assert!(result.bufferbloat_download_ms < 0.0);
+        assert_eq!(result.bufferbloat_grade, BufferbloatGrade::Ungraded);
+        assert!(result.bufferbloat_rationale.is_none());
+        assert_eq!(result.recommendations()[0].key, "bufferbloat.advice.ungraded");
+        assert_eq!(serde_json::to_value(result.bufferbloat_grade).unwrap(), "N/A");
+        
+        // A measured upload is still graded on its own
+        tester.upload_pings = (0..10).map(|i| sample(i, Some(320.0))).collect();
+        let result = tester.calculate_results();
+        assert_eq!(result.bufferbloat_grade, BufferbloatGrade::F);
+        assert_eq!(result.bufferbloat_rationale.unwrap().direction, LoadDirection::Upload);
     }
     
     #[test]
@@ -7,6 +7,7 @@ use tokio::time::sleep;
 
 use crate::config::AppConfig;
 use crate::models::{TestProgress, TestResult};
+use crate::services::jitter::JitterCalculator;
 
 pub struct MeasurementEngine {
     config: AppConfig,
@@ -122,7 +123,7 @@ impl MeasurementEngine {
         
         // Calculate statistics
         let avg_latency = latencies.iter().sum::<f64>() / latencies.len() as f64;
-        let jitter = self.calculate_jitter(&latencies);
+        let jitter = JitterCalculator::rfc3550(&latencies);
         
         Ok((avg_latency, jitter))
     }
@@ -194,9 +195,7 @@ impl MeasurementEngine {
         let mut measurements = Vec::new();
         
         // In a real implementation, we'd receive data from the client
-        // For now, simulate upload by generating data server-side
-        let test_chunk = vec![b'Y'; CHUNK_SIZE];
-        
+        // For now, simulate upload by counting bytes server-side
         while start.elapsed().as_millis() < TEST_DURATION_MS as u128 {
             let chunk_start = Instant::now();
             
@@ -236,19 +235,6 @@ impl MeasurementEngine {
         Ok(avg_speed)
     }
     
-    fn calculate_jitter(&self, latencies: &[f64]) -> f64 {
-        if latencies.len() < 2 {
-            return 0.0;
-        }
-        
-        let mut differences = Vec::new();
-        for i in 1..latencies.len() {
-            differences.push((latencies[i] - latencies[i - 1]).abs());
-        }
-        
-        differences.iter().sum::<f64>() / differences.len() as f64
-    }
-    
     async fn send_progress(
         &self,
         session: &mut Session,
@@ -0,0 +1,237 @@
+//! Mock Chat Completion Server
+//!
+//! A minimal OpenAI-compatible `POST /v1/chat/completions` endpoint for
+//! tests and offline development. It serves scripted replies in order, then
+//! a valid insights object for every further request, and records each
+//! request so tests can check what was sent.
+//!
+//! Built for tests and with the `mock-llm` feature. Run it standalone with
+//! `cargo run --features mock-llm --example mock_completion_server` and point
+//! `OPENAI_BASE_URL` at it.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Served once the scripted replies run out
+pub const MOCK_INSIGHTS: &str = r#"{
+  "summary": "Fast downloads, but latency rises sharply while uploading.",
+  "detailed_analysis": "Idle latency is low. Under upload load the router's buffers fill and latency climbs, which is bufferbloat.",
+  "recommendations": [
+    {
+      "priority": "High",
+      "title": "Enable Smart Queue Management (SQM)",
+      "description": "Turn on SQM or QoS in the router and cap upload at about 90% of the measured speed.",
+      "expected_improvement": "Latency under upload load close to idle latency",
+      "difficulty": "Medium"
+    }
+  ],
+  "predictions": ["Video calls may stutter while someone uploads large files"],
+  "simple_explanation": "Your connection is a wide road with a narrow on-ramp: traffic queues up whenever you send a lot at once."
+}"#;
+
+/// Largest request head or body accepted
+const MAX_REQUEST_BYTES: usize = 1 << 20;
+
+/// One scripted response
+#[derive(Debug, Clone)]
+pub struct MockReply {
+    pub status: u16,
+    pub content: String,
+    /// "stop", or "length" for a reply cut off at the token limit
+    pub finish_reason: String,
+}
+
+impl MockReply {
+    pub fn content(content: impl Into<String>) -> Self {
+        Self { status: 200, content: content.into(), finish_reason: "stop".to_string() }
+    }
+
+    /// A reply cut off at `max_tokens`
+    pub fn truncated(content: impl Into<String>) -> Self {
+        Self { finish_reason: "length".to_string(), ..Self::content(content) }
+    }
+
+    /// An API error with this HTTP status
+    pub fn error(status: u16) -> Self {
+        Self { status, content: String::new(), finish_reason: String::new() }
+    }
+}
+
+/// A request as the server received it
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    /// Lowercase header names
+    pub headers: HashMap<String, String>,
+    pub body: serde_json::Value,
+}
+
+/// Requests received so far, shared with the running server
+#[derive(Debug, Clone, Default)]
+pub struct RequestLog {
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl RequestLog {
+    pub fn all(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub struct MockCompletionServer {
+    listener: TcpListener,
+    replies: Arc<Mutex<VecDeque<MockReply>>>,
+    log: RequestLog,
+}
+
+impl MockCompletionServer {
+    pub async fn bind(addr: &str, replies: Vec<MockReply>) -> std::io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            replies: Arc::new(Mutex::new(replies.into())),
+            log: RequestLog::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Base URL to configure clients with, e.g. "http://127.0.0.1:41234/v1"
+    pub fn base_url(&self) -> std::io::Result<String> {
+        Ok(format!("http://{}/v1", self.local_addr()?))
+    }
+
+    pub fn requests(&self) -> RequestLog {
+        self.log.clone()
+    }
+
+    /// Serve connections until the task is dropped
+    pub async fn run(self) -> std::io::Result<()> {
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            let replies = self.replies.clone();
+            let log = self.log.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Self::serve(stream, replies, log).await {
+                    log::debug!("Mock completion connection closed: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Answer requests on one keep-alive connection
+    async fn serve(
+        mut stream: TcpStream,
+        replies: Arc<Mutex<VecDeque<MockReply>>>,
+        log: RequestLog,
+    ) -> std::io::Result<()> {
+        let mut buffer = Vec::new();
+        while let Some(request) = Self::read_request(&mut stream, &mut buffer).await? {
+            let (status, body) = if request.method == "POST" && request.path.ends_with("/chat/completions") {
+                let reply = replies.lock().unwrap().pop_front().unwrap_or_else(|| MockReply::content(MOCK_INSIGHTS));
+                (reply.status, Self::completion_body(&request.body, &reply))
+            } else {
+                (404, Self::error_body("Unknown endpoint"))
+            };
+            log.requests.lock().unwrap().push(request);
+
+            let response = format!(
+                "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                if status == 200 { "OK" } else { "Error" },
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    /// Next request on the connection; `None` once the client hangs up
+    async fn read_request(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> std::io::Result<Option<RecordedRequest>> {
+        let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+
+        let head_end = loop {
+            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos;
+            }
+            if buffer.len() > MAX_REQUEST_BYTES {
+                return Err(invalid("request head too large"));
+            }
+            let mut chunk = [0u8; 8192];
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        };
+
+        let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().ok_or_else(|| invalid("missing method"))?.to_string();
+        let path = request_line.next().ok_or_else(|| invalid("missing path"))?.to_string();
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+
+        let length: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+        if length > MAX_REQUEST_BYTES {
+            return Err(invalid("request body too large"));
+        }
+        let body_start = head_end + 4;
+        while buffer.len() < body_start + length {
+            let mut chunk = [0u8; 8192];
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Err(invalid("connection closed mid-body"));
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+        let body = serde_json::from_slice(&buffer[body_start..body_start + length]).unwrap_or(serde_json::Value::Null);
+        buffer.drain(..body_start + length);
+
+        Ok(Some(RecordedRequest { method, path, headers, body }))
+    }
+
+    fn completion_body(request: &serde_json::Value, reply: &MockReply) -> String {
+        if reply.status != 200 {
+            return Self::error_body(&format!("Mock error {}", reply.status));
+        }
+        serde_json::json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion",
+            "created": 0,
+            "model": request.get("model").cloned().unwrap_or_else(|| "mock".into()),
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": reply.content },
+                "finish_reason": reply.finish_reason,
+            }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 },
+        })
+        .to_string()
+    }
+
+    fn error_body(message: &str) -> String {
+        serde_json::json!({
+            "error": { "message": message, "type": "server_error", "param": null, "code": null }
+        })
+        .to_string()
+    }
+}
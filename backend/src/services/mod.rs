@@ -2,8 +2,23 @@ pub mod database;
 pub mod measurement;
 pub mod real_measurement; // Real data transfer implementation
 pub mod loaded_latency;
+pub mod jitter;
+pub mod one_way_delay;
+pub mod responsiveness;
 pub mod aim_scoring;
 pub mod ai_insights;
 pub mod binary_protocol;
 pub mod packet_loss;
+pub mod voip_quality;
+pub mod udp_echo;
 pub mod consistency;
+pub mod stall_detection;
+pub mod scoring_profile;
+pub mod usage_priorities;
+pub mod scoring_versions;
+pub mod household_capacity;
+pub mod plan_compliance;
+pub mod i18n;
+pub mod rule_insights;
+#[cfg(any(test, feature = "mock-llm"))]
+pub mod mock_completion;
@@ -0,0 +1,191 @@
+//! One-way delay estimation
+//!
+//! UDP echo probes carry four NTP-style timestamps: client send (t1),
+//! server receive (t2), server send (t3) and client receive (t4). Client and
+//! server clocks disagree, so the offset is estimated from the fastest probe
+//! in each time window (least queuing, so closest to a symmetric path) and a
+//! linear fit over those windows gives offset and drift. With the clock model
+//! fixed, queuing on the uplink and downlink can be told apart.
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::jitter::JitterCalculator;
+
+/// Timestamps of one echoed probe, in microseconds since the Unix epoch
+/// (t1/t4 on the client clock, t2/t3 on the server clock)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimestampedProbe {
+    pub sequence: u64,
+    pub client_send_us: i64,
+    pub server_recv_us: i64,
+    pub server_send_us: i64,
+    pub client_recv_us: i64,
+}
+
+impl TimestampedProbe {
+    /// Round-trip time excluding server processing (NTP delta)
+    pub fn rtt_us(&self) -> i64 {
+        (self.client_recv_us - self.client_send_us) - (self.server_send_us - self.server_recv_us)
+    }
+
+    /// Server clock minus client clock, assuming a symmetric path (NTP theta)
+    pub fn offset_us(&self) -> f64 {
+        ((self.server_recv_us - self.client_send_us) + (self.server_send_us - self.client_recv_us)) as f64 / 2.0
+    }
+}
+
+/// Server-minus-client clock offset as a linear function of client time
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct ClockModel {
+    /// Offset at `reference_us`
+    pub offset_ms: f64,
+    /// Parts per million the server clock runs fast relative to the client
+    pub drift_ppm: f64,
+    /// Client time the model is anchored to
+    pub reference_us: i64,
+    /// Windows the fit was computed from
+    pub windows: usize,
+}
+
+impl ClockModel {
+    pub fn offset_us_at(&self, client_us: i64) -> f64 {
+        self.offset_ms * 1000.0 + (client_us - self.reference_us) as f64 * self.drift_ppm / 1e6
+    }
+}
+
+/// Delay and jitter in one direction
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DirectionalDelay {
+    pub avg_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    /// RFC 3550 interarrival jitter of the one-way delays
+    pub jitter_ms: f64,
+}
+
+/// Upstream (client to server) vs downstream one-way delay for a stage
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OneWayDelayResult {
+    pub upstream: DirectionalDelay,
+    pub downstream: DirectionalDelay,
+    pub samples: usize,
+}
+
+pub struct OneWayDelayEstimator;
+
+impl OneWayDelayEstimator {
+    /// Window used to pick the least-queued probe for the clock fit
+    const WINDOW_US: i64 = 500_000;
+
+    /// Fit the clock model from reference probes, ideally from an idle link
+    pub fn estimate_clock(probes: &[TimestampedProbe]) -> Option<ClockModel> {
+        let first = probes.iter().map(|p| p.client_send_us).min()?;
+
+        // Fastest probe per window
+        let mut best: Vec<TimestampedProbe> = Vec::new();
+        for probe in probes {
+            let window = (probe.client_send_us - first) / Self::WINDOW_US;
+            match best.iter_mut().find(|b| (b.client_send_us - first) / Self::WINDOW_US == window) {
+                Some(b) if probe.rtt_us() < b.rtt_us() => *b = *probe,
+                Some(_) => {}
+                None => best.push(*probe),
+            }
+        }
+
+        // Least-squares offset = a + b * (t - first)
+        let n = best.len() as f64;
+        let xs: Vec<f64> = best.iter().map(|p| (p.client_send_us - first) as f64).collect();
+        let ys: Vec<f64> = best.iter().map(|p| p.offset_us()).collect();
+        let mean_x = xs.iter().sum::<f64>() / n;
+        let mean_y = ys.iter().sum::<f64>() / n;
+        let var_x: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+        let slope = if best.len() > 1 && var_x > 0.0 {
+            xs.iter().zip(&ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>() / var_x
+        } else {
+            0.0
+        };
+        let intercept = mean_y - slope * mean_x;
+
+        Some(ClockModel {
+            offset_ms: intercept / 1000.0,
+            drift_ppm: slope * 1e6,
+            reference_us: first,
+            windows: best.len(),
+        })
+    }
+
+    /// Split each probe into corrected upstream and downstream delays
+    pub fn directional(clock: &ClockModel, probes: &[TimestampedProbe]) -> Option<OneWayDelayResult> {
+        if probes.is_empty() {
+            return None;
+        }
+
+        let mut sorted = probes.to_vec();
+        sorted.sort_by_key(|p| p.sequence);
+
+        let mut upstream = Vec::with_capacity(sorted.len());
+        let mut downstream = Vec::with_capacity(sorted.len());
+        for probe in &sorted {
+            let offset = clock.offset_us_at(probe.client_send_us);
+            upstream.push(((probe.server_recv_us - probe.client_send_us) as f64 - offset) / 1000.0);
+            downstream.push(((probe.client_recv_us - probe.server_send_us) as f64 + offset) / 1000.0);
+        }
+
+        Some(OneWayDelayResult {
+            upstream: Self::summarize(&upstream),
+            downstream: Self::summarize(&downstream),
+            samples: sorted.len(),
+        })
+    }
+
+    fn summarize(delays: &[f64]) -> DirectionalDelay {
+        DirectionalDelay {
+            avg_ms: delays.iter().sum::<f64>() / delays.len() as f64,
+            min_ms: delays.iter().copied().fold(f64::INFINITY, f64::min),
+            max_ms: delays.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            jitter_ms: JitterCalculator::rfc3550(delays),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Probe every 100ms with the given one-way delays, a server clock 250ms
+    /// ahead that gains 100 ppm, and 50us of server processing
+    fn probe(sequence: u64, up_us: i64, down_us: i64) -> TimestampedProbe {
+        let t1 = 1_700_000_000_000_000 + sequence as i64 * 100_000;
+        let offset = |t: i64| 250_000 + (t - 1_700_000_000_000_000) / 10_000;
+        let t2 = t1 + up_us + offset(t1 + up_us);
+        TimestampedProbe {
+            sequence,
+            client_send_us: t1,
+            server_recv_us: t2,
+            server_send_us: t2 + 50,
+            client_recv_us: t1 + up_us + 50 + down_us,
+        }
+    }
+
+    #[test]
+    fn test_clock_offset_and_drift() {
+        let idle: Vec<_> = (0..50).map(|i| probe(i, 10_000, 10_000)).collect();
+        let clock = OneWayDelayEstimator::estimate_clock(&idle).unwrap();
+
+        assert!((clock.offset_ms - 250.0).abs() < 0.1);
+        assert!((clock.drift_ppm - 100.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_upload_queuing_shows_upstream() {
+        let idle: Vec<_> = (0..50).map(|i| probe(i, 10_000, 10_000)).collect();
+        let clock = OneWayDelayEstimator::estimate_clock(&idle).unwrap();
+
+        // Uplink queue of 80ms, downlink untouched
+        let loaded: Vec<_> = (50..100).map(|i| probe(i, 90_000, 10_000)).collect();
+        let result = OneWayDelayEstimator::directional(&clock, &loaded).unwrap();
+
+        assert!((result.upstream.avg_ms - 90.0).abs() < 1.0);
+        assert!((result.downstream.avg_ms - 10.0).abs() < 1.0);
+    }
+}
@@ -1,21 +1,60 @@
-/// Packet Loss Detection using UDP
-/// 
-/// Measures packet loss by sending numbered UDP packets and tracking which are received.
-/// This is more accurate than TCP which masks packet loss with retransmissions.
+//! Packet Loss Detection using UDP
+//! 
+//! Measures packet loss by sending numbered UDP packets and tracking which are received.
+//! This is more accurate than TCP which masks packet loss with retransmissions.
 
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::time::{Duration, Instant};
 use tokio::net::UdpSocket;
-use tokio::time::sleep;
+use tokio::sync::{oneshot, watch};
+
+use crate::services::i18n::Message;
+use crate::services::one_way_delay::TimestampedProbe;
+use crate::services::udp_echo::{EchoProbe, EchoReply};
+
+/// Upper bound on probing alongside a load stage
+pub const MAX_LOAD_PROBE_DURATION: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PacketLossResult {
     pub sent_packets: u64,
+    /// Distinct packets echoed back, including late ones
     pub received_packets: u64,
+    /// Packets never echoed back
     pub lost_packets: u64,
     pub loss_percentage: f64,
     pub grade: PacketLossGrade,
     pub test_duration_ms: u64,
+    
+    /// Echoes that arrived after the late threshold (too late for real-time use)
+    #[serde(default)]
+    pub late_packets: u64,
+    /// Extra copies of already received echoes
+    #[serde(default)]
+    pub duplicate_packets: u64,
+    /// Echoes that arrived after a higher sequence number
+    #[serde(default)]
+    pub reordered_packets: u64,
+    #[serde(default)]
+    pub bursts: BurstLossStats,
+    /// Client/server timestamps of received probes, for one-way delay
+    #[serde(skip)]
+    pub timestamps: Vec<TimestampedProbe>,
+}
+
+/// Loss burst analysis with a two-state Gilbert-Elliott model
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BurstLossStats {
+    /// Runs of consecutive lost packets
+    pub burst_count: u64,
+    pub max_burst_length: u64,
+    pub mean_burst_length: f64,
+    /// P(loss | previous packet received)
+    pub gilbert_p: f64,
+    /// P(received | previous packet lost)
+    pub gilbert_r: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,9 +65,27 @@ pub enum PacketLossGrade {
     Poor,      // >=3.0%
 }
 
+/// An echo matched back to its probe
+#[derive(Debug, Clone, Copy)]
+struct Arrival {
+    sequence: u64,
+    rtt: Duration,
+    timestamps: Option<TimestampedProbe>,
+}
+
 pub struct PacketLossDetector {
     packets_per_second: u32,
     test_duration_seconds: u32,
+    /// Echoes slower than this count as late
+    late_threshold: Duration,
+    /// How long to keep listening after the last probe is sent
+    drain_timeout: Duration,
+}
+
+impl Default for PacketLossDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PacketLossDetector {
@@ -36,11 +93,54 @@ impl PacketLossDetector {
         Self {
             packets_per_second: 50,  // Industry standard
             test_duration_seconds: 10,
+            late_threshold: Duration::from_millis(400),
+            drain_timeout: Duration::from_secs(2),
         }
     }
 
-    /// Measure packet loss using UDP echo
-    pub async fn measure_packet_loss(&self, target: &str) -> Result<PacketLossResult, String> {
+    /// Measure packet loss against a UDP echo responder
+    /// `test_id` and its `secret` come from a start endpoint
+    pub async fn measure_packet_loss(&self, target: &str, test_id: &str, secret: &str) -> Result<PacketLossResult, String> {
+        let duration = Duration::from_secs(self.test_duration_seconds as u64);
+        self.probe(target, test_id, secret, duration, tokio::time::sleep(duration)).await
+    }
+    
+    /// Measure packet loss while `load` runs, e.g. a download or upload stage
+    /// Probing stops when the load completes (or after `MAX_LOAD_PROBE_DURATION`)
+    ///
+    /// Probes go from this host to `target`, so the result covers this
+    /// host's path; run against the server's own echo responder it would
+    /// only measure loopback, which is why the enhanced test does not.
+    pub async fn measure_packet_loss_during<F: Future>(
+        &self,
+        target: &str,
+        test_id: &str,
+        secret: &str,
+        load: F,
+    ) -> (Result<PacketLossResult, String>, F::Output) {
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        let load = async {
+            let output = load.await;
+            let _ = stop_tx.send(());
+            output
+        };
+        let probe = self.probe(target, test_id, secret, MAX_LOAD_PROBE_DURATION, async {
+            let _ = stop_rx.await;
+        });
+        
+        tokio::join!(probe, load)
+    }
+    
+    /// Send probes on a fixed schedule until `stop` resolves or `max_duration`
+    /// elapses, while a separate receive loop matches echoes to sequence numbers
+    async fn probe(
+        &self,
+        target: &str,
+        test_id: &str,
+        secret: &str,
+        max_duration: Duration,
+        stop: impl Future<Output = ()>,
+    ) -> Result<PacketLossResult, String> {
         let start_time = Instant::now();
         
         // Create UDP socket
@@ -52,57 +152,196 @@ impl PacketLossDetector {
             .await
             .map_err(|e| format!("Failed to connect to target: {}", e))?;
 
-        let total_packets = self.packets_per_second * self.test_duration_seconds;
-        let mut sent_packets = 0u64;
-        let mut received_packets = 0u64;
-        
+        let max_packets = self.packets_per_second as u64 * max_duration.as_secs().max(1);
         let interval = Duration::from_millis(1000 / self.packets_per_second as u64);
         
-        // Send packets
-        for sequence in 0..total_packets {
-            // Create packet with sequence number
-            let packet = format!("SPEEDTEST_PACKET:{}", sequence);
-            
-            match socket.send(packet.as_bytes()).await {
-                Ok(_) => {
-                    sent_packets += 1;
-                    
-                    // Try to receive echo (with timeout)
-                    let mut buf = [0u8; 1024];
-                    match tokio::time::timeout(
-                        Duration::from_millis(100),
-                        socket.recv(&mut buf)
-                    ).await {
-                        Ok(Ok(_)) => received_packets += 1,
-                        _ => {} // Packet lost or timeout
+        // Send times are fixed by the schedule, so the receiver can compute
+        // RTTs without sharing state with the sender
+        let send_time = |sequence: u64| start_time + interval * sequence as u32;
+        let (done_tx, mut done_rx) = watch::channel(false);
+        
+        let sender = async {
+            let mut sent = Vec::new();
+            tokio::pin!(stop);
+            for sequence in 0..max_packets {
+                tokio::select! {
+                    _ = &mut stop => break,
+                    _ = tokio::time::sleep_until(send_time(sequence).into()) => {}
+                }
+                let packet = EchoProbe {
+                    test_id: test_id.to_string(),
+                    secret: secret.to_string(),
+                    sequence,
+                    client_send_us: Some(Utc::now().timestamp_micros()),
+                }.encode();
+                match socket.send(packet.as_bytes()).await {
+                    Ok(_) => sent.push(true),
+                    Err(e) => {
+                        log::warn!("Failed to send packet {}: {}", sequence, e);
+                        sent.push(false);
                     }
                 }
-                Err(e) => {
-                    log::warn!("Failed to send packet {}: {}", sequence, e);
+            }
+            let _ = done_tx.send(true);
+            sent
+        };
+        
+        let receiver = async {
+            let mut arrivals = Vec::new();
+            let mut buf = [0u8; 1024];
+            // Pushed out to the drain deadline once the sender finishes
+            let mut deadline = start_time + max_duration + self.drain_timeout;
+            
+            loop {
+                tokio::select! {
+                    received = socket.recv(&mut buf) => {
+                        // A transient error, e.g. ECONNREFUSED from an ICMP
+                        // reply, must not end the receiver and turn every later
+                        // echo into a loss; only the deadline does
+                        let len = match received {
+                            Ok(len) => len,
+                            Err(e) => {
+                                log::debug!("UDP receive error: {}", e);
+                                continue;
+                            }
+                        };
+                        let arrived_at = Instant::now();
+                        let client_recv_us = Utc::now().timestamp_micros();
+                        let Some(reply) = EchoReply::decode(&buf[..len]) else {
+                            continue;
+                        };
+                        if reply.test_id != test_id || reply.sequence >= max_packets {
+                            continue;
+                        }
+                        arrivals.push(Arrival {
+                            sequence: reply.sequence,
+                            rtt: arrived_at.saturating_duration_since(send_time(reply.sequence)),
+                            timestamps: reply.client_send_us.map(|client_send_us| TimestampedProbe {
+                                sequence: reply.sequence,
+                                client_send_us,
+                                server_recv_us: reply.server_recv_us,
+                                server_send_us: reply.server_send_us,
+                                client_recv_us,
+                            }),
+                        });
+                    }
+                    Ok(()) = done_rx.changed() => {
+                        deadline = Instant::now() + self.drain_timeout;
+                    }
+                    _ = tokio::time::sleep_until(deadline.into()) => break,
                 }
             }
+            arrivals
+        };
+        
+        let (sent, arrivals) = tokio::join!(sender, receiver);
+        
+        let mut result = self.analyze(&sent, &arrivals);
+        result.test_duration_ms = start_time.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+    
+    /// Classify arrivals against the probes that were actually sent
+    fn analyze(&self, sent: &[bool], arrivals: &[Arrival]) -> PacketLossResult {
+        let mut received = vec![false; sent.len()];
+        let mut late_packets = 0u64;
+        let mut duplicate_packets = 0u64;
+        let mut reordered_packets = 0u64;
+        let mut highest_sequence: Option<u64> = None;
+        let mut timestamps = Vec::new();
+        
+        for arrival in arrivals {
+            let index = arrival.sequence as usize;
+            if index >= received.len() {
+                continue;
+            }
+            if received[index] {
+                duplicate_packets += 1;
+                continue;
+            }
+            received[index] = true;
+            timestamps.extend(arrival.timestamps);
             
-            sleep(interval).await;
+            if arrival.rtt > self.late_threshold {
+                late_packets += 1;
+            }
+            match highest_sequence {
+                Some(highest) if arrival.sequence < highest => reordered_packets += 1,
+                _ => highest_sequence = Some(arrival.sequence),
+            }
         }
         
-        let elapsed = start_time.elapsed().as_millis() as u64;
-        let lost_packets = sent_packets - received_packets;
+        // Loss pattern over sent probes only (true = lost)
+        let pattern: Vec<bool> = sent.iter()
+            .zip(&received)
+            .filter(|(sent, _)| **sent)
+            .map(|(_, received)| !received)
+            .collect();
+        
+        let sent_packets = pattern.len() as u64;
+        let lost_packets = pattern.iter().filter(|lost| **lost).count() as u64;
+        let received_packets = sent_packets - lost_packets;
         let loss_percentage = if sent_packets > 0 {
             (lost_packets as f64 / sent_packets as f64) * 100.0
         } else {
             0.0
         };
         
-        let grade = Self::calculate_grade(loss_percentage);
-        
-        Ok(PacketLossResult {
+        PacketLossResult {
             sent_packets,
             received_packets,
             lost_packets,
             loss_percentage,
-            grade,
-            test_duration_ms: elapsed,
-        })
+            grade: Self::calculate_grade(loss_percentage),
+            test_duration_ms: 0,
+            late_packets,
+            duplicate_packets,
+            reordered_packets,
+            bursts: Self::analyze_bursts(&pattern),
+            timestamps,
+        }
+    }
+    
+    /// Burst lengths and Gilbert-Elliott transition probabilities from a loss pattern
+    fn analyze_bursts(lost: &[bool]) -> BurstLossStats {
+        let mut bursts = Vec::new();
+        let mut run = 0u64;
+        for &is_lost in lost {
+            if is_lost {
+                run += 1;
+            } else if run > 0 {
+                bursts.push(run);
+                run = 0;
+            }
+        }
+        if run > 0 {
+            bursts.push(run);
+        }
+        
+        // Transitions between consecutive packets
+        let (mut good, mut good_to_bad, mut bad, mut bad_to_good) = (0u64, 0u64, 0u64, 0u64);
+        for pair in lost.windows(2) {
+            if pair[0] {
+                bad += 1;
+                if !pair[1] {
+                    bad_to_good += 1;
+                }
+            } else {
+                good += 1;
+                if pair[1] {
+                    good_to_bad += 1;
+                }
+            }
+        }
+        let ratio = |n: u64, d: u64| if d > 0 { n as f64 / d as f64 } else { 0.0 };
+        
+        BurstLossStats {
+            burst_count: bursts.len() as u64,
+            max_burst_length: bursts.iter().copied().max().unwrap_or(0),
+            mean_burst_length: ratio(bursts.iter().sum(), bursts.len() as u64),
+            gilbert_p: ratio(good_to_bad, good),
+            gilbert_r: ratio(bad_to_good, bad),
+        }
     }
 
     fn calculate_grade(loss_percentage: f64) -> PacketLossGrade {
@@ -132,38 +371,33 @@ impl PacketLossResult {
              Received: {} packets\n\
              Lost:     {} packets\n\
              Loss:     {:.2}% {}\n\
+             Late:     {} | Duplicated: {} | Reordered: {}\n\
+             Bursts:   {} (longest {}, mean {:.1})\n\
              Grade:    {:?}",
             self.sent_packets,
             self.received_packets,
             self.lost_packets,
             self.loss_percentage,
             grade_emoji,
+            self.late_packets,
+            self.duplicate_packets,
+            self.reordered_packets,
+            self.bursts.burst_count,
+            self.bursts.max_burst_length,
+            self.bursts.mean_burst_length,
             self.grade
         )
     }
 
     /// Get recommendation based on packet loss
-    pub fn recommendation(&self) -> &str {
-        match self.grade {
-            PacketLossGrade::Excellent => {
-                "Excellent! Your network has minimal packet loss. Perfect for VoIP, gaming, and streaming."
-            }
-            PacketLossGrade::Good => {
-                "Good packet loss levels. Suitable for most online activities including video calls."
-            }
-            PacketLossGrade::Fair => {
-                "Moderate packet loss detected. May cause occasional issues with real-time applications. \
-                 Check your WiFi signal strength or contact your ISP."
-            }
-            PacketLossGrade::Poor => {
-                "High packet loss detected! This will cause significant issues with video calls, gaming, \
-                 and streaming. Recommendations:\n\
-                 1. Check physical network cables\n\
-                 2. Move closer to WiFi router\n\
-                 3. Reduce interference from other devices\n\
-                 4. Contact your ISP if problem persists"
-            }
-        }
+    pub fn recommendation(&self) -> Message {
+        let key = match self.grade {
+            PacketLossGrade::Excellent => "excellent",
+            PacketLossGrade::Good => "good",
+            PacketLossGrade::Fair => "fair",
+            PacketLossGrade::Poor => "poor",
+        };
+        Message::new(format!("packet_loss.recommendation.{}", key), &[])
     }
 }
 
@@ -178,4 +412,65 @@ mod tests {
         assert!(matches!(PacketLossDetector::calculate_grade(2.0), PacketLossGrade::Fair));
         assert!(matches!(PacketLossDetector::calculate_grade(5.0), PacketLossGrade::Poor));
     }
+
+    #[test]
+    fn test_arrival_classification() {
+        let detector = PacketLossDetector::new();
+        let arrival = |sequence, rtt_ms| Arrival { sequence, rtt: Duration::from_millis(rtt_ms), timestamps: None };
+        // 0 and 2 swapped, 1 duplicated, 3 late, 4 and 5 lost
+        let arrivals = [
+            arrival(2, 20),
+            arrival(0, 25),
+            arrival(1, 20),
+            arrival(1, 21),
+            arrival(3, 900),
+        ];
+
+        let result = detector.analyze(&[true; 6], &arrivals);
+        assert_eq!(result.sent_packets, 6);
+        assert_eq!(result.received_packets, 4);
+        assert_eq!(result.lost_packets, 2);
+        assert_eq!(result.duplicate_packets, 1);
+        assert_eq!(result.reordered_packets, 2);
+        assert_eq!(result.late_packets, 1);
+    }
+
+    #[tokio::test]
+    async fn test_probing_stops_with_load() {
+        use crate::services::udp_echo::{UdpEchoServer, UdpSessionRegistry};
+
+        let registry = UdpSessionRegistry::new();
+        let secret = registry.register("load-test").unwrap();
+        let server = UdpEchoServer::bind("127.0.0.1:0", registry).await.unwrap();
+        let target = server.local_addr().unwrap().to_string();
+        tokio::spawn(server.run());
+
+        let detector = PacketLossDetector::new();
+        let (result, load) = detector
+            .measure_packet_loss_during(&target, "load-test", &secret, async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                "done"
+            })
+            .await;
+        let result = result.unwrap();
+
+        assert_eq!(load, "done");
+        // 50 pps for 300ms, nowhere near the 30s cap
+        assert!(result.sent_packets >= 10 && result.sent_packets <= 20);
+        assert_eq!(result.lost_packets, 0);
+    }
+
+    #[test]
+    fn test_burst_analysis() {
+        // received, lost x3, received x2, lost, received
+        let pattern = [false, true, true, true, false, false, true, false];
+        let bursts = PacketLossDetector::analyze_bursts(&pattern);
+
+        assert_eq!(bursts.burst_count, 2);
+        assert_eq!(bursts.max_burst_length, 3);
+        assert!((bursts.mean_burst_length - 2.0).abs() < 0.001);
+        // 2 of 3 good->next transitions go bad; 2 of 4 bad->next recover
+        assert!((bursts.gilbert_p - 2.0 / 3.0).abs() < 0.001);
+        assert!((bursts.gilbert_r - 0.5).abs() < 0.001);
+    }
 }
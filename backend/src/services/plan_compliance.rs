@@ -0,0 +1,572 @@
+//! ISP Plan Compliance
+//!
+//! Answers "am I getting what I pay for". A plan records the advertised
+//! download and upload speeds, an optional latency SLA and the share of the
+//! advertised speeds the ISP guarantees as a minimum. A plan belongs to the
+//! client IP it was registered from and, optionally, to one device id on
+//! that IP.
+//!
+//! Every test is evaluated against its plan as percent-of-plan metrics, and
+//! a report rolls the evaluations of a period up into a summary statement
+//! with the individual tests attached, to be handed to the ISP as evidence.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::TestResult;
+
+/// Guaranteed share of the advertised speeds when the plan does not say
+const DEFAULT_MINIMUM_PCT: f64 = 80.0;
+/// Below this many tests a report gives no verdict
+const MIN_TESTS_FOR_VERDICT: usize = 5;
+/// Share of tests that must meet the plan for a compliant verdict
+const COMPLIANT_RATE_PCT: f64 = 90.0;
+const PARTIALLY_COMPLIANT_RATE_PCT: f64 = 50.0;
+
+/// Device ids are chosen by clients; keep them URL- and CSV-safe
+pub fn validate_device_id(device_id: &str) -> Result<(), String> {
+    let valid = !device_id.is_empty()
+        && device_id.len() <= 64
+        && device_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    if valid {
+        Ok(())
+    } else {
+        Err("device_id: 1-64 characters of A-Z, a-z, 0-9, '-', '_', '.' or ':'".to_string())
+    }
+}
+
+/// A subscribed internet plan
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IspPlan {
+    pub id: String,
+    /// e.g. "Fiber 300"
+    pub name: String,
+    pub isp: Option<String>,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    /// Highest idle round trip the ISP promises
+    pub latency_sla_ms: Option<f64>,
+    /// Share of the advertised speeds guaranteed as a minimum, in percent
+    pub minimum_pct: f64,
+    pub device_id: Option<String>,
+    /// IP the plan was registered from; never sent back to clients
+    #[serde(default, skip_serializing)]
+    pub client_ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IspPlan {
+    /// Whether a test was run from this plan's IP and, for a device plan,
+    /// by its device
+    pub fn applies_to(&self, test: &TestResult) -> bool {
+        self.client_ip.as_ref() == Some(&test.client_ip)
+            && self.device_id.as_ref().is_none_or(|device_id| test.device_id.as_ref() == Some(device_id))
+    }
+}
+
+/// Body of a plan registration request
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlanRegistration {
+    pub name: String,
+    pub isp: Option<String>,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub latency_sla_ms: Option<f64>,
+    pub minimum_pct: Option<f64>,
+    pub device_id: Option<String>,
+    pub client_ip: Option<String>,
+}
+
+impl PlanRegistration {
+    /// Validate the registration. The plan is always registered to
+    /// `caller_ip`; a different `client_ip` is rejected.
+    pub fn into_plan(self, caller_ip: &str) -> Result<IspPlan, String> {
+        if self.name.trim().is_empty() {
+            return Err("name: must not be empty".to_string());
+        }
+        for (field, value) in [("download_mbps", self.download_mbps), ("upload_mbps", self.upload_mbps)] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{}: must be a positive number", field));
+            }
+        }
+        if let Some(sla) = self.latency_sla_ms {
+            if !sla.is_finite() || sla <= 0.0 {
+                return Err("latency_sla_ms: must be a positive number".to_string());
+            }
+        }
+        let minimum_pct = self.minimum_pct.unwrap_or(DEFAULT_MINIMUM_PCT);
+        if !(minimum_pct > 0.0 && minimum_pct <= 100.0) {
+            return Err("minimum_pct: must be above 0 and at most 100".to_string());
+        }
+        if let Some(device_id) = &self.device_id {
+            validate_device_id(device_id)?;
+        }
+        if self.client_ip.as_deref().is_some_and(|ip| ip.trim() != caller_ip) {
+            return Err("client_ip: plans can only be registered for the caller's own IP".to_string());
+        }
+
+        Ok(IspPlan {
+            id: Uuid::new_v4().to_string(),
+            name: self.name.trim().to_string(),
+            isp: self.isp,
+            download_mbps: self.download_mbps,
+            upload_mbps: self.upload_mbps,
+            latency_sla_ms: self.latency_sla_ms,
+            minimum_pct,
+            device_id: self.device_id,
+            client_ip: Some(caller_ip.to_string()),
+            created_at: Utc::now(),
+        })
+    }
+}
+
+/// One test measured against a plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanEvaluation {
+    pub plan_id: String,
+    pub test_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub server_id: String,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub latency_ms: f64,
+    pub download_pct_of_plan: f64,
+    pub upload_pct_of_plan: f64,
+    /// `None` when the plan has no latency SLA
+    pub latency_within_sla: Option<bool>,
+    /// Both speeds at or above the guaranteed minimum and latency within SLA
+    pub meets_plan: bool,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ComplianceVerdict {
+    Compliant,
+    PartiallyCompliant,
+    NonCompliant,
+    InsufficientData,
+}
+
+impl ComplianceVerdict {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ComplianceVerdict::Compliant => "Compliant",
+            ComplianceVerdict::PartiallyCompliant => "Partially compliant",
+            ComplianceVerdict::NonCompliant => "Non-compliant",
+            ComplianceVerdict::InsufficientData => "Insufficient data",
+        }
+    }
+
+    pub fn emoji(&self) -> &str {
+        match self {
+            ComplianceVerdict::Compliant => "✅",
+            ComplianceVerdict::PartiallyCompliant => "⚠️",
+            ComplianceVerdict::NonCompliant => "❌",
+            ComplianceVerdict::InsufficientData => "❔",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            ComplianceVerdict::Compliant => "The connection delivered the plan in at least 90% of tests",
+            ComplianceVerdict::PartiallyCompliant => "The connection fell short of the plan in more than 10% of tests",
+            ComplianceVerdict::NonCompliant => "The connection fell short of the plan in most tests",
+            ComplianceVerdict::InsufficientData => "Run at least 5 tests, ideally at different times of day",
+        }
+    }
+
+    fn from_rate(tests: usize, compliance_rate_pct: f64) -> Self {
+        match compliance_rate_pct {
+            _ if tests < MIN_TESTS_FOR_VERDICT => ComplianceVerdict::InsufficientData,
+            rate if rate >= COMPLIANT_RATE_PCT => ComplianceVerdict::Compliant,
+            rate if rate >= PARTIALLY_COMPLIANT_RATE_PCT => ComplianceVerdict::PartiallyCompliant,
+            _ => ComplianceVerdict::NonCompliant,
+        }
+    }
+}
+
+/// Delivered speed in one direction over a report period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanSpeedStats {
+    pub advertised_mbps: f64,
+    pub median_mbps: f64,
+    pub median_pct_of_plan: f64,
+    /// Speed reached in 90% of tests
+    pub p10_mbps: f64,
+    pub p10_pct_of_plan: f64,
+    pub min_pct_of_plan: f64,
+    pub tests_below_minimum: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyCompliance {
+    pub sla_ms: f64,
+    pub median_ms: f64,
+    pub within_sla_pct: f64,
+}
+
+/// Rolling compliance over a period, with the tests as evidence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    pub plan: IspPlan,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub tests: usize,
+    pub tests_meeting_plan: usize,
+    pub compliance_rate_pct: f64,
+    /// `None` without tests
+    pub download: Option<PlanSpeedStats>,
+    pub upload: Option<PlanSpeedStats>,
+    /// `None` without tests or without a latency SLA
+    pub latency: Option<LatencyCompliance>,
+    pub verdict: ComplianceVerdict,
+    /// Plain-language summary to quote to the ISP
+    pub statement: String,
+    /// Every test in the period, oldest first
+    pub evaluations: Vec<PlanEvaluation>,
+}
+
+impl ComplianceReport {
+    /// The evidence as CSV, with the plan and summary as leading comment lines
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        csv.push_str(&format!("# Plan: {}\n", csv_field(&Self::plan_line(&self.plan))));
+        csv.push_str(&format!(
+            "# Period (UTC): {} to {}\n",
+            self.period_start.to_rfc3339(), self.period_end.to_rfc3339()
+        ));
+        csv.push_str(&format!(
+            "# Tests meeting plan: {} of {} ({:.0}%)\n",
+            self.tests_meeting_plan, self.tests, self.compliance_rate_pct
+        ));
+        csv.push_str(&format!("# Verdict: {}\n", self.verdict.as_str()));
+        csv.push_str("test_id,timestamp_utc,server_id,download_mbps,download_pct_of_plan,upload_mbps,upload_pct_of_plan,latency_ms,latency_within_sla,meets_plan\n");
+        for e in &self.evaluations {
+            csv.push_str(&format!(
+                "{},{},{},{:.2},{:.1},{:.2},{:.1},{:.1},{},{}\n",
+                e.test_id, e.timestamp.to_rfc3339(), csv_field(&e.server_id),
+                e.download_mbps, e.download_pct_of_plan,
+                e.upload_mbps, e.upload_pct_of_plan,
+                e.latency_ms,
+                e.latency_within_sla.map_or(String::new(), |within| within.to_string()),
+                e.meets_plan
+            ));
+        }
+        csv
+    }
+
+    fn plan_line(plan: &IspPlan) -> String {
+        let mut line = format!(
+            "{}{}, advertised {} Mbps down / {} Mbps up, guaranteed minimum {:.0}%",
+            plan.name,
+            plan.isp.as_ref().map_or(String::new(), |isp| format!(" ({})", isp)),
+            plan.download_mbps, plan.upload_mbps, plan.minimum_pct
+        );
+        if let Some(sla) = plan.latency_sla_ms {
+            line.push_str(&format!(", latency SLA {} ms", sla));
+        }
+        line
+    }
+}
+
+/// Quote a free-text CSV field: quotes are doubled, line breaks flattened so
+/// the comment lines stay single lines, and a leading `=`, `+`, `-`, `@` or
+/// tab is prefixed with `'` so spreadsheets do not evaluate it as a formula
+fn csv_field(value: &str) -> String {
+    let mut value = value.replace(['\r', '\n'], " ");
+    if value.starts_with(['=', '+', '-', '@', '\t']) {
+        value.insert(0, '\'');
+    }
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+pub struct PlanComplianceCalculator;
+
+impl PlanComplianceCalculator {
+    pub fn evaluate(plan: &IspPlan, test: &TestResult) -> PlanEvaluation {
+        let download_pct_of_plan = test.download_mbps / plan.download_mbps * 100.0;
+        let upload_pct_of_plan = test.upload_mbps / plan.upload_mbps * 100.0;
+        let latency_within_sla = plan.latency_sla_ms.map(|sla| test.latency_ms <= sla);
+
+        let mut issues = Vec::new();
+        for (direction, measured, advertised, pct) in [
+            ("Download", test.download_mbps, plan.download_mbps, download_pct_of_plan),
+            ("Upload", test.upload_mbps, plan.upload_mbps, upload_pct_of_plan),
+        ] {
+            if pct < plan.minimum_pct {
+                issues.push(format!(
+                    "{} {:.1} Mbps is {:.0}% of the advertised {} Mbps (guaranteed minimum {:.0}%)",
+                    direction, measured, pct, advertised, plan.minimum_pct
+                ));
+            }
+        }
+        if let (Some(false), Some(sla)) = (latency_within_sla, plan.latency_sla_ms) {
+            issues.push(format!("Latency {:.0} ms exceeds the {} ms SLA", test.latency_ms, sla));
+        }
+
+        PlanEvaluation {
+            plan_id: plan.id.clone(),
+            test_id: test.id.clone(),
+            timestamp: test.timestamp,
+            server_id: test.server_id.clone(),
+            download_mbps: test.download_mbps,
+            upload_mbps: test.upload_mbps,
+            latency_ms: test.latency_ms,
+            download_pct_of_plan,
+            upload_pct_of_plan,
+            latency_within_sla,
+            meets_plan: issues.is_empty(),
+            issues,
+        }
+    }
+
+    /// Roll up the plan's tests between `period_start` and `period_end`;
+    /// tests outside the period, from other clients or without measured
+    /// speeds are ignored
+    pub fn report(
+        plan: &IspPlan,
+        tests: &[TestResult],
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> ComplianceReport {
+        let mut evaluations: Vec<PlanEvaluation> = tests.iter()
+            .filter(|test| test.measured && plan.applies_to(test))
+            .filter(|test| test.timestamp >= period_start && test.timestamp <= period_end)
+            .map(|test| Self::evaluate(plan, test))
+            .collect();
+        evaluations.sort_by_key(|e| e.timestamp);
+
+        let count = evaluations.len();
+        let tests_meeting_plan = evaluations.iter().filter(|e| e.meets_plan).count();
+        let compliance_rate_pct = if count > 0 {
+            tests_meeting_plan as f64 / count as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        let download = Self::speed_stats(plan, plan.download_mbps, evaluations.iter().map(|e| e.download_mbps).collect());
+        let upload = Self::speed_stats(plan, plan.upload_mbps, evaluations.iter().map(|e| e.upload_mbps).collect());
+        let latency = match plan.latency_sla_ms {
+            Some(sla_ms) if count > 0 => {
+                let mut latencies: Vec<f64> = evaluations.iter().map(|e| e.latency_ms).collect();
+                latencies.sort_by(|a, b| a.total_cmp(b));
+                let within = evaluations.iter().filter(|e| e.latency_within_sla == Some(true)).count();
+                Some(LatencyCompliance {
+                    sla_ms,
+                    median_ms: Self::percentile(&latencies, 50.0),
+                    within_sla_pct: within as f64 / count as f64 * 100.0,
+                })
+            }
+            _ => None,
+        };
+        let verdict = ComplianceVerdict::from_rate(count, compliance_rate_pct);
+
+        let mut report = ComplianceReport {
+            plan: plan.clone(),
+            period_start,
+            period_end,
+            generated_at: Utc::now(),
+            tests: count,
+            tests_meeting_plan,
+            compliance_rate_pct,
+            download,
+            upload,
+            latency,
+            verdict,
+            statement: String::new(),
+            evaluations,
+        };
+        report.statement = Self::statement(&report);
+        report
+    }
+
+    fn speed_stats(plan: &IspPlan, advertised_mbps: f64, mut speeds: Vec<f64>) -> Option<PlanSpeedStats> {
+        if speeds.is_empty() {
+            return None;
+        }
+        speeds.sort_by(|a, b| a.total_cmp(b));
+        let pct = |mbps: f64| mbps / advertised_mbps * 100.0;
+        let median_mbps = Self::percentile(&speeds, 50.0);
+        let p10_mbps = Self::percentile(&speeds, 10.0);
+
+        Some(PlanSpeedStats {
+            advertised_mbps,
+            median_mbps,
+            median_pct_of_plan: pct(median_mbps),
+            p10_mbps,
+            p10_pct_of_plan: pct(p10_mbps),
+            min_pct_of_plan: pct(speeds[0]),
+            tests_below_minimum: speeds.iter().filter(|s| pct(**s) < plan.minimum_pct).count(),
+        })
+    }
+
+    /// Linear interpolation between closest ranks
+    fn percentile(sorted: &[f64], p: f64) -> f64 {
+        let rank = p / 100.0 * (sorted.len() - 1) as f64;
+        let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+        sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+    }
+
+    fn statement(report: &ComplianceReport) -> String {
+        let plan = &report.plan;
+        let subject = match (&plan.device_id, &plan.client_ip) {
+            (Some(device_id), _) => format!("device {}", device_id),
+            (None, Some(ip)) => format!("IP address {}", ip),
+            (None, None) => "this connection".to_string(),
+        };
+        let mut statement = format!(
+            "Plan: {}. Between {} and {} (UTC), {} speed test{} run from {}.",
+            ComplianceReport::plan_line(plan),
+            report.period_start.format("%Y-%m-%d %H:%M"),
+            report.period_end.format("%Y-%m-%d %H:%M"),
+            report.tests,
+            if report.tests == 1 { " was" } else { "s were" },
+            subject
+        );
+        if report.tests == 0 {
+            return statement;
+        }
+
+        let mut servers: Vec<&str> = report.evaluations.iter().map(|e| e.server_id.as_str()).collect();
+        servers.sort_unstable();
+        servers.dedup();
+        statement.push_str(&format!(
+            " Tests ran against server{} {}. {} of {} ({:.0}%) met the plan.",
+            if servers.len() == 1 { "" } else { "s" },
+            servers.join(", "),
+            report.tests_meeting_plan, report.tests, report.compliance_rate_pct
+        ));
+        for (direction, stats) in [("download", &report.download), ("upload", &report.upload)] {
+            if let Some(stats) = stats {
+                statement.push_str(&format!(
+                    " Median {} was {:.1} Mbps ({:.0}% of advertised); 90% of tests reached at least {:.1} Mbps ({:.0}%).",
+                    direction, stats.median_mbps, stats.median_pct_of_plan, stats.p10_mbps, stats.p10_pct_of_plan
+                ));
+            }
+        }
+        if let Some(latency) = &report.latency {
+            statement.push_str(&format!(
+                " Latency was within the {} ms SLA in {:.0}% of tests (median {:.0} ms).",
+                latency.sla_ms, latency.within_sla_pct, latency.median_ms
+            ));
+        }
+        statement.push_str(&format!(" Verdict: {}.", report.verdict.as_str()));
+        statement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn plan() -> IspPlan {
+        PlanRegistration {
+            name: "Fiber 300".to_string(),
+            isp: Some("ExampleNet".to_string()),
+            download_mbps: 300.0,
+            upload_mbps: 50.0,
+            latency_sla_ms: Some(30.0),
+            minimum_pct: None,
+            device_id: Some("living-room-pc".to_string()),
+            client_ip: None,
+        }
+        .into_plan("203.0.113.7")
+        .unwrap()
+    }
+
+    fn test(download: f64, upload: f64, latency: f64, hours_ago: i64) -> TestResult {
+        let mut test = TestResult::new("mumbai-01".to_string(), "203.0.113.7".to_string());
+        test.download_mbps = download;
+        test.upload_mbps = upload;
+        test.latency_ms = latency;
+        test.timestamp = Utc::now() - Duration::hours(hours_ago);
+        test.device_id = Some("living-room-pc".to_string());
+        test
+    }
+
+    #[test]
+    fn test_evaluation_reports_percent_of_plan() {
+        let plan = plan();
+        assert_eq!(plan.minimum_pct, 80.0);
+
+        let good = PlanComplianceCalculator::evaluate(&plan, &test(285.0, 48.0, 12.0, 1));
+        assert!(good.meets_plan);
+        assert_eq!(good.download_pct_of_plan, 95.0);
+        assert_eq!(good.latency_within_sla, Some(true));
+
+        let slow = PlanComplianceCalculator::evaluate(&plan, &test(150.0, 48.0, 45.0, 1));
+        assert!(!slow.meets_plan);
+        assert_eq!(slow.issues.len(), 2);
+        assert!(slow.issues[0].contains("50% of the advertised 300 Mbps"));
+
+        assert!(PlanRegistration { download_mbps: 0.0, ..registration() }.into_plan("::1").is_err());
+        assert!(PlanRegistration { device_id: Some("bad id!".to_string()), ..registration() }.into_plan("::1").is_err());
+        assert!(PlanRegistration { client_ip: Some("198.51.100.9".to_string()), ..registration() }.into_plan("::1").is_err());
+        let by_ip = registration().into_plan("198.51.100.2").unwrap();
+        assert_eq!(by_ip.client_ip.as_deref(), Some("198.51.100.2"));
+        assert!(!by_ip.applies_to(&test(100.0, 10.0, 10.0, 1)));
+
+        // Device ids collide across households; the IP must match too
+        assert!(plan.applies_to(&test(100.0, 10.0, 10.0, 1)));
+        let elsewhere = TestResult { client_ip: "198.51.100.9".to_string(), ..test(100.0, 10.0, 10.0, 1) };
+        assert!(!plan.applies_to(&elsewhere));
+    }
+
+    fn registration() -> PlanRegistration {
+        PlanRegistration {
+            name: "Basic 100".to_string(),
+            isp: None,
+            download_mbps: 100.0,
+            upload_mbps: 10.0,
+            latency_sla_ms: None,
+            minimum_pct: None,
+            device_id: None,
+            client_ip: None,
+        }
+    }
+
+    #[test]
+    fn test_report_rolls_up_period_and_exports_csv() {
+        let plan = plan();
+        let mut tests: Vec<TestResult> = (0..8)
+            .map(|i| test(if i < 6 { 290.0 } else { 120.0 }, 49.0, 10.0 + i as f64, i * 12))
+            .collect();
+        // Outside the period, from another device, and with placeholder speeds
+        tests.push(test(10.0, 1.0, 300.0, 24 * 40));
+        let mut other = test(10.0, 1.0, 300.0, 2);
+        other.device_id = Some("phone".to_string());
+        tests.push(other);
+        let mut placeholder = test(10.0, 1.0, 300.0, 3);
+        placeholder.measured = false;
+        tests.push(placeholder);
+
+        let end = Utc::now();
+        let report = PlanComplianceCalculator::report(&plan, &tests, end - Duration::days(30), end);
+        assert_eq!(report.tests, 8);
+        assert_eq!(report.tests_meeting_plan, 6);
+        assert_eq!(report.compliance_rate_pct, 75.0);
+        assert_eq!(report.verdict, ComplianceVerdict::PartiallyCompliant);
+        assert_eq!(report.download.as_ref().unwrap().tests_below_minimum, 2);
+        assert_eq!(report.latency.as_ref().unwrap().within_sla_pct, 100.0);
+        assert!(report.evaluations.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        assert!(report.statement.contains("6 of 8 (75%) met the plan"));
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("# Plan: \"Fiber 300 (ExampleNet), advertised 300 Mbps down / 50 Mbps up"));
+        assert_eq!(csv.lines().filter(|l| !l.starts_with('#')).count(), 9);
+
+        // User-supplied text can neither break the file nor run as a formula
+        let hostile = IspPlan { name: "=HYPERLINK(\"x\"),\n=1".to_string(), isp: Some("a,\"b\"".to_string()), ..plan.clone() };
+        let csv = PlanComplianceCalculator::report(&hostile, &tests, end - Duration::days(30), end).to_csv();
+        assert!(csv.starts_with("# Plan: \"'=HYPERLINK(\"\"x\"\"), =1 (a,\"\"b\"\"), advertised"));
+        assert_eq!(csv.lines().count(), 5 + 8);
+        assert_eq!(csv_field("-5"), "\"'-5\"");
+
+        let empty = PlanComplianceCalculator::report(&plan, &[], end - Duration::days(30), end);
+        assert_eq!(empty.verdict, ComplianceVerdict::InsufficientData);
+        assert!(empty.download.is_none());
+    }
+}
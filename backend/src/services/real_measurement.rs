@@ -1,18 +1,19 @@
-/// Real Speed Test Measurement Engine
-/// 
-/// This implements ACTUAL data transfer for accurate speed testing.
-/// Unlike simulation, this sends real bytes over the network.
+//! Real Speed Test Measurement Engine
+//! 
+//! This implements ACTUAL data transfer for accurate speed testing.
+//! Unlike simulation, this sends real bytes over the network.
 
 use actix_ws::Session;
-use bytes::{Bytes, BytesMut};
+use bytes::Bytes;
 use chrono::Utc;
-use log::{info, debug, warn};
+use log::{info, debug};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use rand::Rng;
 
 use crate::config::AppConfig;
 use crate::models::{TestProgress, TestResult};
+use crate::services::jitter::JitterCalculator;
 
 pub struct RealMeasurementEngine {
     config: AppConfig,
@@ -129,7 +130,7 @@ impl RealMeasurementEngine {
         
         // Calculate statistics
         let avg_latency = latencies.iter().sum::<f64>() / latencies.len() as f64;
-        let jitter = self.calculate_jitter(&latencies);
+        let jitter = JitterCalculator::rfc3550(&latencies);
         
         Ok((avg_latency, jitter))
     }
@@ -171,7 +172,7 @@ impl RealMeasurementEngine {
                 measurements.push(speed_mbps);
                 
                 // Update progress every 50 chunks
-                if chunk_count % 50 == 0 {
+                if chunk_count.is_multiple_of(50) {
                     let progress = 0.2 + (start.elapsed().as_secs_f32() / (test_duration.as_secs_f32())) * 0.4;
                     let avg_so_far = measurements.iter().sum::<f64>() / measurements.len() as f64;
                     
@@ -273,19 +274,6 @@ impl RealMeasurementEngine {
         Ok(avg_speed_mbps)
     }
 
-    fn calculate_jitter(&self, latencies: &[f64]) -> f64 {
-        if latencies.len() < 2 {
-            return 0.0;
-        }
-        
-        let mut differences = Vec::new();
-        for i in 1..latencies.len() {
-            differences.push((latencies[i] - latencies[i - 1]).abs());
-        }
-        
-        differences.iter().sum::<f64>() / differences.len() as f64
-    }
-
     async fn send_progress(
         &self,
         session: &mut Session,
@@ -314,19 +302,3 @@ impl RealMeasurementEngine {
         Ok(())
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_jitter_calculation() {
-        let engine = RealMeasurementEngine::new(AppConfig::default());
-        
-        let latencies = vec![10.0, 11.0, 10.5, 12.0, 11.5];
-        let jitter = engine.calculate_jitter(&latencies);
-        
-        // Average difference should be around 0.75-1.0
-        assert!(jitter > 0.0 && jitter < 2.0);
-    }
-}
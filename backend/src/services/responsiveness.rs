@@ -0,0 +1,564 @@
+//! Responsiveness Test (RPM)
+//!
+//! Implements the methodology of the IETF IPPM responsiveness draft
+//! (draft-ietf-ippm-responsiveness):
+//!
+//! 1. Load-generating connections are added one per interval until the
+//!    goodput and the probe latency both stop changing (saturation)
+//! 2. Foreign probes open a brand-new connection and time the TCP handshake
+//!    and an HTTP request on it separately
+//! 3. Self probes send an HTTP request over an existing loaded connection
+//!    (multiplexed via HTTP/2), exposing queueing inside the load itself
+//! 4. RPM is computed from the trimmed means of those components, taken
+//!    from the probes of the last stable intervals
+//!
+//! The enhanced test runs this against the server's own HTTP port, so it is
+//! a server self-test: it measures how the server's stack queues under
+//! load, not the client's link. Each run opens up to 16 connections for up
+//! to 20 s, so only `MAX_CONCURRENT_RUNS` may run at once; further runs
+//! fail fast and the test reports no responsiveness.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+use crate::services::loaded_latency::{LatencySample, ProbeOutcome, ProbeType};
+use crate::services::stall_detection::ThroughputSample;
+
+/// Responsiveness runs allowed at once across all tests
+pub const MAX_CONCURRENT_RUNS: usize = 2;
+
+static RUNS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(MAX_CONCURRENT_RUNS));
+
+/// Which direction the load-generating connections saturate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadDirection {
+    Download,
+    Upload,
+}
+
+/// Tunables of the responsiveness test, defaults follow the draft
+#[derive(Debug, Clone)]
+pub struct ResponsivenessConfig {
+    /// Length of one measurement interval; a new connection is added per interval
+    pub interval: Duration,
+    /// Moving-average distance (MAD): number of intervals averaged and compared
+    pub moving_average_intervals: usize,
+    /// Maximum relative standard deviation of the moving averages to call them stable
+    pub stability_tolerance: f64,
+    /// Fraction of the highest probe values discarded before averaging
+    pub trim_fraction: f64,
+    /// Hard cap on load-generating connections
+    pub max_connections: usize,
+    /// Give up waiting for saturation after this long
+    pub max_duration: Duration,
+    /// Time between probe rounds (one foreign and one self probe per round)
+    pub probe_interval: Duration,
+    /// Timeout for a single probe
+    pub probe_timeout: Duration,
+    /// Resolution of the throughput time series
+    pub throughput_sample_interval: Duration,
+}
+
+impl Default for ResponsivenessConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            moving_average_intervals: 4,
+            stability_tolerance: 0.05,
+            trim_fraction: 0.05,
+            max_connections: 16,
+            max_duration: Duration::from_secs(20),
+            probe_interval: Duration::from_millis(100),
+            probe_timeout: Duration::from_secs(5),
+            throughput_sample_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponsivenessResult {
+    pub direction: LoadDirection,
+
+    /// Spec-conformant Round-trips Per Minute
+    pub rpm: f64,
+
+    // Trimmed means of each probe component
+    pub foreign_tcp_ms: f64,
+    pub foreign_http_ms: f64,
+    pub self_http_ms: f64,
+
+    /// Whether both goodput and responsiveness reached saturation
+    pub saturated: bool,
+    pub load_connections: usize,
+    pub goodput_mbps: f64,
+    /// Goodput of each interval from the one where the load stopped
+    /// growing (all but the first interval when it never did)
+    #[serde(default)]
+    pub goodput_samples_mbps: Vec<f64>,
+    /// Fine-grained throughput over the same intervals
+    #[serde(default)]
+    pub throughput_series: Vec<ThroughputSample>,
+    #[serde(default)]
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+
+    /// Probes used for the final calculation (foreign and self)
+    pub samples: Vec<LatencySample>,
+}
+
+/// One completed foreign probe: TCP handshake plus an HTTP request on the new connection
+#[derive(Debug, Clone, Copy)]
+struct ForeignProbe {
+    tcp_ms: f64,
+    http_ms: f64,
+}
+
+/// Probe results collected during one interval
+#[derive(Debug, Default)]
+struct IntervalProbes {
+    foreign: Vec<ForeignProbe>,
+    self_http: Vec<f64>,
+    samples: Vec<LatencySample>,
+}
+
+struct LoadGenerator {
+    client: reqwest::Client,
+    handle: JoinHandle<()>,
+}
+
+pub struct ResponsivenessTester {
+    config: ResponsivenessConfig,
+    next_sequence: u32,
+}
+
+impl Default for ResponsivenessTester {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponsivenessTester {
+    pub fn new() -> Self {
+        Self::with_config(ResponsivenessConfig::default())
+    }
+
+    pub fn with_config(config: ResponsivenessConfig) -> Self {
+        Self {
+            config,
+            next_sequence: 0,
+        }
+    }
+
+    /// Run the full ramp-up and probing cycle against `target` (host:port);
+    /// fails at once when `MAX_CONCURRENT_RUNS` are already running
+    pub async fn run(
+        &mut self,
+        target: &str,
+        direction: LoadDirection,
+    ) -> Result<ResponsivenessResult, Box<dyn std::error::Error>> {
+        let _permit = RUNS.try_acquire()
+            .map_err(|_| format!("{} responsiveness tests already running; try again later", MAX_CONCURRENT_RUNS))?;
+        log::info!("📶 Responsiveness test ({:?}) against {}", direction, target);
+
+        let started_at = Utc::now();
+        let start = Instant::now();
+        let bytes = Arc::new(AtomicU64::new(0));
+        let mut generators: Vec<LoadGenerator> = Vec::new();
+        let series = Arc::new(Mutex::new(Vec::new()));
+        let sampler = Self::spawn_sampler(start, bytes.clone(), series.clone(), self.config.throughput_sample_interval);
+        let mut interval_offsets_ms = Vec::new();
+
+        let mut goodput_history = Vec::new();
+        let mut goodput_averages = Vec::new();
+        let mut latency_history = Vec::new();
+        let mut latency_averages = Vec::new();
+        let mut intervals: Vec<IntervalProbes> = Vec::new();
+        let mut goodput_saturated = false;
+        let mut steady_from = None;
+        let mut saturated = false;
+
+        while start.elapsed() < self.config.max_duration {
+            // Keep adding load until goodput stops growing
+            if !goodput_saturated && generators.len() < self.config.max_connections {
+                generators.push(Self::spawn_generator(target, direction, bytes.clone())?);
+            }
+
+            let interval_start = Instant::now();
+            interval_offsets_ms.push(start.elapsed().as_millis() as u64);
+            let bytes_before = bytes.load(Ordering::Relaxed);
+            let probes = self.probe_interval(target, &generators).await;
+            let elapsed = interval_start.elapsed().as_secs_f64();
+            let transferred = bytes.load(Ordering::Relaxed) - bytes_before;
+
+            let goodput_mbps = if elapsed > 0.0 {
+                transferred as f64 * 8.0 / elapsed / 1_000_000.0
+            } else {
+                0.0
+            };
+            goodput_history.push(goodput_mbps);
+            goodput_averages.push(Self::moving_average(&goodput_history, self.config.moving_average_intervals));
+
+            let mut interval_latencies: Vec<f64> = probes.foreign.iter()
+                .map(|p| p.tcp_ms + p.http_ms)
+                .chain(probes.self_http.iter().copied())
+                .collect();
+            latency_history.push(Self::trimmed_mean(&mut interval_latencies, self.config.trim_fraction));
+            latency_averages.push(Self::moving_average(&latency_history, self.config.moving_average_intervals));
+            intervals.push(probes);
+
+            log::debug!("Interval {}: {} connections, {:.1} Mbps",
+                intervals.len(), generators.len(), goodput_mbps);
+
+            goodput_saturated = goodput_saturated || Self::is_stable(
+                &goodput_averages,
+                self.config.moving_average_intervals,
+                self.config.stability_tolerance,
+            );
+            if goodput_saturated && steady_from.is_none() {
+                steady_from = Some(goodput_history.len().saturating_sub(self.config.moving_average_intervals));
+            }
+            let latency_saturated = Self::is_stable(
+                &latency_averages,
+                self.config.moving_average_intervals,
+                self.config.stability_tolerance,
+            );
+
+            if goodput_saturated && latency_saturated {
+                saturated = true;
+                break;
+            }
+        }
+
+        let load_connections = generators.len();
+        for generator in generators {
+            generator.handle.abort();
+        }
+        sampler.abort();
+
+        // Only the probes of the last MAD intervals represent the saturated state
+        let keep = intervals.len().min(self.config.moving_average_intervals);
+        let mut foreign = Vec::new();
+        let mut self_http = Vec::new();
+        let mut samples = Vec::new();
+        for probes in intervals.into_iter().rev().take(keep) {
+            foreign.extend(probes.foreign);
+            self_http.extend(probes.self_http);
+            samples.extend(probes.samples);
+        }
+        samples.sort_by_key(|s| s.sequence);
+
+        if foreign.is_empty() || self_http.is_empty() {
+            return Err("No successful responsiveness probes".into());
+        }
+
+        let mut tcp: Vec<f64> = foreign.iter().map(|p| p.tcp_ms).collect();
+        let mut http: Vec<f64> = foreign.iter().map(|p| p.http_ms).collect();
+        let foreign_tcp_ms = Self::trimmed_mean(&mut tcp, self.config.trim_fraction);
+        let foreign_http_ms = Self::trimmed_mean(&mut http, self.config.trim_fraction);
+        let self_http_ms = Self::trimmed_mean(&mut self_http, self.config.trim_fraction);
+        let rpm = Self::calculate_rpm(foreign_tcp_ms, foreign_http_ms, self_http_ms);
+
+        let goodput_mbps = goodput_averages.last().copied().unwrap_or(0.0);
+        let steady_from = steady_from.unwrap_or(1).min(goodput_history.len());
+        let goodput_samples_mbps = goodput_history.split_off(steady_from);
+        let steady_from_ms = interval_offsets_ms.get(steady_from).copied().unwrap_or(u64::MAX);
+        let sample_ms = self.config.throughput_sample_interval.as_millis() as u64;
+        let throughput_series: Vec<ThroughputSample> = series.lock().unwrap().iter()
+            .filter(|sample| sample.offset_ms >= steady_from_ms.saturating_add(sample_ms))
+            .copied()
+            .collect();
+
+        log::info!("✅ Responsiveness ({:?}): {:.0} RPM with {} connections ({})",
+            direction, rpm, load_connections,
+            if saturated { "saturated" } else { "not saturated" });
+
+        Ok(ResponsivenessResult {
+            direction,
+            rpm,
+            foreign_tcp_ms,
+            foreign_http_ms,
+            self_http_ms,
+            saturated,
+            load_connections,
+            goodput_mbps,
+            goodput_samples_mbps,
+            throughput_series,
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            samples,
+        })
+    }
+
+    /// Record the throughput of every `period` until aborted
+    fn spawn_sampler(
+        start: Instant,
+        bytes: Arc<AtomicU64>,
+        series: Arc<Mutex<Vec<ThroughputSample>>>,
+        period: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            let mut last_bytes = 0;
+            let mut last_tick = Instant::now();
+            loop {
+                ticker.tick().await;
+                let now = Instant::now();
+                let total = bytes.load(Ordering::Relaxed);
+                let elapsed = now.duration_since(last_tick).as_secs_f64();
+                series.lock().unwrap().push(ThroughputSample {
+                    offset_ms: now.duration_since(start).as_millis() as u64,
+                    mbps: (total - last_bytes) as f64 * 8.0 / elapsed / 1_000_000.0,
+                });
+                last_bytes = total;
+                last_tick = now;
+            }
+        })
+    }
+
+    /// Open a new HTTP/2 connection that keeps transferring data in `direction`
+    fn spawn_generator(
+        target: &str,
+        direction: LoadDirection,
+        bytes: Arc<AtomicU64>,
+    ) -> Result<LoadGenerator, Box<dyn std::error::Error>> {
+        // One client per generator so each owns exactly one connection,
+        // which self probes can then be multiplexed onto
+        let client = reqwest::Client::builder()
+            .http2_prior_knowledge()
+            .build()?;
+
+        let worker = client.clone();
+        let handle = match direction {
+            LoadDirection::Download => {
+                let url = format!("http://{}/api/download?size_mb=25", target);
+                tokio::spawn(async move {
+                    loop {
+                        let Ok(mut response) = worker.get(&url).send().await else {
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        };
+                        while let Ok(Some(chunk)) = response.chunk().await {
+                            bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                        }
+                    }
+                })
+            }
+            LoadDirection::Upload => {
+                // Stay below the server's default 256 KiB payload limit
+                const UPLOAD_CHUNK: usize = 192 * 1024;
+                let url = format!("http://{}/api/upload", target);
+                let payload = bytes::Bytes::from(vec![0u8; UPLOAD_CHUNK]);
+                tokio::spawn(async move {
+                    loop {
+                        match worker.post(&url).body(payload.clone()).send().await {
+                            Ok(response) if response.status().is_success() => {
+                                bytes.fetch_add(UPLOAD_CHUNK as u64, Ordering::Relaxed);
+                            }
+                            _ => tokio::time::sleep(Duration::from_millis(100)).await,
+                        }
+                    }
+                })
+            }
+        };
+
+        Ok(LoadGenerator { client, handle })
+    }
+
+    /// Send probe rounds for one interval, spreading self probes over the generators
+    async fn probe_interval(&mut self, target: &str, generators: &[LoadGenerator]) -> IntervalProbes {
+        let interval_start = Instant::now();
+        let mut probes = IntervalProbes::default();
+        let mut round = 0usize;
+        if generators.is_empty() {
+            return probes;
+        }
+
+        while interval_start.elapsed() < self.config.interval {
+            let round_start = Instant::now();
+            let generator = &generators[round % generators.len()];
+
+            let foreign_sequence = self.take_sequence();
+            let self_sequence = self.take_sequence();
+            let sent_at = Utc::now();
+
+            let (foreign, self_rtt) = tokio::join!(
+                Self::foreign_probe(target, self.config.probe_timeout),
+                Self::self_probe(&generator.client, target, self.config.probe_timeout),
+            );
+
+            probes.samples.push(LatencySample {
+                sequence: foreign_sequence,
+                sent_at,
+                probe_type: ProbeType::Foreign,
+                outcome: if foreign.is_some() { ProbeOutcome::Ok } else { ProbeOutcome::Timeout },
+                rtt_ms: foreign.map(|p| p.tcp_ms + p.http_ms),
+            });
+            probes.samples.push(LatencySample {
+                sequence: self_sequence,
+                sent_at,
+                probe_type: ProbeType::SelfProbe,
+                outcome: if self_rtt.is_some() { ProbeOutcome::Ok } else { ProbeOutcome::Timeout },
+                rtt_ms: self_rtt,
+            });
+            probes.foreign.extend(foreign);
+            probes.self_http.extend(self_rtt);
+
+            round += 1;
+            if let Some(remaining) = self.config.probe_interval.checked_sub(round_start.elapsed()) {
+                tokio::time::sleep(remaining).await;
+            }
+        }
+
+        probes
+    }
+
+    /// New TCP connection, then a minimal HTTP/1.1 request on it
+    async fn foreign_probe(target: &str, timeout: Duration) -> Option<ForeignProbe> {
+        let probe = async {
+            let connect_start = Instant::now();
+            let mut stream = TcpStream::connect(target).await.ok()?;
+            let tcp_ms = connect_start.elapsed().as_secs_f64() * 1000.0;
+
+            let request = format!(
+                "GET /api/health HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                target
+            );
+            let http_start = Instant::now();
+            stream.write_all(request.as_bytes()).await.ok()?;
+
+            // The first bytes of the response complete the round trip
+            let mut buf = [0u8; 512];
+            let read = stream.read(&mut buf).await.ok()?;
+            if read == 0 {
+                return None;
+            }
+            let http_ms = http_start.elapsed().as_secs_f64() * 1000.0;
+
+            Some(ForeignProbe { tcp_ms, http_ms })
+        };
+
+        tokio::time::timeout(timeout, probe).await.ok().flatten()
+    }
+
+    /// HTTP request multiplexed onto a load-generating connection
+    async fn self_probe(client: &reqwest::Client, target: &str, timeout: Duration) -> Option<f64> {
+        let url = format!("http://{}/api/health", target);
+        let start = Instant::now();
+        match client.get(&url).timeout(timeout).send().await {
+            Ok(_) => Some(start.elapsed().as_secs_f64() * 1000.0),
+            Err(_) => None,
+        }
+    }
+
+    fn take_sequence(&mut self) -> u32 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        sequence
+    }
+
+    /// RPM as defined by the draft: foreign and self probes weigh half each,
+    /// the foreign half being the mean of its components (TCP and HTTP; no TLS here)
+    fn calculate_rpm(foreign_tcp_ms: f64, foreign_http_ms: f64, self_http_ms: f64) -> f64 {
+        let foreign_ms = (foreign_tcp_ms + foreign_http_ms) / 2.0;
+        let round_trip_ms = 0.5 * foreign_ms + 0.5 * self_http_ms;
+
+        if round_trip_ms > 0.0 {
+            60_000.0 / round_trip_ms
+        } else {
+            0.0
+        }
+    }
+
+    /// Mean after discarding the highest `trim_fraction` of values
+    fn trimmed_mean(values: &mut [f64], trim_fraction: f64) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let discard = (values.len() as f64 * trim_fraction).floor() as usize;
+        let kept = &values[..values.len() - discard];
+
+        kept.iter().sum::<f64>() / kept.len() as f64
+    }
+
+    /// Mean of the last `window` values
+    fn moving_average(values: &[f64], window: usize) -> f64 {
+        let window = &values[values.len().saturating_sub(window)..];
+        if window.is_empty() {
+            return 0.0;
+        }
+        window.iter().sum::<f64>() / window.len() as f64
+    }
+
+    /// Stable once the last `window` moving averages deviate less than `tolerance` from their mean
+    fn is_stable(averages: &[f64], window: usize, tolerance: f64) -> bool {
+        if window == 0 || averages.len() < window {
+            return false;
+        }
+
+        let recent = &averages[averages.len() - window..];
+        let mean = recent.iter().sum::<f64>() / recent.len() as f64;
+        if mean <= 0.0 {
+            return false;
+        }
+
+        let variance = recent.iter()
+            .map(|v| (v - mean).powi(2))
+            .sum::<f64>() / recent.len() as f64;
+
+        variance.sqrt() / mean < tolerance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trimmed_mean_drops_top_values() {
+        let mut values: Vec<f64> = (1..=19).map(|v| v as f64).collect();
+        values.push(1000.0);
+
+        // 5% of 20 values drops exactly the outlier
+        let mean = ResponsivenessTester::trimmed_mean(&mut values, 0.05);
+        assert!((mean - 10.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_stability_detection() {
+        assert!(!ResponsivenessTester::is_stable(&[100.0, 100.0, 100.0], 4, 0.05));
+        assert!(!ResponsivenessTester::is_stable(&[50.0, 100.0, 150.0, 200.0], 4, 0.05));
+        assert!(ResponsivenessTester::is_stable(&[10.0, 199.0, 200.0, 201.0, 200.0], 4, 0.05));
+    }
+
+    #[test]
+    fn test_rpm_weights_foreign_and_self_equally() {
+        // Foreign: (10 + 30) / 2 = 20ms, self: 100ms → 60ms round trip
+        let rpm = ResponsivenessTester::calculate_rpm(10.0, 30.0, 100.0);
+        assert!((rpm - 1000.0).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runs_are_capped() {
+        let busy = RUNS.acquire_many(MAX_CONCURRENT_RUNS as u32).await.unwrap();
+        let started = Instant::now();
+        let err = ResponsivenessTester::new().run("127.0.0.1:9", LoadDirection::Download).await.unwrap_err();
+        assert!(err.to_string().contains("already running"));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        drop(busy);
+        assert_eq!(RUNS.available_permits(), MAX_CONCURRENT_RUNS);
+    }
+}
@@ -0,0 +1,277 @@
+//! Rule-Based Network Insights
+//!
+//! Builds `AIInsights` from the recommendations the latency, packet loss and
+//! AIM scoring already produce, without a language model. Deterministic and
+//! offline, so air-gapped deployments still get insights and LLM failures
+//! have something to fall back to. Texts come from the message catalog, so
+//! the insights can be rebuilt in any supported locale.
+
+use async_trait::async_trait;
+
+use crate::models::TestResult;
+use crate::services::ai_insights::{AIInsights, AIRecommendation, Difficulty, InsightProvider, Priority};
+use crate::services::aim_scoring::{AIMScores, QualityGrade};
+use crate::services::household_capacity::{Activity, CapacityCalculator, CapacityLimit};
+use crate::services::i18n::{Locale, Message};
+use crate::services::loaded_latency::{BufferbloatGrade, LoadedLatencyResult};
+use crate::services::packet_loss::PacketLossGrade;
+
+/// At most this many recommendations, most urgent first
+const MAX_RECOMMENDATIONS: usize = 5;
+
+pub struct RuleBasedInsights;
+
+#[async_trait(?Send)]
+impl InsightProvider for RuleBasedInsights {
+    fn name(&self) -> &str {
+        "rules"
+    }
+
+    async fn generate(
+        &self,
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        aim_scores: &AIMScores,
+    ) -> Result<AIInsights, Box<dyn std::error::Error>> {
+        Ok(Self::analyze(test_result, loaded_latency, aim_scores))
+    }
+}
+
+impl RuleBasedInsights {
+    pub fn analyze(
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        aim_scores: &AIMScores,
+    ) -> AIInsights {
+        Self::analyze_in(test_result, loaded_latency, aim_scores, Locale::En)
+    }
+
+    /// Insights with every text rendered from the catalog in `locale`
+    pub fn analyze_in(
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        aim_scores: &AIMScores,
+        locale: Locale,
+    ) -> AIInsights {
+        let grade = loaded_latency.bufferbloat_grade;
+        let summary = t(locale, "insights.summary", &[
+            ("score", format!("{:.0}", aim_scores.overall_score)),
+            ("grade", t(locale, &format!("grade.{}", aim_scores.overall_grade.key()), &[])),
+            ("download_mbps", format!("{:.1}", test_result.download_mbps)),
+            ("upload_mbps", format!("{:.1}", test_result.upload_mbps)),
+            ("bufferbloat_grade", grade.as_str().to_string()),
+            ("bufferbloat", grade.description().render(locale)),
+        ]);
+
+        let mut recommendations = Self::recommendations(loaded_latency, aim_scores, locale);
+        recommendations.sort_by_key(|rec| rec.priority);
+        recommendations.truncate(MAX_RECOMMENDATIONS);
+
+        let mut predictions: Vec<String> = aim_scores.use_cases.iter()
+            .filter(|score| matches!(score.grade, QualityGrade::Fair | QualityGrade::Poor | QualityGrade::VeryPoor))
+            .map(|score| t(locale, "insights.prediction", &[
+                ("use_case", score.use_case.localized_label(locale)),
+                ("assessment", score.assessment.render(locale)),
+            ]))
+            .collect();
+        if predictions.is_empty() {
+            predictions.push(t(locale, "insights.no_issues_expected", &[]));
+        }
+
+        let detailed_analysis = format!(
+            "{}\n\n{}",
+            t(locale, "insights.latency", &[
+                ("idle_ms", format!("{:.0}", loaded_latency.idle_avg_ms)),
+                ("download_ms", format!("{:.0}", loaded_latency.download_avg_ms)),
+                ("upload_ms", format!("{:.0}", loaded_latency.upload_avg_ms)),
+            ]),
+            aim_scores.summary_in(locale)
+        );
+
+        AIInsights {
+            summary,
+            detailed_analysis,
+            recommendations,
+            predictions,
+            simple_explanation: Self::simple_explanation(test_result, loaded_latency, aim_scores, locale),
+            provider: "rules".to_string(),
+        }
+    }
+
+    /// What the link carries at once (for measured speeds), and what it
+    /// suits best and worst
+    fn simple_explanation(
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        aim_scores: &AIMScores,
+        locale: Locale,
+    ) -> String {
+        let mut sentences = Vec::new();
+        // Capacity from placeholder speeds would be made up
+        if test_result.measured {
+            let capacity = CapacityCalculator::estimate(test_result, loaded_latency);
+            let sessions = |activity: Activity| capacity.activities.iter()
+                .find(|a| a.activity == activity)
+                .map_or(0, |a| a.max_sessions)
+                .to_string();
+            sentences.push(t(locale, "insights.capacity", &[
+                ("streams_4k", sessions(Activity::Stream4k)),
+                ("video_calls", sessions(Activity::HdVideoCall)),
+                ("gaming_sessions", sessions(Activity::Gaming)),
+            ]));
+            if capacity.activities.iter().any(|a| a.limited_by == CapacityLimit::Latency) {
+                sentences.push(t(locale, "insights.capacity_latency_limited", &[]));
+            }
+        }
+
+        let mut ranked: Vec<_> = aim_scores.use_cases.iter().collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        if let (Some(best), Some(worst)) = (ranked.first(), ranked.last()) {
+            if best.use_case != worst.use_case {
+                sentences.push(t(locale, "insights.best_and_worst", &[
+                    ("best", best.use_case.localized_label(locale).to_lowercase()),
+                    ("worst", worst.use_case.localized_label(locale).to_lowercase()),
+                ]));
+            }
+        }
+        sentences.join(" ")
+    }
+
+    fn recommendations(loaded_latency: &LoadedLatencyResult, aim_scores: &AIMScores, locale: Locale) -> Vec<AIRecommendation> {
+        // Title and expected improvement come from `insights.recommendation.<name>_*`
+        let recommendation = |priority, name: &str, params: &[(&str, String)], description: String, difficulty| AIRecommendation {
+            priority,
+            title: t(locale, &format!("insights.recommendation.{}_title", name), params),
+            description,
+            expected_improvement: t(locale, &format!("insights.recommendation.{}_improvement", name), params),
+            difficulty,
+        };
+        let mut recommendations = Vec::new();
+
+        let bufferbloat_priority = match loaded_latency.bufferbloat_grade {
+            BufferbloatGrade::APlus | BufferbloatGrade::A | BufferbloatGrade::B | BufferbloatGrade::Ungraded => None,
+            BufferbloatGrade::C => Some(Priority::Medium),
+            BufferbloatGrade::D => Some(Priority::High),
+            BufferbloatGrade::F => Some(Priority::Critical),
+        };
+        if let Some(priority) = bufferbloat_priority {
+            let advice: Vec<String> = loaded_latency.recommendations().iter().map(|m| m.render(locale)).collect();
+            recommendations.push(recommendation(
+                priority,
+                "sqm",
+                &[("idle_ms", format!("{:.0}", loaded_latency.idle_avg_ms))],
+                advice.join(" "),
+                Difficulty::Medium,
+            ));
+        }
+
+        if let Some(loss) = &loaded_latency.idle_packet_loss {
+            let priority = match loss.grade {
+                PacketLossGrade::Excellent | PacketLossGrade::Good => None,
+                PacketLossGrade::Fair => Some(Priority::High),
+                PacketLossGrade::Poor => Some(Priority::Critical),
+            };
+            if let Some(priority) = priority {
+                recommendations.push(recommendation(
+                    priority,
+                    "packet_loss",
+                    &[("loss_pct", format!("{:.2}", loss.loss_percentage))],
+                    loss.recommendation().render(locale),
+                    Difficulty::Easy,
+                ));
+            }
+        }
+
+        for score in &aim_scores.use_cases {
+            let priority = match score.grade {
+                QualityGrade::VeryPoor => Priority::High,
+                QualityGrade::Poor => Priority::Medium,
+                _ => continue,
+            };
+            if score.recommendations.is_empty() {
+                continue;
+            }
+            let advice: Vec<String> = score.recommendations.iter().map(|m| m.render(locale)).collect();
+            recommendations.push(recommendation(
+                priority,
+                "use_case",
+                &[
+                    ("use_case", score.use_case.localized_label(locale)),
+                    ("score", format!("{:.0}", score.score)),
+                ],
+                advice.join(". "),
+                Difficulty::Medium,
+            ));
+        }
+
+        let caveats = loaded_latency.caveats();
+        if !caveats.is_empty() {
+            let caveats: Vec<String> = caveats.iter().map(|m| m.render(locale)).collect();
+            recommendations.push(recommendation(
+                Priority::Low,
+                "rerun",
+                &[],
+                t(locale, "insights.recommendation.rerun_description", &[("caveats", caveats.join(". "))]),
+                Difficulty::Easy,
+            ));
+        }
+
+        if recommendations.is_empty() {
+            recommendations.push(recommendation(
+                Priority::Low,
+                "none",
+                &[],
+                t(locale, "insights.recommendation.none_description", &[]),
+                Difficulty::Easy,
+            ));
+        }
+
+        recommendations
+    }
+}
+
+fn t(locale: Locale, key: &str, params: &[(&str, String)]) -> String {
+    Message::new(key, params).render(locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::aim_scoring::AIMCalculator;
+    use crate::services::loaded_latency::LoadedLatencyTester;
+
+    #[test]
+    fn test_bufferbloat_drives_recommendations() {
+        let mut test_result = TestResult::new("test".to_string(), "127.0.0.1".to_string());
+        test_result.download_mbps = 200.0;
+        test_result.upload_mbps = 20.0;
+
+        let mut loaded_latency = LoadedLatencyTester::new().calculate_results();
+        let clean = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+        let insights = RuleBasedInsights::analyze(&test_result, &loaded_latency, &clean);
+        assert_eq!(insights.provider, "rules");
+        assert!(insights.recommendations.iter().all(|rec| rec.title != "Enable Smart Queue Management (SQM)"));
+
+        loaded_latency.bufferbloat_grade = BufferbloatGrade::F;
+        let insights = RuleBasedInsights::analyze(&test_result, &loaded_latency, &clean);
+        assert_eq!(insights.recommendations[0].priority, Priority::Critical);
+        assert_eq!(insights.recommendations[0].title, "Enable Smart Queue Management (SQM)");
+        assert!(insights.recommendations.len() <= MAX_RECOMMENDATIONS);
+        assert!(insights.recommendations.windows(2).all(|pair| pair[0].priority <= pair[1].priority));
+
+        // Same inputs, same insights
+        assert_eq!(
+            serde_json::to_string(&insights).unwrap(),
+            serde_json::to_string(&RuleBasedInsights::analyze(&test_result, &loaded_latency, &clean)).unwrap()
+        );
+
+        let hindi = RuleBasedInsights::analyze_in(&test_result, &loaded_latency, &clean, Locale::Hi);
+        assert_eq!(hindi.recommendations[0].title, "Smart Queue Management (SQM) चालू करें");
+        assert!(hindi.summary.starts_with("कुल क्वालिटी"));
+        assert!(!hindi.simple_explanation.contains('{'));
+
+        test_result.measured = false;
+        let unmeasured = RuleBasedInsights::analyze(&test_result, &loaded_latency, &clean);
+        assert!(!unmeasured.simple_explanation.contains("4K streams"));
+    }
+}
@@ -0,0 +1,376 @@
+//! AIM Scoring Profiles
+//!
+//! The weights and breakpoints behind every use-case score. Each use case is
+//! a list of factors; a factor reads one metric and awards the points of the
+//! first band the value falls into, along with that band's capabilities and
+//! recommendations. A use-case score is the points earned as a share of the
+//! points possible, so weights need not add up to 100. Only the use cases a
+//! profile lists are scored.
+//!
+//! The built-in profile (`scoring_profiles/default.toml`) is the current
+//! scoring version; the original scoring is kept, frozen, as
+//! `scoring_profiles/aim-v1.toml`. Deployments point `SCORING_PROFILE` at a
+//! TOML or JSON file of the same shape to set different expectations.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::services::aim_scoring::UseCase;
+use crate::services::i18n::Message;
+
+const DEFAULT_PROFILE: &str = include_str!("../../scoring_profiles/default.toml");
+
+static DEFAULT: Lazy<ScoringProfile> = Lazy::new(|| {
+    ScoringProfile::from_toml(DEFAULT_PROFILE).expect("built-in scoring profile is valid")
+});
+
+/// A measured quantity a factor can score
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    DownloadMbps,
+    UploadMbps,
+    IdleLatencyMs,
+    DownloadLoadedLatencyMs,
+    UploadLoadedLatencyMs,
+    /// The worse of download and upload loaded latency
+    WorstLoadedLatencyMs,
+    /// RFC 3550 jitter of the idle probes
+    JitterMs,
+    /// Idle UDP loss
+    PacketLossPct,
+    /// Worst of idle and download-stage loss
+    DownloadPacketLossPct,
+    /// Worst of idle and upload-stage loss
+    UploadPacketLossPct,
+    /// Worst of idle and both loaded stages
+    WorstPacketLossPct,
+    /// Robust variation of the saturated download goodput, in percent
+    DownloadVariationPct,
+    UploadVariationPct,
+    /// 0 clean, 1 drops only, 2 one stall under 1 s, 3 stalls under 2 s, 4 longer
+    DownloadStallSeverity,
+    UploadStallSeverity,
+    /// Latency probes that never came back, across all stages, in percent
+    ProbeTimeoutPct,
+}
+
+impl Metric {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Metric::DownloadMbps => "download_mbps",
+            Metric::UploadMbps => "upload_mbps",
+            Metric::IdleLatencyMs => "idle_latency_ms",
+            Metric::DownloadLoadedLatencyMs => "download_loaded_latency_ms",
+            Metric::UploadLoadedLatencyMs => "upload_loaded_latency_ms",
+            Metric::WorstLoadedLatencyMs => "worst_loaded_latency_ms",
+            Metric::JitterMs => "jitter_ms",
+            Metric::PacketLossPct => "packet_loss_pct",
+            Metric::DownloadPacketLossPct => "download_packet_loss_pct",
+            Metric::UploadPacketLossPct => "upload_packet_loss_pct",
+            Metric::WorstPacketLossPct => "worst_packet_loss_pct",
+            Metric::DownloadVariationPct => "download_variation_pct",
+            Metric::UploadVariationPct => "upload_variation_pct",
+            Metric::DownloadStallSeverity => "download_stall_severity",
+            Metric::UploadStallSeverity => "upload_stall_severity",
+            Metric::ProbeTimeoutPct => "probe_timeout_pct",
+        }
+    }
+
+    pub fn is_packet_loss(&self) -> bool {
+        matches!(
+            self,
+            Metric::PacketLossPct
+                | Metric::DownloadPacketLossPct
+                | Metric::UploadPacketLossPct
+                | Metric::WorstPacketLossPct
+        )
+    }
+
+    /// Display name for breakdowns, e.g. "Worst loaded latency"
+    pub fn label(&self) -> &str {
+        match self {
+            Metric::DownloadMbps => "Download speed",
+            Metric::UploadMbps => "Upload speed",
+            Metric::IdleLatencyMs => "Idle latency",
+            Metric::DownloadLoadedLatencyMs => "Download loaded latency",
+            Metric::UploadLoadedLatencyMs => "Upload loaded latency",
+            Metric::WorstLoadedLatencyMs => "Worst loaded latency",
+            Metric::JitterMs => "Jitter",
+            Metric::PacketLossPct => "Packet loss",
+            Metric::DownloadPacketLossPct => "Packet loss while downloading",
+            Metric::UploadPacketLossPct => "Packet loss while uploading",
+            Metric::WorstPacketLossPct => "Worst packet loss",
+            Metric::DownloadVariationPct => "Download speed variation",
+            Metric::UploadVariationPct => "Upload speed variation",
+            Metric::DownloadStallSeverity => "Download stalls",
+            Metric::UploadStallSeverity => "Upload stalls",
+            Metric::ProbeTimeoutPct => "Probe timeouts",
+        }
+    }
+
+    pub fn unit(&self) -> &str {
+        match self {
+            Metric::DownloadMbps | Metric::UploadMbps => "Mbps",
+            Metric::IdleLatencyMs
+            | Metric::DownloadLoadedLatencyMs
+            | Metric::UploadLoadedLatencyMs
+            | Metric::WorstLoadedLatencyMs
+            | Metric::JitterMs => "ms",
+            Metric::DownloadStallSeverity | Metric::UploadStallSeverity => "",
+            _ => "%",
+        }
+    }
+
+    /// How `{value}` is rendered in band texts
+    pub fn format_value(&self, value: f64) -> String {
+        match self {
+            Metric::DownloadMbps | Metric::UploadMbps => format!("{:.1}", value),
+            m if m.is_packet_loss() => format!("{:.1}", value),
+            Metric::ProbeTimeoutPct => format!("{:.1}", value),
+            Metric::DownloadVariationPct | Metric::UploadVariationPct => format!("{:.0}", value),
+            _ => format!("{}", value as u32),
+        }
+    }
+
+    /// Caveat added to a score when the metric was not measured
+    pub fn missing_caveat(&self) -> Option<Message> {
+        let key = match self {
+            m if m.is_packet_loss() => "caveat.packet_loss_unmeasured",
+            Metric::DownloadVariationPct => "caveat.download_consistency_unmeasured",
+            Metric::UploadVariationPct => "caveat.upload_consistency_unmeasured",
+            _ => return None,
+        };
+        Some(Message::new(key, &[]))
+    }
+}
+
+/// One breakpoint range of a factor; a band with neither bound matches anything
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScoreBand {
+    /// Matches values strictly below this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+    /// Matches values at or above this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at_least: Option<f64>,
+    pub points: f64,
+    /// Texts may use `{value}` and metric-specific placeholders
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recommendations: Vec<String>,
+}
+
+impl ScoreBand {
+    pub fn matches(&self, value: f64) -> bool {
+        self.below.is_none_or(|limit| value < limit) && self.at_least.is_none_or(|limit| value >= limit)
+    }
+
+    fn is_catch_all(&self) -> bool {
+        self.below.is_none() && self.at_least.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FactorProfile {
+    pub metric: Metric,
+    /// Points possible
+    pub weight: f64,
+    /// Points when the metric was not measured; without it an unmeasured
+    /// factor is left out of the points possible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unmeasured_points: Option<f64>,
+    /// Checked in order; the first match wins and the last must match anything
+    pub bands: Vec<ScoreBand>,
+}
+
+impl FactorProfile {
+    pub fn band(&self, value: f64) -> Option<&ScoreBand> {
+        self.bands.iter().find(|band| band.matches(value))
+    }
+
+    /// Range a band covers given the bands before it, e.g. "20–50 ms"
+    pub fn describe_band(&self, index: usize) -> String {
+        let band = &self.bands[index];
+        let previous = index.checked_sub(1).map(|i| &self.bands[i]);
+        let lower = band.at_least.or(previous.and_then(|p| p.below));
+        let upper = band.below.or(previous.and_then(|p| p.at_least));
+
+        let range = match (lower, upper) {
+            (Some(lower), Some(upper)) => format!("{}–{}", lower, upper),
+            (Some(lower), None) => format!("≥ {}", lower),
+            (None, Some(upper)) => format!("< {}", upper),
+            (None, None) => return "any".to_string(),
+        };
+        match self.metric.unit() {
+            "" => range,
+            "%" => format!("{}%", range),
+            unit => format!("{} {}", range, unit),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UseCaseProfile {
+    pub factors: Vec<FactorProfile>,
+}
+
+impl UseCaseProfile {
+    pub fn points_possible(&self) -> f64 {
+        self.factors.iter().map(|f| f.weight).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScoringProfile {
+    pub name: String,
+    /// Scoring algorithm version stamped on every score, e.g. "aim-v3"
+    pub version: String,
+    /// Relative weight of each use case in the overall score
+    pub overall_weights: BTreeMap<UseCase, f64>,
+    /// The use cases to score, reported in `UseCase` order
+    pub use_cases: BTreeMap<UseCase, UseCaseProfile>,
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        DEFAULT.clone()
+    }
+}
+
+impl ScoringProfile {
+    /// The built-in profile, without cloning
+    pub fn builtin() -> &'static ScoringProfile {
+        &DEFAULT
+    }
+
+    /// Load a profile file (`.json`, anything else is read as TOML), or the
+    /// built-in profile when no path is given
+    pub fn load(path: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read scoring profile {}: {}", path, e))?;
+        let profile = if Path::new(path).extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&contents)?
+        } else {
+            Self::from_toml(&contents)?
+        };
+
+        log::info!("📐 Loaded scoring profile '{}' ({}) from {}", profile.name, profile.version, path);
+        Ok(profile)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let profile: Self = toml::from_str(contents)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let profile: Self = serde_json::from_str(contents)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Weight of a use case in the overall score; unlisted use cases count zero
+    pub fn overall_weight(&self, use_case: UseCase) -> f64 {
+        self.overall_weights.get(&use_case).copied().unwrap_or(0.0)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version.trim().is_empty() {
+            return Err("version: must not be empty".to_string());
+        }
+        if self.use_cases.is_empty() {
+            return Err("use_cases: at least one use case is required".to_string());
+        }
+        for (use_case, profile) in &self.use_cases {
+            let name = use_case.as_str();
+            if profile.factors.is_empty() {
+                return Err(format!("{}: no factors", name));
+            }
+            for factor in &profile.factors {
+                let label = format!("{}.{}", name, factor.metric.as_str());
+                if factor.weight <= 0.0 {
+                    return Err(format!("{}: weight must be positive", label));
+                }
+                if !factor.bands.last().is_some_and(ScoreBand::is_catch_all) {
+                    return Err(format!("{}: the last band must have no bounds", label));
+                }
+                let points = factor.bands.iter().map(|b| b.points).chain(factor.unmeasured_points);
+                for points in points {
+                    if !(0.0..=factor.weight).contains(&points) {
+                        return Err(format!("{}: points {} outside 0..={}", label, points, factor.weight));
+                    }
+                }
+            }
+        }
+
+        for (use_case, weight) in &self.overall_weights {
+            if !self.use_cases.contains_key(use_case) {
+                return Err(format!("overall_weights: {} is not scored", use_case.as_str()));
+            }
+            if *weight < 0.0 {
+                return Err(format!("overall_weights.{}: must not be negative", use_case.as_str()));
+            }
+        }
+        if self.overall_weights.values().sum::<f64>() <= 0.0 {
+            return Err("overall_weights: at least one use case needs a positive weight".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profile_and_json_round_trip() {
+        let profile = ScoringProfile::builtin();
+        assert_eq!(profile.use_cases.len(), UseCase::ALL.len());
+        for use_case in profile.use_cases.values() {
+            assert_eq!(use_case.points_possible(), 100.0);
+        }
+        assert_eq!(profile.overall_weight(UseCase::Gaming), 0.25);
+        assert_eq!(profile.overall_weight(UseCase::CloudBackup), 0.0);
+
+        let latency = &profile.use_cases[&UseCase::Gaming].factors[0];
+        assert_eq!(latency.describe_band(0), "< 20 ms");
+        assert_eq!(latency.describe_band(1), "20–50 ms");
+        assert_eq!(latency.describe_band(5), "≥ 150 ms");
+        let speed = &profile.use_cases[&UseCase::Streaming].factors[0];
+        assert_eq!(speed.describe_band(1), "50–100 Mbps");
+        assert_eq!(speed.describe_band(6), "< 5 Mbps");
+
+        let json = serde_json::to_string(profile).unwrap();
+        assert_eq!(&ScoringProfile::from_json(&json).unwrap(), profile);
+    }
+
+    #[test]
+    fn test_invalid_profiles_are_rejected() {
+        let mut profile = ScoringProfile::default();
+        profile.use_cases.get_mut(&UseCase::Gaming).unwrap().factors[0].bands.pop();
+        assert!(profile.validate().unwrap_err().contains("last band"));
+
+        let mut profile = ScoringProfile::default();
+        profile.use_cases.get_mut(&UseCase::Streaming).unwrap().factors[0].bands[0].points = 500.0;
+        assert!(profile.validate().is_err());
+
+        let mut profile = ScoringProfile::default();
+        profile.use_cases.remove(&UseCase::SmartHome);
+        profile.overall_weights.insert(UseCase::SmartHome, 1.0);
+        assert!(profile.validate().unwrap_err().contains("not scored"));
+
+        let unknown = DEFAULT_PROFILE.replace("[overall_weights]", "[overall_weights]\nkaraoke = 1");
+        assert!(ScoringProfile::from_toml(&unknown).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Scoring Algorithm Versions
+//!
+//! Every stored AIM score carries the version of the scoring profile that
+//! produced it, and the raw measurements behind it are stored next to it, so
+//! any historical test can be re-scored with any supported version and shown
+//! side by side with the score it was originally given.
+//!
+//! Built-in versions are frozen profile files under `scoring_profiles/`.
+//! Changing thresholds means adding a new version, never editing an old one.
+
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::models::TestResult;
+use crate::services::aim_scoring::{AIMCalculator, AIMScores, UseCase};
+use crate::services::loaded_latency::LoadedLatencyResult;
+use crate::services::scoring_profile::ScoringProfile;
+use crate::services::usage_priorities::OverallWeights;
+
+const FROZEN_PROFILES: [(&str, &str); 3] = [
+    ("aim-v1", include_str!("../../scoring_profiles/aim-v1.toml")),
+    ("aim-v2", include_str!("../../scoring_profiles/aim-v2.toml")),
+    ("aim-v3", include_str!("../../scoring_profiles/aim-v3.toml")),
+];
+
+/// Frozen built-in versions, oldest first. The editable default profile must
+/// match the last of them (a test checks), so editing it cannot change what
+/// stored scores of a built-in version mean.
+static BUILTIN: Lazy<Vec<ScoringProfile>> = Lazy::new(|| {
+    FROZEN_PROFILES.iter()
+        .map(|(version, toml)| {
+            ScoringProfile::from_toml(toml)
+                .unwrap_or_else(|e| panic!("built-in {} profile is invalid: {}", version, e))
+        })
+        .collect()
+});
+
+/// The scoring versions this server can score with
+#[derive(Debug, Clone)]
+pub struct ScoringVersions {
+    profiles: BTreeMap<String, ScoringProfile>,
+    active: String,
+}
+
+/// A supported version, as listed by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoringVersionInfo {
+    pub version: String,
+    pub profile_name: String,
+    pub use_cases: Vec<UseCase>,
+    /// Whether new tests are scored with this version
+    pub active: bool,
+}
+
+impl Default for ScoringVersions {
+    fn default() -> Self {
+        Self::new(ScoringProfile::default()).expect("built-in profile is a built-in version")
+    }
+}
+
+impl ScoringVersions {
+    /// The built-in versions plus `active`, which scores new tests. A custom
+    /// profile may not reuse a built-in version with different contents.
+    pub fn new(active: ScoringProfile) -> Result<Self, String> {
+        let mut profiles: BTreeMap<String, ScoringProfile> = BUILTIN.iter()
+            .map(|profile| (profile.version.clone(), profile.clone()))
+            .collect();
+
+        if let Some(builtin) = profiles.get(&active.version) {
+            if *builtin != active {
+                return Err(format!(
+                    "version {}: reserved for the built-in profile; give the custom profile its own version",
+                    active.version
+                ));
+            }
+        }
+
+        let version = active.version.clone();
+        profiles.insert(version.clone(), active);
+        Ok(Self { profiles, active: version })
+    }
+
+    pub fn active(&self) -> &ScoringProfile {
+        &self.profiles[&self.active]
+    }
+
+    pub fn get(&self, version: &str) -> Option<&ScoringProfile> {
+        self.profiles.get(version)
+    }
+
+    pub fn versions(&self) -> Vec<ScoringVersionInfo> {
+        self.profiles.values()
+            .map(|profile| ScoringVersionInfo {
+                version: profile.version.clone(),
+                profile_name: profile.name.clone(),
+                use_cases: profile.use_cases.keys().copied().collect(),
+                active: profile.version == self.active,
+            })
+            .collect()
+    }
+}
+
+/// The raw measurements a test's AIM scores are computed from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreInputs {
+    pub test_result: TestResult,
+    pub loaded_latency: LoadedLatencyResult,
+    /// The household priorities the test was run with
+    #[serde(default)]
+    pub overall_weights: OverallWeights,
+}
+
+impl ScoreInputs {
+    /// Score these inputs with `profile`, keeping the test's priorities
+    pub fn score(&self, profile: &ScoringProfile) -> AIMScores {
+        let weights = self.overall_weights.for_profile(profile);
+        AIMCalculator::calculate_weighted(
+            profile,
+            &weights,
+            &self.test_result,
+            &self.loaded_latency,
+            self.loaded_latency.idle_packet_loss.as_ref(),
+        )
+    }
+}
+
+/// A historical test's stored scores next to a fresh re-score
+#[derive(Debug, Clone, Serialize)]
+pub struct RescoreComparison {
+    pub test_id: String,
+    /// Scores stored when the test ran; `None` if they were never persisted
+    pub original: Option<AIMScores>,
+    pub rescored: AIMScores,
+    /// Rescored minus original overall score
+    pub overall_change: Option<f64>,
+    pub use_case_changes: Vec<UseCaseChange>,
+}
+
+/// One use case's score under both versions
+#[derive(Debug, Clone, Serialize)]
+pub struct UseCaseChange {
+    pub use_case: UseCase,
+    /// `None` when the original version did not score this use case
+    pub original: Option<f64>,
+    /// `None` when the new version does not score this use case
+    pub rescored: Option<f64>,
+    pub change: Option<f64>,
+}
+
+impl RescoreComparison {
+    pub fn new(test_id: &str, original: Option<AIMScores>, rescored: AIMScores) -> Self {
+        let score_of = |scores: Option<&AIMScores>, use_case| {
+            scores.and_then(|s| s.get(use_case)).map(|score| score.score)
+        };
+
+        let use_case_changes = UseCase::ALL.into_iter()
+            .filter_map(|use_case| {
+                let before = score_of(original.as_ref(), use_case);
+                let after = score_of(Some(&rescored), use_case);
+                if before.is_none() && after.is_none() {
+                    return None;
+                }
+                Some(UseCaseChange {
+                    use_case,
+                    original: before,
+                    rescored: after,
+                    change: before.zip(after).map(|(before, after)| after - before),
+                })
+            })
+            .collect();
+
+        Self {
+            test_id: test_id.to_string(),
+            overall_change: original.as_ref().map(|o| rescored.overall_score - o.overall_score),
+            original,
+            rescored,
+            use_case_changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::loaded_latency::LoadedLatencyTester;
+
+    #[test]
+    fn test_rescoring_with_older_version_side_by_side() {
+        let versions = ScoringVersions::default();
+        assert_eq!(versions.active().version, "aim-v3");
+        assert_eq!(versions.versions().iter().map(|v| v.version.as_str()).collect::<Vec<_>>(), ["aim-v1", "aim-v2", "aim-v3"]);
+
+        let mut test_result = TestResult::new("test".to_string(), "127.0.0.1".to_string());
+        test_result.download_mbps = 150.0;
+        test_result.upload_mbps = 8.0;
+        let mut loaded_latency = LoadedLatencyTester::new().calculate_results();
+        loaded_latency.idle_avg_ms = 20.0;
+        loaded_latency.download_avg_ms = 45.0;
+        loaded_latency.upload_avg_ms = 90.0;
+        let inputs = ScoreInputs { test_result, loaded_latency, overall_weights: OverallWeights::default() };
+
+        // Inputs survive storage as JSON and score identically afterwards
+        let stored: ScoreInputs = serde_json::from_str(&serde_json::to_string(&inputs).unwrap()).unwrap();
+        let original = inputs.score(versions.active());
+        assert_eq!(stored.score(versions.active()).overall_score, original.overall_score);
+        assert_eq!(original.scoring_version, "aim-v3");
+
+        let rescored = stored.score(versions.get("aim-v1").unwrap());
+        assert_eq!(rescored.scoring_version, "aim-v1");
+        assert_eq!(rescored.use_cases.len(), 4);
+
+        // Packet loss was not measured: aim-v1 awards it anyway, aim-v3 leaves it out
+        let comparison = RescoreComparison::new("test", Some(original), rescored);
+        assert!(comparison.overall_change.unwrap() > 0.0);
+        let backup = comparison.use_case_changes.iter().find(|c| c.use_case == UseCase::CloudBackup).unwrap();
+        assert!(backup.original.is_some() && backup.rescored.is_none() && backup.change.is_none());
+        let gaming = comparison.use_case_changes.iter().find(|c| c.use_case == UseCase::Gaming).unwrap();
+        assert!(gaming.change.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_default_profile_is_the_latest_frozen_version() {
+        for ((version, _), profile) in FROZEN_PROFILES.iter().zip(BUILTIN.iter()) {
+            assert_eq!(profile.version, *version);
+        }
+
+        // Editing default.toml needs a new version, frozen as its own file
+        let default = ScoringProfile::builtin();
+        let latest = BUILTIN.last().unwrap();
+        assert_eq!(
+            default, latest,
+            "default.toml differs from the frozen {} profile; give it a new version and freeze that as scoring_profiles/<version>.toml",
+            latest.version
+        );
+    }
+
+    #[test]
+    fn test_custom_profile_cannot_claim_builtin_version() {
+        let mut custom = ScoringProfile::default();
+        custom.overall_weights.insert(UseCase::Gaming, 1.0);
+        assert!(ScoringVersions::new(custom.clone()).is_err());
+
+        custom.version = "isp-2026".to_string();
+        let versions = ScoringVersions::new(custom).unwrap();
+        assert_eq!(versions.active().version, "isp-2026");
+        assert!(versions.get("aim-v1").is_some());
+        assert_eq!(versions.versions().iter().filter(|v| v.active).count(), 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Throughput Stall and Dropout Detection
+//!
+//! A coefficient of variation cannot tell a steady wobble from a complete
+//! two-second stall, yet stalls are what empty a video buffer. This scans
+//! the fine-grained throughput series of a saturated transfer for episodes
+//! below a fraction of the typical (median) rate:
+//! - a stall when throughput stays near zero for at least `min_stall`
+//! - a drop when it stays well below normal for at least `min_drop`
+//!
+//! Each episode records when it started, how long it lasted and how long the
+//! transfer took to get back to normal afterwards.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Throughput over one sampling period
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ThroughputSample {
+    /// End of the sampling period, relative to the start of the transfer
+    pub offset_ms: u64,
+    pub mbps: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EpisodeKind {
+    Stall,
+    Drop,
+}
+
+impl EpisodeKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EpisodeKind::Stall => "Stall",
+            EpisodeKind::Drop => "Drop",
+        }
+    }
+
+    pub fn emoji(&self) -> &str {
+        match self {
+            EpisodeKind::Stall => "🛑",
+            EpisodeKind::Drop => "📉",
+        }
+    }
+}
+
+/// One stretch of abnormally low throughput
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThroughputEpisode {
+    pub kind: EpisodeKind,
+    pub started_at: DateTime<Utc>,
+    /// Start relative to the start of the transfer
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    /// Longest stretch below the stall threshold within the episode; stall
+    /// totals count only this, not the partial drop around it
+    #[serde(default)]
+    pub stall_ms: u64,
+    /// Lowest throughput seen during the episode
+    pub floor_mbps: f64,
+    /// Time from the end of the episode until throughput was back near the
+    /// baseline; `None` if it never got there before the transfer ended
+    pub recovery_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StallAnalysis {
+    /// Median throughput the episodes are measured against
+    pub baseline_mbps: f64,
+    pub stall_count: usize,
+    pub total_stall_ms: u64,
+    pub longest_stall_ms: u64,
+    pub drop_count: usize,
+    pub episodes: Vec<ThroughputEpisode>,
+    /// Length of the analysed series
+    pub duration_ms: u64,
+}
+
+impl StallAnalysis {
+    pub fn is_clean(&self) -> bool {
+        self.episodes.is_empty()
+    }
+}
+
+/// Thresholds, as fractions of the baseline throughput
+#[derive(Debug, Clone)]
+pub struct StallDetectorConfig {
+    /// Below this fraction the transfer counts as stalled
+    pub stall_ratio: f64,
+    pub min_stall: Duration,
+    /// Below this fraction an episode starts
+    pub drop_ratio: f64,
+    pub min_drop: Duration,
+    /// At or above this fraction the transfer has recovered
+    pub recovery_ratio: f64,
+}
+
+impl Default for StallDetectorConfig {
+    fn default() -> Self {
+        Self {
+            stall_ratio: 0.05,
+            min_stall: Duration::from_millis(500),
+            drop_ratio: 0.5,
+            min_drop: Duration::from_millis(300),
+            recovery_ratio: 0.8,
+        }
+    }
+}
+
+pub struct StallDetector {
+    config: StallDetectorConfig,
+}
+
+impl Default for StallDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StallDetector {
+    /// Samples needed before the median means anything
+    const MIN_SAMPLES: usize = 10;
+
+    pub fn new() -> Self {
+        Self::with_config(StallDetectorConfig::default())
+    }
+
+    pub fn with_config(config: StallDetectorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Find stall and drop episodes in a series that started at `started_at`
+    pub fn analyze(&self, series: &[ThroughputSample], started_at: DateTime<Utc>) -> Option<StallAnalysis> {
+        if series.len() < Self::MIN_SAMPLES {
+            return None;
+        }
+
+        let mut sorted: Vec<f64> = series.iter().map(|s| s.mbps).collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let baseline_mbps = sorted[sorted.len() / 2];
+        if baseline_mbps <= 0.0 {
+            return None;
+        }
+
+        let stall_below = baseline_mbps * self.config.stall_ratio;
+        let drop_below = baseline_mbps * self.config.drop_ratio;
+        let recovered_at = baseline_mbps * self.config.recovery_ratio;
+
+        // Sampling period, for the start of the first sample in a run
+        let period_ms = series.windows(2)
+            .map(|pair| pair[1].offset_ms.saturating_sub(pair[0].offset_ms))
+            .min()
+            .unwrap_or(0);
+
+        let mut episodes = Vec::new();
+        let mut i = 0;
+        while i < series.len() {
+            if series[i].mbps >= drop_below {
+                i += 1;
+                continue;
+            }
+
+            let first = i;
+            let mut longest_stall_ms = 0;
+            let mut stall_start: Option<u64> = None;
+            while i < series.len() && series[i].mbps < drop_below {
+                let sample_start = series[i].offset_ms.saturating_sub(period_ms);
+                if series[i].mbps < stall_below {
+                    let start = *stall_start.get_or_insert(sample_start);
+                    longest_stall_ms = longest_stall_ms.max(series[i].offset_ms - start);
+                } else {
+                    stall_start = None;
+                }
+                i += 1;
+            }
+
+            let start_ms = series[first].offset_ms.saturating_sub(period_ms);
+            let duration_ms = series[i - 1].offset_ms - start_ms;
+            let kind = if longest_stall_ms >= self.config.min_stall.as_millis() as u64 {
+                EpisodeKind::Stall
+            } else if duration_ms >= self.config.min_drop.as_millis() as u64 {
+                EpisodeKind::Drop
+            } else {
+                continue;
+            };
+
+            let end_ms = series[i - 1].offset_ms;
+            let recovery_ms = series[i..].iter()
+                .find(|s| s.mbps >= recovered_at)
+                .map(|s| s.offset_ms - end_ms);
+
+            episodes.push(ThroughputEpisode {
+                kind,
+                started_at: started_at + chrono::Duration::milliseconds(start_ms as i64),
+                start_ms,
+                duration_ms,
+                stall_ms: longest_stall_ms,
+                floor_mbps: series[first..i].iter().map(|s| s.mbps).fold(f64::INFINITY, f64::min),
+                recovery_ms,
+            });
+        }
+
+        let stalls: Vec<&ThroughputEpisode> = episodes.iter().filter(|e| e.kind == EpisodeKind::Stall).collect();
+        let analysis = StallAnalysis {
+            baseline_mbps,
+            stall_count: stalls.len(),
+            total_stall_ms: stalls.iter().map(|e| e.stall_ms).sum(),
+            longest_stall_ms: stalls.iter().map(|e| e.stall_ms).max().unwrap_or(0),
+            drop_count: episodes.len() - stalls.len(),
+            duration_ms: series.last().map(|s| s.offset_ms).unwrap_or(0)
+                - series[0].offset_ms.saturating_sub(period_ms),
+            episodes,
+        };
+
+        if !analysis.is_clean() {
+            log::info!("📉 Throughput: {} stalls ({} ms total), {} drops against a {:.1} Mbps baseline",
+                analysis.stall_count, analysis.total_stall_ms, analysis.drop_count, baseline_mbps);
+        }
+
+        Some(analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100ms samples at 100 Mbps, with the given (from, to) sample ranges replaced
+    fn series(len: usize, dips: &[(usize, usize, f64)]) -> Vec<ThroughputSample> {
+        (0..len)
+            .map(|i| {
+                let mbps = dips.iter()
+                    .find(|(from, to, _)| (*from..*to).contains(&i))
+                    .map(|(_, _, mbps)| *mbps)
+                    .unwrap_or(100.0);
+                ThroughputSample { offset_ms: (i as u64 + 1) * 100, mbps }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stall_has_timestamp_and_duration() {
+        let started_at = Utc::now();
+        // 2s at zero starting at 3s, then a slow 300ms climb back
+        let samples = series(80, &[(30, 50, 0.0), (50, 53, 60.0)]);
+        let analysis = StallDetector::new().analyze(&samples, started_at).unwrap();
+
+        assert_eq!(analysis.stall_count, 1);
+        assert_eq!(analysis.drop_count, 0);
+        let stall = &analysis.episodes[0];
+        assert_eq!(stall.start_ms, 3_000);
+        assert_eq!(stall.duration_ms, 2_000);
+        assert_eq!(stall.recovery_ms, Some(400));
+        assert_eq!(stall.started_at, started_at + chrono::Duration::milliseconds(3_000));
+
+        // Half-speed stretches on either side belong to the episode, but
+        // only the 1s at zero counts as stalled
+        let samples = series(80, &[(28, 30, 30.0), (30, 40, 0.0), (40, 45, 30.0)]);
+        let analysis = StallDetector::new().analyze(&samples, started_at).unwrap();
+        assert_eq!(analysis.episodes[0].duration_ms, 1_700);
+        assert_eq!(analysis.episodes[0].stall_ms, 1_000);
+        assert_eq!(analysis.total_stall_ms, 1_000);
+        assert_eq!(analysis.longest_stall_ms, 1_000);
+    }
+
+    #[test]
+    fn test_drops_and_noise() {
+        // A 500ms halving is a drop, a single 100ms dip is noise,
+        // and a dip that never recovers has no recovery time
+        let samples = series(60, &[(10, 15, 30.0), (30, 31, 0.0), (55, 60, 20.0)]);
+        let analysis = StallDetector::new().analyze(&samples, Utc::now()).unwrap();
+
+        assert_eq!(analysis.stall_count, 0);
+        assert_eq!(analysis.drop_count, 2);
+        assert_eq!(analysis.episodes[0].duration_ms, 500);
+        assert_eq!(analysis.episodes[0].recovery_ms, Some(100));
+        assert_eq!(analysis.episodes[1].recovery_ms, None);
+
+        assert!(StallDetector::new().analyze(&series(60, &[]), Utc::now()).unwrap().is_clean());
+    }
+}
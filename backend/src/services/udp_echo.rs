@@ -0,0 +1,311 @@
+//! UDP Echo Responder
+//!
+//! Echoes packet loss probes back to the client with the server receive
+//! timestamp. Probes must carry a test id issued by one of the start
+//! endpoints and the random secret issued with it, so knowing a test id
+//! is not enough to turn the responder into a reflector. Each session is
+//! pinned to the first client IP that uses it and rate limited, and at
+//! most `MAX_SESSIONS` are live at once.
+//!
+//! Wire format (ASCII, timestamps in Unix microseconds):
+//! - probe: `SPEEDTEST_PACKET:<test_id>:<secret>:<sequence>[:<client_send>]`
+//! - echo:  `SPEEDTEST_ECHO:<test_id>:<sequence>:<server_recv>:<server_send>[:<client_send>]`
+//!
+//! The client send time is echoed back so the client can compute one-way
+//! delays without keeping per-probe state.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use tokio::net::UdpSocket;
+
+const PROBE_PREFIX: &str = "SPEEDTEST_PACKET";
+const ECHO_PREFIX: &str = "SPEEDTEST_ECHO";
+
+/// Sessions expire this long after registration
+const SESSION_TTL: Duration = Duration::from_secs(120);
+/// Echoes per session per second; well above the 50 pps probe rate
+const MAX_PACKETS_PER_SECOND: u32 = 500;
+/// Live sessions at once; start endpoints are refused beyond this
+pub const MAX_SESSIONS: usize = 1024;
+
+/// A probe sent by the client
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchoProbe {
+    pub test_id: String,
+    /// Issued with the test id by the start endpoint
+    pub secret: String,
+    pub sequence: u64,
+    pub client_send_us: Option<i64>,
+}
+
+/// An echo returned by the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchoReply {
+    pub test_id: String,
+    pub sequence: u64,
+    pub server_recv_us: i64,
+    pub server_send_us: i64,
+    pub client_send_us: Option<i64>,
+}
+
+impl EchoProbe {
+    pub fn encode(&self) -> String {
+        let mut packet = format!("{}:{}:{}:{}", PROBE_PREFIX, self.test_id, self.secret, self.sequence);
+        if let Some(sent) = self.client_send_us {
+            packet.push_str(&format!(":{}", sent));
+        }
+        packet
+    }
+
+    pub fn decode(packet: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(packet).ok()?;
+        let mut parts = text.splitn(5, ':');
+        if parts.next()? != PROBE_PREFIX {
+            return None;
+        }
+        let test_id = parts.next().filter(|id| !id.is_empty())?.to_string();
+        let secret = parts.next().filter(|secret| !secret.is_empty())?.to_string();
+        let sequence = parts.next()?.parse().ok()?;
+        let client_send_us = match parts.next() {
+            Some(sent) => Some(sent.parse().ok()?),
+            None => None,
+        };
+
+        Some(Self { test_id, secret, sequence, client_send_us })
+    }
+}
+
+impl EchoReply {
+    pub fn encode(&self) -> String {
+        let mut packet = format!(
+            "{}:{}:{}:{}:{}",
+            ECHO_PREFIX, self.test_id, self.sequence, self.server_recv_us, self.server_send_us
+        );
+        if let Some(sent) = self.client_send_us {
+            packet.push_str(&format!(":{}", sent));
+        }
+        packet
+    }
+
+    pub fn decode(packet: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(packet).ok()?;
+        let mut parts = text.splitn(6, ':');
+        if parts.next()? != ECHO_PREFIX {
+            return None;
+        }
+        let test_id = parts.next()?.to_string();
+        let sequence = parts.next()?.parse().ok()?;
+        let server_recv_us = parts.next()?.parse().ok()?;
+        let server_send_us = parts.next()?.parse().ok()?;
+        let client_send_us = match parts.next() {
+            Some(sent) => Some(sent.parse().ok()?),
+            None => None,
+        };
+
+        Some(Self { test_id, sequence, server_recv_us, server_send_us, client_send_us })
+    }
+}
+
+#[derive(Debug)]
+struct UdpSession {
+    created_at: Instant,
+    secret: String,
+    /// Bound on the first probe
+    client_ip: Option<IpAddr>,
+    window_start: Instant,
+    window_packets: u32,
+}
+
+/// Test ids that may use the echo responder, shared with the HTTP handlers
+#[derive(Debug, Clone, Default)]
+pub struct UdpSessionRegistry {
+    sessions: Arc<Mutex<HashMap<String, UdpSession>>>,
+}
+
+impl UdpSessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow probes for a test id and return the secret they must carry; an
+    /// existing session keeps its secret and binding. Fails when
+    /// `MAX_SESSIONS` sessions are live.
+    pub fn register(&self, test_id: &str) -> Result<String, String> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, session| now.duration_since(session.created_at) < SESSION_TTL);
+        if let Some(session) = sessions.get(test_id) {
+            return Ok(session.secret.clone());
+        }
+        if sessions.len() >= MAX_SESSIONS {
+            return Err(format!("{} UDP echo sessions already live; try again later", MAX_SESSIONS));
+        }
+        
+        let secret = format!("{:032x}", rand::random::<u128>());
+        sessions.insert(test_id.to_string(), UdpSession {
+            created_at: now,
+            secret: secret.clone(),
+            client_ip: None,
+            window_start: now,
+            window_packets: 0,
+        });
+        Ok(secret)
+    }
+
+    /// Whether a start endpoint registered `test_id` and it has not expired
+    pub fn is_registered(&self, test_id: &str) -> bool {
+        self.sessions.lock().unwrap()
+            .get(test_id)
+            .is_some_and(|session| session.created_at.elapsed() < SESSION_TTL)
+    }
+
+    /// End a session once its test finishes
+    pub fn remove(&self, test_id: &str) {
+        self.sessions.lock().unwrap().remove(test_id);
+    }
+
+    /// Whether a probe from `source` for `test_id` should be echoed
+    fn admit(&self, test_id: &str, secret: &str, source: IpAddr, now: Instant) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(test_id) else {
+            return false;
+        };
+
+        if now.duration_since(session.created_at) >= SESSION_TTL {
+            sessions.remove(test_id);
+            return false;
+        }
+        if session.secret != secret {
+            return false;
+        }
+
+        match session.client_ip {
+            Some(ip) if ip != source => return false,
+            Some(_) => {}
+            None => session.client_ip = Some(source),
+        }
+
+        if now.duration_since(session.window_start) >= Duration::from_secs(1) {
+            session.window_start = now;
+            session.window_packets = 0;
+        }
+        session.window_packets += 1;
+
+        session.window_packets <= MAX_PACKETS_PER_SECOND
+    }
+}
+
+pub struct UdpEchoServer {
+    socket: UdpSocket,
+    registry: UdpSessionRegistry,
+}
+
+impl UdpEchoServer {
+    pub async fn bind(addr: &str, registry: UdpSessionRegistry) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(addr).await?;
+        Ok(Self { socket, registry })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Echo admitted probes until the socket fails
+    pub async fn run(self) -> std::io::Result<()> {
+        let mut buf = [0u8; 1500];
+
+        loop {
+            let (len, source) = self.socket.recv_from(&mut buf).await?;
+            let server_recv_us = Utc::now().timestamp_micros();
+
+            let Some(probe) = EchoProbe::decode(&buf[..len]) else {
+                continue;
+            };
+            if !self.registry.admit(&probe.test_id, &probe.secret, source.ip(), Instant::now()) {
+                log::debug!("Dropped UDP probe for unknown or foreign session from {}", source);
+                continue;
+            }
+
+            let reply = EchoReply {
+                test_id: probe.test_id,
+                sequence: probe.sequence,
+                server_recv_us,
+                server_send_us: Utc::now().timestamp_micros(),
+                client_send_us: probe.client_send_us,
+            };
+            if let Err(e) = self.socket.send_to(reply.encode().as_bytes(), source).await {
+                log::warn!("Failed to echo UDP probe to {}: {}", source, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_round_trip() {
+        let probe = EchoProbe { test_id: "abc-123".to_string(), secret: "s3cr3t".to_string(), sequence: 42, client_send_us: None };
+        assert_eq!(EchoProbe::decode(probe.encode().as_bytes()), Some(probe));
+        assert_eq!(EchoProbe::decode(b"SPEEDTEST_PACKET:42"), None);
+        // The pre-secret format no longer parses
+        assert_eq!(EchoProbe::decode(b"SPEEDTEST_PACKET:abc-123:42"), None);
+
+        let timed = EchoProbe { test_id: "abc-123".to_string(), secret: "s3cr3t".to_string(), sequence: 43, client_send_us: Some(1_700_000_000_000_000) };
+        assert_eq!(EchoProbe::decode(timed.encode().as_bytes()), Some(timed));
+
+        let reply = EchoReply {
+            test_id: "abc-123".to_string(),
+            sequence: 42,
+            server_recv_us: 1_700_000_000_000_100,
+            server_send_us: 1_700_000_000_000_150,
+            client_send_us: Some(1_700_000_000_000_000),
+        };
+        assert_eq!(EchoReply::decode(reply.encode().as_bytes()), Some(reply));
+    }
+
+    #[test]
+    fn test_sessions_are_pinned_and_rate_limited() {
+        let registry = UdpSessionRegistry::new();
+        let owner: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let secret = registry.register("test-1").unwrap();
+        assert_eq!(registry.register("test-1").unwrap(), secret);
+        let now = Instant::now();
+
+        assert!(!registry.admit("unknown", &secret, owner, now));
+        assert!(registry.is_registered("test-1"));
+        assert!(!registry.is_registered("unknown"));
+        // A guessed test id without its secret neither pins nor gets echoes
+        assert!(!registry.admit("test-1", "guess", other, now));
+        assert!(registry.admit("test-1", &secret, owner, now));
+        assert!(!registry.admit("test-1", &secret, other, now));
+
+        let admitted = (0..MAX_PACKETS_PER_SECOND * 2)
+            .filter(|_| registry.admit("test-1", &secret, owner, now))
+            .count();
+        assert_eq!(admitted as u32, MAX_PACKETS_PER_SECOND - 1);
+        assert!(registry.admit("test-1", &secret, owner, now + Duration::from_secs(1)));
+
+        registry.remove("test-1");
+        assert!(!registry.admit("test-1", &secret, owner, now + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_live_sessions_are_capped() {
+        let registry = UdpSessionRegistry::new();
+        let secrets: Vec<String> = (0..MAX_SESSIONS)
+            .map(|i| registry.register(&format!("test-{}", i)).unwrap())
+            .collect();
+        assert_ne!(secrets[0], secrets[1]);
+        assert!(registry.register("one-too-many").is_err());
+
+        registry.remove("test-0");
+        assert!(registry.register("one-too-many").is_ok());
+    }
+}
@@ -0,0 +1,312 @@
+//! Household Usage Priorities
+//!
+//! The overall AIM score is a weighted average of the use-case scores. By
+//! default the scoring profile's `overall_weights` apply; a test may instead
+//! ask for a named preset ("gamer", "remote worker", "streamer") or give its
+//! own per-use-case priorities, so a household that only streams is not
+//! graded on gaming latency.
+//!
+//! Weights are resolved when the test starts and held per test id until the
+//! WebSocket run picks them up.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::aim_scoring::UseCase;
+use crate::services::scoring_profile::ScoringProfile;
+
+/// Pending weights expire this long after the test was started
+const PENDING_TTL: Duration = Duration::from_secs(120);
+
+/// Named household profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsagePreset {
+    /// The scoring profile's own weights
+    Balanced,
+    Gamer,
+    RemoteWorker,
+    Streamer,
+}
+
+impl UsagePreset {
+    pub const ALL: [UsagePreset; 4] = [
+        UsagePreset::Balanced,
+        UsagePreset::Gamer,
+        UsagePreset::RemoteWorker,
+        UsagePreset::Streamer,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            UsagePreset::Balanced => "balanced",
+            UsagePreset::Gamer => "gamer",
+            UsagePreset::RemoteWorker => "remote_worker",
+            UsagePreset::Streamer => "streamer",
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            UsagePreset::Balanced => "Balanced",
+            UsagePreset::Gamer => "Gamer",
+            UsagePreset::RemoteWorker => "Remote Worker",
+            UsagePreset::Streamer => "Streamer",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            UsagePreset::Balanced => "Every use case counts as the scoring profile defines",
+            UsagePreset::Gamer => "Latency-sensitive play first, streaming and calls second",
+            UsagePreset::RemoteWorker => "Video calls, remote desktops and file sync",
+            UsagePreset::Streamer => "Video streaming and everyday browsing; latency barely matters",
+        }
+    }
+
+    /// Parse an identifier, ignoring case, spaces and dashes ("Remote Worker", "remote-worker")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace([' ', '-'], "_");
+        match name.as_str() {
+            "default" => Some(UsagePreset::Balanced),
+            "wfh" | "work_from_home" => Some(UsagePreset::RemoteWorker),
+            _ => Self::ALL.into_iter().find(|p| p.as_str() == name),
+        }
+    }
+
+    /// Relative weights; `None` means the profile's own
+    fn weights(&self) -> Option<&'static [(UseCase, f64)]> {
+        match self {
+            UsagePreset::Balanced => None,
+            UsagePreset::Gamer => Some(&[
+                (UseCase::Gaming, 0.5),
+                (UseCase::CloudGaming, 0.2),
+                (UseCase::Streaming, 0.1),
+                (UseCase::VideoConferencing, 0.1),
+                (UseCase::GeneralBrowsing, 0.1),
+            ]),
+            UsagePreset::RemoteWorker => Some(&[
+                (UseCase::VideoConferencing, 0.4),
+                (UseCase::RemoteDesktop, 0.25),
+                (UseCase::GeneralBrowsing, 0.2),
+                (UseCase::CloudBackup, 0.15),
+            ]),
+            UsagePreset::Streamer => Some(&[
+                (UseCase::Streaming, 0.7),
+                (UseCase::GeneralBrowsing, 0.2),
+                (UseCase::SmartHome, 0.1),
+            ]),
+        }
+    }
+}
+
+/// The weights behind a test's overall score, echoed in its result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OverallWeights {
+    /// Preset the weights came from; `None` for custom priorities
+    pub preset: Option<UsagePreset>,
+    /// Share of the overall score per use case, summing to 1
+    pub weights: BTreeMap<UseCase, f64>,
+}
+
+impl OverallWeights {
+    /// The scoring profile's own weights
+    pub fn from_profile(profile: &ScoringProfile) -> Self {
+        let weights = profile.use_cases.keys()
+            .map(|use_case| (*use_case, profile.overall_weight(*use_case)))
+            .collect();
+        Self { preset: Some(UsagePreset::Balanced), weights: normalize(weights) }
+    }
+
+    /// Weights for a named preset, limited to the use cases the profile scores
+    pub fn from_preset(profile: &ScoringProfile, preset: UsagePreset) -> Result<Self, String> {
+        let Some(preset_weights) = preset.weights() else {
+            return Ok(Self::from_profile(profile));
+        };
+        let weights: BTreeMap<UseCase, f64> = preset_weights.iter()
+            .filter(|(use_case, _)| profile.use_cases.contains_key(use_case))
+            .copied()
+            .collect();
+        if weights.is_empty() {
+            return Err(format!(
+                "preset {}: none of its use cases are scored by the '{}' profile",
+                preset.as_str(), profile.name
+            ));
+        }
+        Ok(Self { preset: Some(preset), weights: normalize(weights) })
+    }
+
+    /// Weights from explicit priorities keyed by use-case name. Two names
+    /// for the same use case (e.g. "video" and "conferencing") are rejected
+    /// rather than one silently overwriting the other.
+    pub fn from_priorities(profile: &ScoringProfile, priorities: &HashMap<String, f64>) -> Result<Self, String> {
+        // Sorted, so errors name the same key whatever the map's order
+        let mut priorities: Vec<(&String, &f64)> = priorities.iter().collect();
+        priorities.sort_by_key(|(name, _)| *name);
+        
+        let mut weights = BTreeMap::new();
+        let mut names: BTreeMap<UseCase, &str> = BTreeMap::new();
+        for (name, priority) in priorities {
+            let use_case = UseCase::from_name(name)
+                .ok_or_else(|| format!("priorities.{}: unknown use case", name))?;
+            if let Some(first) = names.insert(use_case, name) {
+                return Err(format!("priorities.{}: same use case as priorities.{}", name, first));
+            }
+            if !profile.use_cases.contains_key(&use_case) {
+                return Err(format!("priorities.{}: not scored by the '{}' profile", name, profile.name));
+            }
+            if !priority.is_finite() || *priority < 0.0 {
+                return Err(format!("priorities.{}: must be a non-negative number", name));
+            }
+            weights.insert(use_case, *priority);
+        }
+        if weights.values().sum::<f64>() <= 0.0 {
+            return Err("priorities: at least one use case needs a positive priority".to_string());
+        }
+        Ok(Self { preset: None, weights: normalize(weights) })
+    }
+
+    /// Resolve a start request's preset name or priorities
+    pub fn resolve(
+        profile: &ScoringProfile,
+        preset: Option<&str>,
+        priorities: Option<&HashMap<String, f64>>,
+    ) -> Result<Self, String> {
+        match (preset, priorities) {
+            (Some(_), Some(_)) => Err("give either a preset or priorities, not both".to_string()),
+            (Some(name), None) => {
+                let preset = UsagePreset::from_name(name)
+                    .ok_or_else(|| format!("preset {}: unknown preset", name))?;
+                Self::from_preset(profile, preset)
+            }
+            (None, Some(priorities)) => Self::from_priorities(profile, priorities),
+            (None, None) => Ok(Self::from_profile(profile)),
+        }
+    }
+
+    /// The same priorities applied to another profile, e.g. when re-scoring
+    /// with a different scoring version; falls back to the profile's weights
+    pub fn for_profile(&self, profile: &ScoringProfile) -> Self {
+        match self.preset {
+            Some(preset) => Self::from_preset(profile, preset)
+                .unwrap_or_else(|_| Self::from_profile(profile)),
+            None => {
+                let weights: BTreeMap<UseCase, f64> = self.weights.iter()
+                    .filter(|(use_case, _)| profile.use_cases.contains_key(use_case))
+                    .map(|(use_case, weight)| (*use_case, *weight))
+                    .collect();
+                if weights.values().sum::<f64>() > 0.0 {
+                    Self { preset: None, weights: normalize(weights) }
+                } else {
+                    Self::from_profile(profile)
+                }
+            }
+        }
+    }
+
+    pub fn weight(&self, use_case: UseCase) -> f64 {
+        self.weights.get(&use_case).copied().unwrap_or(0.0)
+    }
+
+    /// "Gamer", "Balanced" or "Custom"
+    pub fn label(&self) -> &str {
+        self.preset.as_ref().map_or("Custom", |preset| preset.label())
+    }
+}
+
+fn normalize(mut weights: BTreeMap<UseCase, f64>) -> BTreeMap<UseCase, f64> {
+    let total: f64 = weights.values().sum();
+    if total > 0.0 {
+        weights.values_mut().for_each(|weight| *weight /= total);
+    }
+    weights
+}
+
+/// Weights chosen at test start, waiting for the WebSocket run
+#[derive(Debug, Clone, Default)]
+pub struct PendingWeights {
+    pending: Arc<Mutex<HashMap<String, (Instant, OverallWeights)>>>,
+}
+
+impl PendingWeights {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, test_id: &str, weights: OverallWeights) {
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, (created_at, _)| now.duration_since(*created_at) < PENDING_TTL);
+        pending.insert(test_id.to_string(), (now, weights));
+    }
+
+    /// Remove and return the weights for a test, if still fresh
+    pub fn take(&self, test_id: &str) -> Option<OverallWeights> {
+        let (created_at, weights) = self.pending.lock().unwrap().remove(test_id)?;
+        (created_at.elapsed() < PENDING_TTL).then_some(weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_and_priorities_resolve_against_profile() {
+        let profile = ScoringProfile::builtin();
+
+        let balanced = OverallWeights::resolve(profile, None, None).unwrap();
+        assert_eq!(balanced.preset, Some(UsagePreset::Balanced));
+        assert_eq!(balanced.weight(UseCase::Gaming), 0.25);
+        assert_eq!(balanced.weight(UseCase::CloudGaming), 0.0);
+
+        let streamer = OverallWeights::resolve(profile, Some("Streamer"), None).unwrap();
+        assert_eq!(streamer.weight(UseCase::Gaming), 0.0);
+        assert!((streamer.weights.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(
+            OverallWeights::resolve(profile, Some("remote worker"), None).unwrap().preset,
+            Some(UsagePreset::RemoteWorker)
+        );
+
+        let priorities = HashMap::from([("streaming".to_string(), 3.0), ("browsing".to_string(), 1.0)]);
+        let custom = OverallWeights::resolve(profile, None, Some(&priorities)).unwrap();
+        assert_eq!(custom.label(), "Custom");
+        assert_eq!(custom.weight(UseCase::Streaming), 0.75);
+        assert_eq!(custom.weight(UseCase::GeneralBrowsing), 0.25);
+
+        assert!(OverallWeights::resolve(profile, Some("couch potato"), None).is_err());
+        assert!(OverallWeights::resolve(profile, Some("gamer"), Some(&priorities)).is_err());
+        let negative = HashMap::from([("gaming".to_string(), -1.0)]);
+        assert!(OverallWeights::resolve(profile, None, Some(&negative)).is_err());
+        let zero = HashMap::from([("gaming".to_string(), 0.0)]);
+        assert!(OverallWeights::resolve(profile, None, Some(&zero)).is_err());
+    }
+
+    #[test]
+    fn test_aliases_of_one_use_case_are_rejected() {
+        let profile = ScoringProfile::builtin();
+        let aliases = HashMap::from([
+            ("video".to_string(), 5.0),
+            ("conferencing".to_string(), 1.0),
+            ("gaming".to_string(), 1.0),
+        ]);
+        assert_eq!(
+            OverallWeights::resolve(profile, None, Some(&aliases)).unwrap_err(),
+            "priorities.video: same use case as priorities.conferencing"
+        );
+    }
+
+    #[test]
+    fn test_pending_weights_are_taken_once() {
+        let pending = PendingWeights::new();
+        let weights = OverallWeights::from_preset(ScoringProfile::builtin(), UsagePreset::Gamer).unwrap();
+        pending.insert("test-1", weights.clone());
+
+        assert_eq!(pending.take("test-1"), Some(weights));
+        assert_eq!(pending.take("test-1"), None);
+        assert_eq!(pending.take("unknown"), None);
+    }
+}
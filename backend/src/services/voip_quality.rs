@@ -0,0 +1,274 @@
+//! VoIP call quality using the ITU-T G.107 E-model
+//!
+//! Answers "will my calls sound good" with an R-factor and MOS per codec.
+//! Simplifications relative to the full model:
+//! - Default values for everything not measured (Ro - Is = 93.2, A = 0)
+//! - Echo is assumed to be cancelled, so delay impairment is Idd only
+//! - A fixed jitter buffer of twice the measured jitter, which adds delay
+//!   instead of discarding late packets
+//! - Opus impairment values are approximations; G.113 does not list it
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::TestResult;
+use crate::services::loaded_latency::LoadedLatencyResult;
+use crate::services::packet_loss::PacketLossResult;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceCodec {
+    G711,
+    Opus,
+}
+
+impl VoiceCodec {
+    pub const ALL: [VoiceCodec; 2] = [VoiceCodec::G711, VoiceCodec::Opus];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            VoiceCodec::G711 => "G.711",
+            VoiceCodec::Opus => "Opus",
+        }
+    }
+
+    /// Equipment impairment factor Ie
+    fn equipment_impairment(&self) -> f64 {
+        match self {
+            VoiceCodec::G711 => 0.0,
+            VoiceCodec::Opus => 5.0,
+        }
+    }
+
+    /// Packet-loss robustness factor Bpl (with concealment)
+    fn loss_robustness(&self) -> f64 {
+        match self {
+            VoiceCodec::G711 => 25.1,
+            VoiceCodec::Opus => 30.0,
+        }
+    }
+
+    /// Packetization plus algorithmic (lookahead) delay in ms
+    fn codec_delay_ms(&self) -> f64 {
+        match self {
+            VoiceCodec::G711 => 20.0,
+            VoiceCodec::Opus => 26.5,
+        }
+    }
+}
+
+/// G.109 user satisfaction category for an R-factor
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallRating {
+    VerySatisfied,          // R >= 90
+    Satisfied,              // R >= 80
+    SomeUsersDissatisfied,  // R >= 70
+    ManyUsersDissatisfied,  // R >= 60
+    NearlyAllDissatisfied,  // R < 60
+}
+
+impl CallRating {
+    pub fn from_r_factor(r: f64) -> Self {
+        match r {
+            r if r >= 90.0 => CallRating::VerySatisfied,
+            r if r >= 80.0 => CallRating::Satisfied,
+            r if r >= 70.0 => CallRating::SomeUsersDissatisfied,
+            r if r >= 60.0 => CallRating::ManyUsersDissatisfied,
+            _ => CallRating::NearlyAllDissatisfied,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            CallRating::VerySatisfied => "very_satisfied",
+            CallRating::Satisfied => "satisfied",
+            CallRating::SomeUsersDissatisfied => "some_users_dissatisfied",
+            CallRating::ManyUsersDissatisfied => "many_users_dissatisfied",
+            CallRating::NearlyAllDissatisfied => "nearly_all_dissatisfied",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            CallRating::VerySatisfied => "Crystal clear calls",
+            CallRating::Satisfied => "Good call quality",
+            CallRating::SomeUsersDissatisfied => "Acceptable, with occasional glitches",
+            CallRating::ManyUsersDissatisfied => "Noticeably degraded calls",
+            CallRating::NearlyAllDissatisfied => "Calls will be hard to follow",
+        }
+    }
+
+    pub fn emoji(&self) -> &str {
+        match self {
+            CallRating::VerySatisfied => "⭐",
+            CallRating::Satisfied => "✅",
+            CallRating::SomeUsersDissatisfied => "✓",
+            CallRating::ManyUsersDissatisfied => "⚠️",
+            CallRating::NearlyAllDissatisfied => "❌",
+        }
+    }
+}
+
+/// Estimated call quality for one codec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceQualityEstimate {
+    pub codec: VoiceCodec,
+    /// Network one-way delay the estimate was based on
+    pub one_way_delay_ms: f64,
+    pub jitter_buffer_ms: f64,
+    /// Mouth-to-ear delay: network + jitter buffer + codec
+    pub effective_delay_ms: f64,
+    pub packet_loss_pct: f64,
+    pub r_factor: f64,
+    pub mos: f64,
+    pub rating: CallRating,
+}
+
+pub struct VoipQualityCalculator;
+
+impl VoipQualityCalculator {
+    /// Ro - Is with G.107 default values
+    const R_BASE: f64 = 93.2;
+
+    /// Estimate call quality for all supported codecs from a test
+    ///
+    /// Calls are modelled while the uplink is busy, matching the video
+    /// conferencing score: the worse direction's one-way delay and jitter
+    /// under upload load when measured (else half the upload loaded RTT and
+    /// the upload-stage jitter), and the worse of idle and upload-stage loss.
+    /// Without a loss measurement there is no estimate: assuming no loss
+    /// would rate calls better than anything measured supports.
+    pub fn estimate_all(
+        test_result: &TestResult,
+        loaded_latency: &LoadedLatencyResult,
+        packet_loss: Option<&PacketLossResult>,
+    ) -> Vec<VoiceQualityEstimate> {
+        let (one_way_delay_ms, jitter_ms) = match &loaded_latency.upload_one_way {
+            Some(one_way) => (
+                one_way.upstream.avg_ms.max(one_way.downstream.avg_ms),
+                one_way.upstream.jitter_ms.max(one_way.downstream.jitter_ms),
+            ),
+            None if loaded_latency.upload_jitter.samples_used > 1 => (
+                loaded_latency.upload_avg_ms / 2.0,
+                loaded_latency.upload_jitter.rfc3550_jitter_ms,
+            ),
+            None => (loaded_latency.upload_avg_ms / 2.0, test_result.jitter_ms),
+        };
+
+        let worst_loss = packet_loss
+            .into_iter()
+            .chain(loaded_latency.upload_packet_loss.as_ref())
+            .max_by(|a, b| a.loss_percentage.total_cmp(&b.loss_percentage));
+        let Some(worst_loss) = worst_loss else {
+            return Vec::new();
+        };
+        let loss_pct = worst_loss.loss_percentage;
+        let burst_ratio = Self::burst_ratio(worst_loss);
+
+        VoiceCodec::ALL
+            .iter()
+            .map(|codec| Self::estimate(*codec, one_way_delay_ms, jitter_ms, loss_pct, burst_ratio))
+            .collect()
+    }
+
+    /// E-model estimate for one codec from network conditions
+    pub fn estimate(
+        codec: VoiceCodec,
+        one_way_delay_ms: f64,
+        jitter_ms: f64,
+        loss_pct: f64,
+        burst_ratio: f64,
+    ) -> VoiceQualityEstimate {
+        let jitter_buffer_ms = 2.0 * jitter_ms;
+        let effective_delay_ms = one_way_delay_ms + jitter_buffer_ms + codec.codec_delay_ms();
+
+        let r_factor = Self::R_BASE
+            - Self::delay_impairment(effective_delay_ms)
+            - Self::effective_equipment_impairment(codec, loss_pct, burst_ratio);
+        let mos = Self::mos(r_factor);
+
+        VoiceQualityEstimate {
+            codec,
+            one_way_delay_ms,
+            jitter_buffer_ms,
+            effective_delay_ms,
+            packet_loss_pct: loss_pct,
+            r_factor,
+            mos,
+            rating: CallRating::from_r_factor(r_factor),
+        }
+    }
+
+    /// Idd: impairment from absolute mouth-to-ear delay Ta
+    fn delay_impairment(ta_ms: f64) -> f64 {
+        if ta_ms <= 100.0 {
+            return 0.0;
+        }
+        let x = (ta_ms / 100.0).log2();
+        25.0 * ((1.0 + x.powi(6)).powf(1.0 / 6.0) - 3.0 * (1.0 + (x / 3.0).powi(6)).powf(1.0 / 6.0) + 2.0)
+    }
+
+    /// Ie,eff: codec impairment degraded by (possibly bursty) packet loss
+    fn effective_equipment_impairment(codec: VoiceCodec, loss_pct: f64, burst_ratio: f64) -> f64 {
+        let ie = codec.equipment_impairment();
+        ie + (95.0 - ie) * loss_pct / (loss_pct / burst_ratio + codec.loss_robustness())
+    }
+
+    /// BurstR from the Gilbert-Elliott model; 1.0 means random loss
+    fn burst_ratio(loss: &PacketLossResult) -> f64 {
+        let transitions = loss.bursts.gilbert_p + loss.bursts.gilbert_r;
+        if loss.lost_packets == 0 || transitions <= 0.0 {
+            1.0
+        } else {
+            (1.0 / transitions).max(1.0)
+        }
+    }
+
+    /// G.107 R to MOS conversion
+    pub fn mos(r: f64) -> f64 {
+        if r <= 0.0 {
+            1.0
+        } else if r >= 100.0 {
+            4.5
+        } else {
+            1.0 + 0.035 * r + r * (r - 60.0) * (100.0 - r) * 7.0e-6
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_link_is_toll_quality() {
+        let g711 = VoipQualityCalculator::estimate(VoiceCodec::G711, 20.0, 2.0, 0.0, 1.0);
+        assert!((g711.r_factor - 93.2).abs() < 0.001);
+        assert!(g711.mos > 4.3);
+        assert_eq!(g711.rating, CallRating::VerySatisfied);
+    }
+
+    #[test]
+    fn test_delay_and_bursty_loss_degrade_calls() {
+        let slow = VoipQualityCalculator::estimate(VoiceCodec::G711, 300.0, 10.0, 0.0, 1.0);
+        assert!(slow.r_factor < 80.0);
+
+        let random = VoipQualityCalculator::estimate(VoiceCodec::G711, 20.0, 2.0, 3.0, 1.0);
+        let bursty = VoipQualityCalculator::estimate(VoiceCodec::G711, 20.0, 2.0, 3.0, 4.0);
+        assert!(bursty.r_factor < random.r_factor);
+
+        // Opus's higher base impairment is recovered by its concealment
+        let opus = VoipQualityCalculator::estimate(VoiceCodec::Opus, 20.0, 2.0, 3.0, 1.0);
+        assert!(opus.r_factor > random.r_factor - 5.0);
+    }
+
+    #[test]
+    fn test_no_estimate_without_packet_loss() {
+        use crate::services::loaded_latency::LoadedLatencyTester;
+
+        let test_result = TestResult::new("test".to_string(), "127.0.0.1".to_string());
+        let loaded_latency = LoadedLatencyTester::new().calculate_results();
+        assert!(loaded_latency.upload_packet_loss.is_none());
+        assert!(VoipQualityCalculator::estimate_all(&test_result, &loaded_latency, None).is_empty());
+    }
+}
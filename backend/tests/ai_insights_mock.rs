@@ -0,0 +1,47 @@
+//! Drives the insights path end to end against the mock completion server
+
+use speedtest_pro_backend::models::TestResult;
+use speedtest_pro_backend::services::ai_insights::{generate_insights, AINetworkAnalyzer, OpenAISettings};
+use speedtest_pro_backend::services::aim_scoring::AIMCalculator;
+use speedtest_pro_backend::services::loaded_latency::LoadedLatencyTester;
+use speedtest_pro_backend::services::mock_completion::{MockCompletionServer, MockReply, RequestLog};
+
+async fn mock_analyzer(replies: Vec<MockReply>) -> (AINetworkAnalyzer, RequestLog) {
+    let server = MockCompletionServer::bind("127.0.0.1:0", replies).await.unwrap();
+    let settings = OpenAISettings {
+        base_url: server.base_url().unwrap(),
+        model: "mock-model".to_string(),
+        ..OpenAISettings::default()
+    };
+    let log = server.requests();
+    tokio::spawn(server.run());
+    (AINetworkAnalyzer::new(settings).unwrap(), log)
+}
+
+#[tokio::test]
+async fn test_insights_from_mock_completion() {
+    let mut test_result = TestResult::new("integration".to_string(), "127.0.0.1".to_string());
+    test_result.download_mbps = 200.0;
+    test_result.upload_mbps = 20.0;
+    let loaded_latency = LoadedLatencyTester::new().calculate_results();
+    let aim_scores = AIMCalculator::calculate_all_scores(&test_result, &loaded_latency, None);
+    
+    // A malformed reply is re-asked, then the default insights are served
+    let (analyzer, log) = mock_analyzer(vec![MockReply::content("Enable SQM.")]).await;
+    let insights = generate_insights(&analyzer, &test_result, &loaded_latency, &aim_scores).await;
+    assert_eq!(insights.provider, "openai");
+    assert_eq!(insights.recommendations[0].title, "Enable Smart Queue Management (SQM)");
+    assert!(!insights.simple_explanation.is_empty());
+    
+    let requests = log.all();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].body["model"], "mock-model");
+    let prompt = requests[1].body["messages"].to_string();
+    assert!(prompt.contains("200"), "prompt should carry the measured download speed");
+    
+    // An API error falls back to the rules
+    let (analyzer, log) = mock_analyzer(vec![MockReply::error(500)]).await;
+    let insights = generate_insights(&analyzer, &test_result, &loaded_latency, &aim_scores).await;
+    assert_eq!(insights.provider, "rules");
+    assert_eq!(log.len(), 1);
+}
@@ -5,6 +5,8 @@ import { useSpeedTest } from './hooks/useSpeedTest';
 import { SpeedGauge } from './components/SpeedGauge';
 import { BufferbloatCard } from './components/BufferbloatCard';
 import { AIMScoreCard } from './components/AIMScoreCard';
+import { HouseholdCapacityCard } from './components/HouseholdCapacityCard';
+import { PlanComplianceCard } from './components/PlanComplianceCard';
 import { AIInsightsPanel } from './components/AIInsightsPanel';
 import { ProgressOverlay } from './components/ProgressOverlay';
 import { DarkModeToggle } from './components/DarkModeToggle';
@@ -12,10 +14,19 @@ import { ShareResults } from './components/ShareResults';
 import { ExportResults } from './components/ExportResults';
 import { ServerSelector } from './components/ServerSelector';
 import { TestHistory } from './components/TestHistory';
+import type { UsagePreset } from './types';
+
+const USAGE_PRESETS: { value: UsagePreset; label: string }[] = [
+  { value: 'balanced', label: 'Balanced household' },
+  { value: 'gamer', label: 'Gamer' },
+  { value: 'remote_worker', label: 'Remote worker' },
+  { value: 'streamer', label: 'Streamer' },
+];
 
 function App() {
   const { isRunning, progress, result, error, startTest } = useSpeedTest();
   const [includeAI, setIncludeAI] = useState(false);
+  const [preset, setPreset] = useState<UsagePreset>('balanced');
   const [showHistory, setShowHistory] = useState(false);
 
   return (
@@ -77,7 +88,7 @@ function App() {
             className="flex flex-col items-center mb-12"
           >
             <button
-              onClick={() => startTest(includeAI)}
+              onClick={() => startTest(includeAI, preset)}
               disabled={isRunning}
               className="group relative px-12 py-6 bg-gradient-to-r from-primary-500 to-blue-600 rounded-2xl font-bold text-xl transition-all hover:scale-105 hover:shadow-2xl hover:shadow-primary-500/50 disabled:opacity-50 disabled:cursor-not-allowed disabled:hover:scale-100"
             >
@@ -111,6 +122,20 @@ function App() {
                 Include AI Insights (GPT-4 analysis)
               </span>
             </label>
+
+            <label className="flex items-center gap-2 mt-3 text-gray-300">
+              Score for
+              <select
+                value={preset}
+                onChange={(e) => setPreset(e.target.value as UsagePreset)}
+                disabled={isRunning}
+                className="px-3 py-1 rounded-lg bg-dark-800 border border-primary-500/50 text-white disabled:opacity-50"
+              >
+                {USAGE_PRESETS.map(({ value, label }) => (
+                  <option key={value} value={value}>{label}</option>
+                ))}
+              </select>
+            </label>
           </motion.div>
         )}
 
@@ -165,7 +190,7 @@ function App() {
 
             {/* Loaded Latency */}
             {result.loaded_latency && (
-              <BufferbloatCard data={result.loaded_latency} />
+              <BufferbloatCard data={result.loaded_latency} advice={result.advice} />
             )}
 
             {/* AIM Scores */}
@@ -173,6 +198,16 @@ function App() {
               <AIMScoreCard scores={result.aim_scores} />
             )}
 
+            {/* ISP Plan Compliance */}
+            {result.plan_compliance && (
+              <PlanComplianceCard evaluation={result.plan_compliance} />
+            )}
+
+            {/* Household Capacity */}
+            {result.household_capacity && (
+              <HouseholdCapacityCard capacity={result.household_capacity} />
+            )}
+
             {/* AI Insights */}
             {result.ai_insights && (
               <AIInsightsPanel insights={result.ai_insights} />
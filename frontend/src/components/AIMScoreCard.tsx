@@ -1,38 +1,41 @@
+import type { ReactNode } from 'react';
 import { motion } from 'framer-motion';
-import { Gamepad2, Video, Users, Globe } from 'lucide-react';
-import type { AIMScores } from '../types';
+import { Gamepad2, Video, Users, Globe, Joystick, Monitor, CloudUpload, House, Gauge } from 'lucide-react';
+import type { AIMScores, UseCase } from '../types';
 
 interface AIMScoreCardProps {
   scores: AIMScores;
 }
 
+const USE_CASE_STYLES: Record<UseCase, { icon: ReactNode; label: string; color: string }> = {
+  gaming: { icon: <Gamepad2 className="w-6 h-6" />, label: 'Gaming', color: 'from-purple-500 to-pink-500' },
+  streaming: { icon: <Video className="w-6 h-6" />, label: 'Streaming', color: 'from-red-500 to-orange-500' },
+  video_conferencing: { icon: <Users className="w-6 h-6" />, label: 'Video Calls', color: 'from-blue-500 to-cyan-500' },
+  general_browsing: { icon: <Globe className="w-6 h-6" />, label: 'Browsing', color: 'from-green-500 to-emerald-500' },
+  cloud_gaming: { icon: <Joystick className="w-6 h-6" />, label: 'Cloud Gaming', color: 'from-fuchsia-500 to-violet-500' },
+  remote_desktop: { icon: <Monitor className="w-6 h-6" />, label: 'Remote Desktop', color: 'from-slate-500 to-gray-500' },
+  cloud_backup: { icon: <CloudUpload className="w-6 h-6" />, label: 'Cloud Backup', color: 'from-sky-500 to-indigo-500' },
+  smart_home: { icon: <House className="w-6 h-6" />, label: 'Smart Home', color: 'from-amber-500 to-yellow-500' },
+};
+
 export function AIMScoreCard({ scores }: AIMScoreCardProps) {
-  const useCases = [
-    {
-      icon: <Gamepad2 className="w-6 h-6" />,
-      label: 'Gaming',
-      score: scores.gaming,
-      color: 'from-purple-500 to-pink-500',
-    },
-    {
-      icon: <Video className="w-6 h-6" />,
-      label: 'Streaming',
-      score: scores.streaming,
-      color: 'from-red-500 to-orange-500',
-    },
-    {
-      icon: <Users className="w-6 h-6" />,
-      label: 'Video Calls',
-      score: scores.video_conferencing,
-      color: 'from-blue-500 to-cyan-500',
-    },
-    {
-      icon: <Globe className="w-6 h-6" />,
-      label: 'Browsing',
-      score: scores.general_browsing,
-      color: 'from-green-500 to-emerald-500',
-    },
-  ];
+  // Use cases this build does not know yet still render, with a generic style
+  const useCases = scores.use_cases.map((score) => ({
+    icon: <Gauge className="w-6 h-6" />,
+    label: score.label,
+    color: 'from-gray-500 to-gray-600',
+    ...USE_CASE_STYLES[score.use_case],
+    score,
+  }));
+
+  // Only mention the weighting when it differs from the default
+  const weights = scores.overall_weights;
+  const weightedFor = weights && weights.preset !== 'balanced'
+    ? useCases
+        .filter(({ score }) => (weights.weights[score.use_case] ?? 0) > 0)
+        .map(({ label, score }) => `${label} ${Math.round((weights.weights[score.use_case] ?? 0) * 100)}%`)
+        .join(', ')
+    : null;
 
   return (
     <motion.div
@@ -79,8 +82,30 @@ export function AIMScoreCard({ scores }: AIMScoreCardProps) {
             </div>
 
             <p className="mt-3 text-xs text-gray-400 line-clamp-2">
-              {useCase.score.assessment}
+              {useCase.score.assessment.text}
             </p>
+
+            {useCase.score.factors && useCase.score.factors.length > 0 && (
+              <details className="mt-3 text-xs text-gray-400">
+                <summary className="cursor-pointer text-gray-500 hover:text-gray-300">Score breakdown</summary>
+                <ul className="mt-2 space-y-1">
+                  {useCase.score.factors.map((factor) => (
+                    <li key={factor.metric} className="flex justify-between gap-2">
+                      <span>
+                        {factor.label}:{' '}
+                        {factor.value === null
+                          ? 'not measured'
+                          : `${Number(factor.value.toFixed(1))} ${factor.unit}`}
+                        {factor.band && <span className="text-gray-600"> ({factor.band})</span>}
+                      </span>
+                      <span className={factor.points_earned < factor.points_possible ? 'text-orange-400' : 'text-gray-300'}>
+                        {factor.points_earned}/{factor.points_possible}
+                      </span>
+                    </li>
+                  ))}
+                </ul>
+              </details>
+            )}
           </motion.div>
         ))}
       </div>
@@ -100,6 +125,9 @@ export function AIMScoreCard({ scores }: AIMScoreCardProps) {
                   {scores.overall_grade}
                 </span>
               </div>
+              {weightedFor && (
+                <div className="mt-2 text-xs text-gray-400">Weighted for {weightedFor}</div>
+              )}
             </div>
             
             {/* Circular progress */}
@@ -1,12 +1,14 @@
 import { motion } from 'framer-motion';
 import { AlertTriangle, CheckCircle, XCircle, Activity } from 'lucide-react';
-import type { LoadedLatencyResult, BufferbloatGrade } from '../types';
+import type { LoadedLatencyResult, BufferbloatGrade, LatencyAdvice } from '../types';
 
 interface BufferbloatCardProps {
   data: LoadedLatencyResult;
+  /** Server-localized advice; the built-in English text is the fallback */
+  advice?: LatencyAdvice | null;
 }
 
-export function BufferbloatCard({ data }: BufferbloatCardProps) {
+export function BufferbloatCard({ data, advice }: BufferbloatCardProps) {
   const gradeInfo = getGradeInfo(data.bufferbloat_grade);
 
   return (
@@ -57,8 +59,15 @@ export function BufferbloatCard({ data }: BufferbloatCardProps) {
       {/* Explanation */}
       <div className="mt-6 p-4 bg-dark-900/50 rounded-lg">
         <p className="text-sm text-gray-300">
-          {gradeInfo.explanation}
+          {advice ? advice.bufferbloat.text : gradeInfo.explanation}
         </p>
+        {advice && advice.recommendations.length > 0 && (
+          <ul className="mt-3 space-y-1 text-xs text-gray-400">
+            {advice.recommendations.map((rec) => (
+              <li key={rec.key}>{rec.text}</li>
+            ))}
+          </ul>
+        )}
       </div>
     </motion.div>
   );
@@ -137,6 +146,11 @@ function getGradeInfo(grade: BufferbloatGrade) {
       textColor: 'text-red-400',
       explanation: '❌ Severe bufferbloat! Your connection becomes very laggy under load. Contact ISP or enable SQM.',
     },
+    'N/A': {
+      bgColor: 'bg-gray-500/20',
+      textColor: 'text-gray-400',
+      explanation: '❔ Not graded: too few latency probes succeeded. Run the test again.',
+    },
   };
   return gradeMap[grade];
 }
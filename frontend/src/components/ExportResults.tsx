@@ -19,7 +19,7 @@ export function ExportResults({ result }: ExportResultsProps) {
 
   const exportAsCSV = () => {
     const csv = [
-      'Metric,Value,Unit',
+      'Metric,Value,Unit,Points',
       `Download Speed,${result.basic.download_mbps},Mbps`,
       `Upload Speed,${result.basic.upload_mbps},Mbps`,
       `Latency,${result.basic.latency_ms},ms`,
@@ -28,10 +28,12 @@ export function ExportResults({ result }: ExportResultsProps) {
       result.loaded_latency ? `Download Latency,${result.loaded_latency.download_avg_ms},ms` : '',
       result.loaded_latency ? `Upload Latency,${result.loaded_latency.upload_avg_ms},ms` : '',
       result.loaded_latency ? `Bufferbloat Grade,${result.loaded_latency.bufferbloat_grade},` : '',
-      result.aim_scores ? `Gaming Score,${result.aim_scores.gaming.score},/100` : '',
-      result.aim_scores ? `Streaming Score,${result.aim_scores.streaming.score},/100` : '',
-      result.aim_scores ? `Video Call Score,${result.aim_scores.video_conferencing.score},/100` : '',
-      result.aim_scores ? `Browsing Score,${result.aim_scores.general_browsing.score},/100` : '',
+      ...(result.aim_scores?.use_cases ?? []).flatMap((s) => [
+        `${s.label} Score,${s.score},/100`,
+        ...(s.factors ?? []).map((f) =>
+          `${s.label} - ${f.label},${f.value ?? 'not measured'},${f.unit},${f.points_earned}/${f.points_possible}`
+        ),
+      ]),
       result.aim_scores ? `Overall Score,${result.aim_scores.overall_score},/100` : '',
     ].filter(Boolean).join('\n');
 
@@ -70,10 +72,12 @@ Bufferbloat Grade: ${result.loaded_latency.bufferbloat_grade}
 ${result.aim_scores ? `
 AIM SCORES
 ----------
-Gaming: ${result.aim_scores.gaming.score}/100 (${result.aim_scores.gaming.grade})
-Streaming: ${result.aim_scores.streaming.score}/100 (${result.aim_scores.streaming.grade})
-Video Calls: ${result.aim_scores.video_conferencing.score}/100 (${result.aim_scores.video_conferencing.grade})
-Browsing: ${result.aim_scores.general_browsing.score}/100 (${result.aim_scores.general_browsing.grade})
+${result.aim_scores.use_cases.map((s) => [
+  `${s.label}: ${s.score}/100 (${s.grade})`,
+  ...(s.factors ?? []).map((f) =>
+    `  - ${f.label}: ${f.value === null ? 'not measured' : `${f.value} ${f.unit}`}${f.band ? ` (${f.band})` : ''} ${f.points_earned}/${f.points_possible}`
+  ),
+].join('\n')).join('\n')}
 Overall: ${result.aim_scores.overall_score.toFixed(0)}/100 (${result.aim_scores.overall_grade})
 ` : ''}
 
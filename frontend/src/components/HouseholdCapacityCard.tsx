@@ -0,0 +1,111 @@
+import { useState } from 'react';
+import { motion } from 'framer-motion';
+import { House, Minus, Plus } from 'lucide-react';
+import type { ActivityKind, ActivityMix, CapacityLimit, HouseholdCapacity } from '../types';
+
+interface HouseholdCapacityCardProps {
+  capacity: HouseholdCapacity;
+}
+
+const MIX_FIELDS: Record<ActivityKind, keyof ActivityMix> = {
+  stream_4k: 'streams_4k',
+  hd_video_call: 'video_calls',
+  gaming: 'gaming_sessions',
+};
+
+const LIMIT_TEXT: Record<CapacityLimit, string> = {
+  download: 'download bandwidth',
+  upload: 'upload bandwidth',
+  latency: 'latency under load',
+};
+
+// Mirrors HouseholdCapacity::check in the backend
+function checkMix(capacity: HouseholdCapacity, mix: ActivityMix): CapacityLimit | null {
+  let download = 0;
+  let upload = 0;
+  const latencySensitive: number[] = [];
+  for (const activity of capacity.activities) {
+    const count = mix[MIX_FIELDS[activity.activity]];
+    download += count * activity.download_mbps_each;
+    upload += count * activity.upload_mbps_each;
+    if (count > 0 && activity.latency_budget_ms !== null) {
+      latencySensitive.push(activity.latency_budget_ms);
+    }
+  }
+  const downloadPct = (download / Math.max(capacity.download_budget_mbps, 1e-3)) * 100;
+  const uploadPct = (upload / Math.max(capacity.upload_budget_mbps, 1e-3)) * 100;
+  const bloated = latencySensitive.some((budget) => capacity.loaded_latency_ms > budget);
+
+  if (latencySensitive.some((budget) => capacity.idle_latency_ms > budget)) return 'latency';
+  if (downloadPct > 100) return 'download';
+  if (uploadPct > 100) return 'upload';
+  if (bloated && Math.max(downloadPct, uploadPct) > capacity.bloated_utilization_pct) return 'latency';
+  return null;
+}
+
+export function HouseholdCapacityCard({ capacity }: HouseholdCapacityCardProps) {
+  const [mix, setMix] = useState<ActivityMix>({ streams_4k: 1, video_calls: 1, gaming_sessions: 0 });
+  const limit = checkMix(capacity, mix);
+
+  const adjust = (field: keyof ActivityMix, delta: number) =>
+    setMix((current) => ({ ...current, [field]: Math.max(0, current[field] + delta) }));
+
+  return (
+    <motion.div
+      initial={{ y: 20, opacity: 0 }}
+      animate={{ y: 0, opacity: 1 }}
+      transition={{ delay: 0.4 }}
+      className="bg-dark-800/50 backdrop-blur-lg rounded-2xl p-6 border border-dark-700"
+    >
+      <div className="flex items-center gap-3 mb-4">
+        <House className="w-6 h-6 text-primary-400" />
+        <h3 className="text-xl font-bold text-white">Household Capacity</h3>
+      </div>
+
+      <p className="text-sm text-gray-300 mb-6">{capacity.summary}</p>
+
+      <div className="space-y-3">
+        {capacity.activities.map((activity) => {
+          const field = MIX_FIELDS[activity.activity];
+          return (
+            <div key={activity.activity} className="flex items-center justify-between">
+              <div>
+                <div className="text-white">{activity.label}s</div>
+                <div className="text-xs text-gray-500">
+                  up to {activity.max_sessions} alone · limited by {LIMIT_TEXT[activity.limited_by]}
+                </div>
+              </div>
+              <div className="flex items-center gap-3">
+                <button
+                  onClick={() => adjust(field, -1)}
+                  className="p-1 rounded bg-dark-700 hover:bg-dark-600"
+                  aria-label={`Fewer ${activity.label}s`}
+                >
+                  <Minus className="w-4 h-4" />
+                </button>
+                <span className="w-6 text-center font-semibold text-white">{mix[field]}</span>
+                <button
+                  onClick={() => adjust(field, 1)}
+                  className="p-1 rounded bg-dark-700 hover:bg-dark-600"
+                  aria-label={`More ${activity.label}s`}
+                >
+                  <Plus className="w-4 h-4" />
+                </button>
+              </div>
+            </div>
+          );
+        })}
+      </div>
+
+      <div
+        className={`mt-6 p-4 rounded-lg text-sm ${
+          limit ? 'bg-red-500/10 text-red-400' : 'bg-green-500/10 text-green-400'
+        }`}
+      >
+        {limit
+          ? `This mix is too much for the connection: ${LIMIT_TEXT[limit]} runs out.`
+          : 'This mix runs smoothly at the same time.'}
+      </div>
+    </motion.div>
+  );
+}
@@ -0,0 +1,67 @@
+import { motion } from 'framer-motion';
+import { ReceiptText } from 'lucide-react';
+import type { PlanEvaluation } from '../types';
+
+interface PlanComplianceCardProps {
+  evaluation: PlanEvaluation;
+}
+
+function pctColor(pct: number): string {
+  if (pct >= 90) return 'text-green-400';
+  if (pct >= 80) return 'text-yellow-400';
+  return 'text-red-400';
+}
+
+export function PlanComplianceCard({ evaluation }: PlanComplianceCardProps) {
+  const metrics = [
+    { label: 'Download', pct: evaluation.download_pct_of_plan, mbps: evaluation.download_mbps },
+    { label: 'Upload', pct: evaluation.upload_pct_of_plan, mbps: evaluation.upload_mbps },
+  ];
+
+  return (
+    <motion.div
+      initial={{ y: 20, opacity: 0 }}
+      animate={{ y: 0, opacity: 1 }}
+      transition={{ delay: 0.4 }}
+      className="bg-dark-800/50 backdrop-blur-lg rounded-2xl p-6 border border-dark-700"
+    >
+      <div className="flex items-center gap-3 mb-4">
+        <ReceiptText className="w-6 h-6 text-primary-400" />
+        <h3 className="text-xl font-bold text-white">Your ISP Plan</h3>
+      </div>
+
+      <div className="grid grid-cols-2 gap-4 mb-4">
+        {metrics.map((metric) => (
+          <div key={metric.label} className="p-4 rounded-lg bg-dark-700/50">
+            <div className="text-sm text-gray-400">{metric.label}</div>
+            <div className={`text-2xl font-bold ${pctColor(metric.pct)}`}>{metric.pct.toFixed(0)}%</div>
+            <div className="text-xs text-gray-500">of plan · {metric.mbps.toFixed(1)} Mbps</div>
+          </div>
+        ))}
+      </div>
+
+      {evaluation.latency_within_sla !== null && (
+        <div className="text-sm text-gray-300 mb-4">
+          Latency {evaluation.latency_ms.toFixed(0)} ms —{' '}
+          {evaluation.latency_within_sla ? 'within SLA' : 'outside SLA'}
+        </div>
+      )}
+
+      <div
+        className={`p-4 rounded-lg text-sm ${
+          evaluation.meets_plan ? 'bg-green-500/10 text-green-400' : 'bg-red-500/10 text-red-400'
+        }`}
+      >
+        {evaluation.meets_plan ? (
+          'This test delivered what your plan promises.'
+        ) : (
+          <ul className="list-disc list-inside space-y-1">
+            {evaluation.issues.map((issue) => (
+              <li key={issue}>{issue}</li>
+            ))}
+          </ul>
+        )}
+      </div>
+    </motion.div>
+  );
+}
@@ -1,8 +1,19 @@
 import { useState, useCallback, useRef } from 'react';
 import * as msgpack from '@msgpack/msgpack';
-import type { EnhancedTestResult, TestProgress, StartTestResponse } from '../types';
+import type { EnhancedTestResult, TestProgress, StartTestResponse, UsagePreset } from '../types';
 
 const API_BASE = 'http://localhost:8080/api';
+const DEVICE_ID_KEY = 'speedtest-device-id';
+
+// Stable per-browser id so results can be matched to a registered ISP plan
+function getDeviceId(): string {
+  let deviceId = localStorage.getItem(DEVICE_ID_KEY);
+  if (!deviceId) {
+    deviceId = crypto.randomUUID();
+    localStorage.setItem(DEVICE_ID_KEY, deviceId);
+  }
+  return deviceId;
+}
 
 export function useSpeedTest() {
   const [isRunning, setIsRunning] = useState(false);
@@ -11,7 +22,7 @@ export function useSpeedTest() {
   const [error, setError] = useState<string | null>(null);
   const wsRef = useRef<WebSocket | null>(null);
 
-  const startTest = useCallback(async (includeAI: boolean = false) => {
+  const startTest = useCallback(async (includeAI: boolean = false, preset: UsagePreset = 'balanced') => {
     setIsRunning(true);
     setProgress(null);
     setResult(null);
@@ -26,6 +37,9 @@ export function useSpeedTest() {
           include_ai_insights: includeAI,
           use_binary_protocol: true,
           duration_ms: 10000,
+          preset,
+          device_id: getDeviceId(),
+          lang: navigator.language,
         }),
       });
 
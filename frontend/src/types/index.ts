@@ -11,6 +11,9 @@ export interface TestResult {
   protocol: string;
   client_ip: string;
   test_duration_ms: number;
+  device_id?: string | null;
+  /** False when the speeds are placeholders; such tests skip plan compliance */
+  measured?: boolean;
 }
 
 export interface LoadedLatencyResult {
@@ -25,23 +28,88 @@ export interface LoadedLatencyResult {
   bufferbloat_upload_ratio: number;
 }
 
-export type BufferbloatGrade = 'A+' | 'A' | 'B' | 'C' | 'D' | 'F';
+/** 'N/A' when too few latency probes succeeded to grade */
+export type BufferbloatGrade = 'A+' | 'A' | 'B' | 'C' | 'D' | 'F' | 'N/A';
+
+export interface LatencyAdvice {
+  bufferbloat: Message;
+  recommendations: Message[];
+  packet_loss: Message | null;
+  caveats: Message[];
+}
+
+export type UseCase =
+  | 'gaming'
+  | 'streaming'
+  | 'video_conferencing'
+  | 'general_browsing'
+  | 'cloud_gaming'
+  | 'remote_desktop'
+  | 'cloud_backup'
+  | 'smart_home';
+
+/** A localized text with the catalog key and parameters it was rendered from */
+export interface Message {
+  key: string;
+  params?: Record<string, string>;
+  text: string;
+}
+
+export interface FactorScore {
+  metric: string;
+  label: string;
+  unit: string;
+  value: number | null;
+  weight: number;
+  points_earned: number;
+  points_possible: number;
+  band: string | null;
+}
 
 export interface AIMScore {
+  use_case: UseCase;
+  label: string;
   score: number;
+  /** Some factors were not measured; the score covers only the rest */
+  partial?: boolean;
   grade: string;
-  assessment: string;
-  capabilities?: string[];
-  recommendations?: string[];
+  assessment: Message;
+  explanation?: Message;
+  capabilities?: Message[];
+  recommendations?: Message[];
+  caveats?: Message[];
+  factors?: FactorScore[];
+}
+
+export interface VoiceQualityEstimate {
+  codec: 'g711' | 'opus';
+  one_way_delay_ms: number;
+  jitter_buffer_ms: number;
+  effective_delay_ms: number;
+  packet_loss_pct: number;
+  r_factor: number;
+  mos: number;
+  rating: string;
+}
+
+export type UsagePreset = 'balanced' | 'gamer' | 'remote_worker' | 'streamer';
+
+export interface OverallWeights {
+  /** null when the test gave its own priorities */
+  preset: UsagePreset | null;
+  /** Share of the overall score per use case, summing to 1 */
+  weights: Partial<Record<UseCase, number>>;
 }
 
 export interface AIMScores {
-  gaming: AIMScore;
-  streaming: AIMScore;
-  video_conferencing: AIMScore;
-  general_browsing: AIMScore;
+  use_cases: AIMScore[];
+  /** Empty when packet loss was not measured */
+  voice_calls?: VoiceQualityEstimate[];
   overall_score: number;
   overall_grade: string;
+  overall_weights?: OverallWeights;
+  /** Scoring version that produced these scores, e.g. "aim-v3" */
+  scoring_version?: string;
 }
 
 export interface AIRecommendation {
@@ -58,12 +126,116 @@ export interface AIInsights {
   recommendations: AIRecommendation[];
   predictions: string[];
   simple_explanation: string;
+  /** Who wrote the insights */
+  provider?: 'openai' | 'rules';
+}
+
+export interface ConsistencyScore {
+  coefficient_of_variation: number;
+  stability_grade:
+    | 'Excellent - Very stable'
+    | 'Good - Minor fluctuations'
+    | 'Fair - Noticeable variation'
+    | 'Poor - Highly unstable'
+    | 'Unknown';
+  mean_speed: number;
+  std_deviation: number;
+  measurements_range: [number, number];
+  samples: number;
+  median_speed?: number;
+  mad?: number;
+  iqr?: number;
+  p10_speed?: number;
+  p90_speed?: number;
+  robust_variation?: number;
+}
+
+export interface ThroughputEpisode {
+  kind: 'stall' | 'drop';
+  started_at: string;
+  start_ms: number;
+  duration_ms: number;
+  /** Time below the stall threshold; stall totals count only this */
+  stall_ms: number;
+  floor_mbps: number;
+  recovery_ms: number | null;
+}
+
+export interface StallAnalysis {
+  baseline_mbps: number;
+  stall_count: number;
+  total_stall_ms: number;
+  longest_stall_ms: number;
+  drop_count: number;
+  episodes: ThroughputEpisode[];
+  duration_ms: number;
+}
+
+export type ActivityKind = 'stream_4k' | 'hd_video_call' | 'gaming';
+
+export type CapacityLimit = 'download' | 'upload' | 'latency';
+
+export interface ActivityCapacity {
+  activity: ActivityKind;
+  label: string;
+  download_mbps_each: number;
+  upload_mbps_each: number;
+  latency_budget_ms: number | null;
+  max_sessions: number;
+  limited_by: CapacityLimit;
+}
+
+export interface ActivityMix {
+  streams_4k: number;
+  video_calls: number;
+  gaming_sessions: number;
+}
+
+export interface MixCheck {
+  mix: ActivityMix;
+  fits: boolean;
+  download_used_pct: number;
+  upload_used_pct: number;
+  limited_by: CapacityLimit | null;
+}
+
+export interface HouseholdCapacity {
+  download_budget_mbps: number;
+  upload_budget_mbps: number;
+  idle_latency_ms: number;
+  loaded_latency_ms: number;
+  bloated_utilization_pct: number;
+  activities: ActivityCapacity[];
+  suggested_mixes: MixCheck[];
+  summary: string;
+}
+
+export interface PlanEvaluation {
+  plan_id: string;
+  test_id: string;
+  timestamp: string;
+  server_id: string;
+  download_mbps: number;
+  upload_mbps: number;
+  latency_ms: number;
+  download_pct_of_plan: number;
+  upload_pct_of_plan: number;
+  latency_within_sla: boolean | null;
+  meets_plan: boolean;
+  issues: string[];
 }
 
 export interface EnhancedTestResult {
   basic: TestResult;
   loaded_latency: LoadedLatencyResult | null;
+  advice?: LatencyAdvice | null;
+  download_consistency?: ConsistencyScore | null;
+  upload_consistency?: ConsistencyScore | null;
+  download_stalls?: StallAnalysis | null;
+  upload_stalls?: StallAnalysis | null;
   aim_scores: AIMScores | null;
+  plan_compliance?: PlanEvaluation | null;
+  household_capacity?: HouseholdCapacity | null;
   ai_insights: AIInsights | null;
 }
 
@@ -82,4 +254,7 @@ export interface StartTestResponse {
   test_id: string;
   server_id: string;
   websocket_url: string;
+  udp_echo_port: number;
+  /** Must accompany every UDP echo probe for this test */
+  udp_secret: string;
 }